@@ -0,0 +1,187 @@
+//! Rough timings of the hot paths, run with `cargo bench`. There's no benchmarking crate, so each
+//! case is just run repeatedly and the mean time printed.
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use obsidian_calendar_summary::{
+    calendar::Calendar,
+    event::{Event, SourcedEvent},
+    query::EventQuery,
+};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    hint::black_box,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+/// Counts allocations so that the benchmarks can show how many each case makes
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn bench(name: &str, iterations: u32, mut f: impl FnMut()) {
+    f(); // warm up
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for _ in 0..iterations {
+        f();
+    }
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+    println!(
+        "{:<32} {:>12?} {:>8} allocations per iteration",
+        name,
+        elapsed / iterations,
+        allocations / iterations as usize
+    );
+}
+
+fn time(hour: u32, minute: u32) -> NaiveTime {
+    NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+}
+
+/// `count` events of every kind, spread across a couple of months
+fn calendar(count: usize, recurring_only: bool) -> Calendar {
+    let first = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+    let weekdays = [Weekday::Mon, Weekday::Wed, Weekday::Fri];
+    let events = (0..count)
+        .map(|i| {
+            let day = first + chrono::Days::new((i % 60) as u64);
+            let begin = time(8 + (i % 10) as u32, (i % 4) as u32 * 15);
+            let end = time(9 + (i % 10) as u32, (i % 4) as u32 * 15);
+            let title = format!("Event number {}", i).into();
+            let event = match i % 3 {
+                _ if recurring_only => Event::Recurring {
+                    title,
+                    begin,
+                    end,
+                    begin_recur: Some(first),
+                    end_recur: None,
+                    recur_days: vec![weekdays[i % 3]],
+                    skip_holidays: i % 2 == 0,
+                    travel_minutes: None,
+                    week_of_month: None,
+                },
+                0 => Event::Once {
+                    title,
+                    begin,
+                    end,
+                    day,
+                    travel_minutes: None,
+                    actual_end: None,
+                },
+                1 => Event::Recurring {
+                    title,
+                    begin,
+                    end,
+                    begin_recur: Some(first),
+                    end_recur: None,
+                    recur_days: weekdays.to_vec(),
+                    skip_holidays: false,
+                    travel_minutes: None,
+                    week_of_month: None,
+                },
+                _ => Event::AllDay {
+                    title,
+                    begin_date: day,
+                    end_date: day + chrono::Days::new(2),
+                    suppress_recurring: false,
+                },
+            };
+            SourcedEvent::new(event, format!("/vault/{}.md", i))
+        })
+        .collect();
+    let holidays = (0..20)
+        .map(|i| {
+            let day = first + chrono::Days::new(i * 5);
+            SourcedEvent::new(
+                Event::AllDay {
+                    title: format!("Holiday {}", i).into(),
+                    begin_date: day,
+                    end_date: day + chrono::Days::new(1),
+                    suppress_recurring: false,
+                },
+                "/holidays.ics",
+            )
+        })
+        .collect();
+    Calendar {
+        events,
+        holidays,
+        problems: Vec::new(),
+    }
+}
+
+fn main() {
+    let now = NaiveDateTime::new(NaiveDate::from_ymd_opt(2024, 5, 15).unwrap(), time(12, 0));
+
+    let big = calendar(5000, false);
+    bench("today, 5k events", 200, || {
+        black_box(EventQuery::today(now).apply(&big));
+    });
+
+    bench("copy 5k events", 200, || {
+        black_box(
+            big.events
+                .iter()
+                .map(|sourced| sourced.event.clone())
+                .collect::<Vec<Event>>(),
+        );
+    });
+
+    let recurring = calendar(1000, true);
+    bench("90 days, 1k recurring events", 20, || {
+        for date in now.date().iter_days().take(90) {
+            black_box(
+                EventQuery::new()
+                    .on_date(date)
+                    .skip_holidays(true)
+                    .apply(&recurring),
+            );
+        }
+    });
+
+    // every occurrence over a year, in order
+    let recurring = calendar(2000, true);
+    let year_end = now.date() + chrono::Days::new(365);
+    bench("year, 2k recurring, sorted", 5, || {
+        let mut all: Vec<(NaiveDateTime, &str, NaiveDate)> = recurring
+            .events
+            .iter()
+            .flat_map(|sourced| {
+                sourced
+                    .event
+                    .occurrences(now.date(), year_end)
+                    .map(move |date| {
+                        let begin = sourced
+                            .event
+                            .times_on(date)
+                            .map_or(date.and_time(NaiveTime::MIN), |(begin, _)| begin);
+                        (begin, sourced.event.title(), date)
+                    })
+            })
+            .collect();
+        all.sort_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)));
+        black_box(all.first());
+    });
+    bench("year, 2k recurring, merged", 5, || {
+        black_box(recurring.occurrences(now.date(), year_end).count());
+    });
+    bench("first of a year, 2k recurring", 5, || {
+        black_box(recurring.occurrences(now.date(), year_end).next());
+    });
+}
@@ -0,0 +1,21 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use obsidian_calendar_summary::parse::{parse_cal_file, AllDayEnd, WeekdayLocale};
+use std::path::Path;
+
+// parse_cal_file should only ever return Ok or Err, whatever it's given
+fuzz_target!(|data: &[u8]| {
+    if let Ok(contents) = std::str::from_utf8(data) {
+        for locale in [
+            WeekdayLocale::English,
+            WeekdayLocale::German,
+            WeekdayLocale::French,
+            WeekdayLocale::Spanish,
+        ] {
+            for allday_end in [AllDayEnd::Exclusive, AllDayEnd::Inclusive] {
+                let _ = parse_cal_file(contents, Path::new("fuzz.md"), locale, allday_end);
+            }
+        }
+    }
+});
@@ -0,0 +1,108 @@
+use crate::{
+    cli::Options,
+    error::Error,
+    event::Event,
+    parse::{calendar_notes, calendar_roots, parse_note},
+};
+use chrono::NaiveDate;
+use std::path::{Path, PathBuf};
+
+/// Whether the event is over before `before`: one-off events and deadlines on an earlier day,
+/// all-day events whose last day is earlier and recurring events whose `endRecur` is
+pub fn over_before(event: &Event, before: NaiveDate) -> bool {
+    match event {
+        Event::Once { day, .. } | Event::Deadline { due: day, .. } => *day < before,
+        // the end date is the day after the last day
+        Event::AllDay { end_date, .. } => *end_date <= before,
+        Event::Recurring { end_recur, .. } => end_recur.is_some_and(|end_recur| end_recur < before),
+    }
+}
+
+/// What `archive` is going to do
+#[derive(Debug, Default)]
+pub struct Plan {
+    /// each note to move, and where to
+    pub moves: Vec<(PathBuf, PathBuf)>,
+    /// why each note which might be over isn't moved: it can't be read, or there's already a note
+    /// of the same name in the archive
+    pub refused: Vec<String>,
+    /// how many notes have an event which isn't over yet
+    pub kept: usize,
+}
+
+/// Works out which notes in the calendar directories only have events which are over before
+/// `before`, and where in `dest` they go, in the same folders as they were in below their
+/// calendar directory. Notes which can't be read are left where they are, since there's no
+/// telling what's in them, and so are notes already in `dest`.
+pub fn plan(options: &Options, before: NaiveDate, dest: &Path) -> Result<Plan, Error> {
+    let mut plan = Plan::default();
+    let archive = std::fs::canonicalize(dest).ok();
+    let (roots, problems) = calendar_roots(&options.paths);
+    plan.refused
+        .extend(problems.iter().map(ToString::to_string));
+    for root in &roots {
+        // a note given by itself goes straight into `dest`
+        let base = if root.is_file() {
+            root.parent().unwrap_or(root)
+        } else {
+            root
+        };
+        for (note, folder) in calendar_notes(root, options.infer_category, &options.ignore)? {
+            if archive.as_ref().is_some_and(|archive| {
+                std::fs::canonicalize(&note).is_ok_and(|note| note.starts_with(archive))
+            }) {
+                continue;
+            }
+            let events = match parse_note(
+                note.clone(),
+                folder.as_deref(),
+                options.weekday_locale,
+                options.allday_end,
+                options.retry_unreadable,
+                options.relative_dates_today(),
+                options.strip_date_prefix,
+                options.filename_date,
+            ) {
+                Ok((events, _)) => events,
+                Err(e) => {
+                    plan.refused.push(e.to_string());
+                    continue;
+                }
+            };
+            if !events
+                .iter()
+                .all(|sourced| over_before(&sourced.event, before))
+            {
+                plan.kept += 1;
+                continue;
+            }
+            let target = dest.join(note.strip_prefix(base).unwrap_or(&note));
+            if target.exists() {
+                plan.refused.push(format!(
+                    "{}: {} already exists",
+                    note.display(),
+                    target.display()
+                ));
+            } else {
+                plan.moves.push((note, target));
+            }
+        }
+    }
+    Ok(plan)
+}
+
+/// Moves a note into the archive, making the folders it goes in
+pub fn move_note(from: &Path, to: &Path) -> Result<(), Error> {
+    let io = |path: &Path| {
+        let path = path.to_path_buf();
+        move |source| Error::Io { path, source }
+    };
+    if let Some(parent) = to.parent() {
+        std::fs::create_dir_all(parent).map_err(io(parent))?;
+    }
+    // renaming doesn't work across filesystems, so fall back to copying
+    std::fs::rename(from, to).or_else(|_| {
+        std::fs::copy(from, to).map_err(io(to))?;
+        std::fs::remove_file(from).map_err(io(from))
+    })
+}
@@ -0,0 +1,126 @@
+use crate::{
+    calendar::Calendar, cli::Options, event::Event, focus::gaps_between, lang::Lang,
+    render::json_string,
+};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime};
+
+/// The free time on one day
+pub struct Day {
+    pub date: NaiveDate,
+    pub slots: Vec<(NaiveDateTime, NaiveDateTime)>,
+}
+
+/// The times on `date` taken up by events. Timed events running past midnight from the day
+/// before count too, and vacations, or with `--allday-busy` any all-day event, take up the
+/// whole day.
+pub fn busy_on(
+    calendar: &Calendar,
+    options: &Options,
+    date: NaiveDate,
+) -> Vec<(NaiveDateTime, NaiveDateTime)> {
+    let midnight = date.and_time(NaiveTime::MIN);
+    let whole_day = (midnight, midnight + chrono::Days::new(1));
+    if calendar.vacation_on(date).is_some() {
+        return vec![whole_day];
+    }
+    let mut busy = Vec::new();
+    for sourced in calendar.events_on(date, options) {
+        match sourced.event {
+            Event::AllDay { .. } if options.allday_busy => busy.push(whole_day),
+            _ => busy.extend(sourced.event.times_on(date)),
+        }
+    }
+    if let Some(day_before) = date.pred_opt() {
+        busy.extend(
+            calendar
+                .events_on(day_before, options)
+                .iter()
+                .filter_map(|sourced| sourced.event.times_on(day_before))
+                .filter(|(_, end)| *end > midnight),
+        );
+    }
+    busy
+}
+
+/// The free time at least `--slot` long between `--day-start` and `--day-end` on each day from
+/// `--from` to `--to`, leaving out what's already over at `now`
+pub fn free_days(calendar: &Calendar, options: &Options, now: NaiveDateTime) -> Vec<Day> {
+    let first = options.avail_from.unwrap_or(now.date());
+    let last = options.avail_to.unwrap_or(first);
+    first
+        .iter_days()
+        .take_while(|date| *date <= last)
+        .map(|date| {
+            let midnight = date.and_time(NaiveTime::MIN);
+            let day_start = midnight + Duration::try_seconds(options.day_start).unwrap_or_default();
+            let day_end = midnight + Duration::try_seconds(options.day_end).unwrap_or_default();
+            let slots = gaps_between(
+                busy_on(calendar, options, date),
+                now.max(day_start),
+                day_end,
+            )
+            .into_iter()
+            .filter(|(begin, end)| *end - *begin >= options.slot)
+            .collect();
+            Day { date, slots }
+        })
+        .collect()
+}
+
+/// e.g. "09:00", or "24:00" for the midnight at the end of `date`
+fn clock(time: NaiveDateTime, date: NaiveDate) -> String {
+    if time.date() > date {
+        "24:00".into()
+    } else {
+        time.format("%H:%M").to_string()
+    }
+}
+
+/// A line for each day, e.g. "Mon May 06: 09:00–10:30, 13:00–17:00", to paste into a message
+pub fn text(days: &[Day], lang: &Lang) -> String {
+    let mut out = String::new();
+    for day in days {
+        let slots: Vec<String> = day
+            .slots
+            .iter()
+            .map(|(begin, end)| format!("{}–{}", clock(*begin, day.date), clock(*end, day.date)))
+            .collect();
+        out += &format!(
+            "{} {}: {}\n",
+            lang.weekday(day.date.weekday()),
+            lang.month_day(day.date),
+            if slots.is_empty() {
+                lang.no_free_time.clone()
+            } else {
+                slots.join(", ")
+            }
+        );
+    }
+    out
+}
+
+/// A JSON array with an object for each day, listing its free slots as local date-times
+pub fn json(days: &[Day]) -> String {
+    let objects: Vec<String> = days
+        .iter()
+        .map(|day| {
+            let slots: Vec<String> = day
+                .slots
+                .iter()
+                .map(|(begin, end)| {
+                    format!(
+                        "{{\"start\":{},\"end\":{}}}",
+                        json_string(&begin.format("%Y-%m-%dT%H:%M").to_string()),
+                        json_string(&end.format("%Y-%m-%dT%H:%M").to_string())
+                    )
+                })
+                .collect();
+            format!(
+                "{{\"date\":{},\"free\":[{}]}}",
+                json_string(&day.date.to_string()),
+                slots.join(",")
+            )
+        })
+        .collect();
+    format!("[{}]", objects.join(","))
+}
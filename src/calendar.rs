@@ -0,0 +1,308 @@
+use crate::{
+    cli::{Command, ErrorFormat, Options},
+    error::Error,
+    event::{Event, SourcedEvent},
+    ics,
+    index::parse_index,
+    parse::{calendar_roots, parse_events},
+    query::EventQuery,
+};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+/// Every event from the calendar directories, the remote sources and the holidays file
+#[derive(Debug)]
+pub struct Calendar {
+    pub events: Vec<SourcedEvent>,
+    pub holidays: Vec<SourcedEvent>,
+    /// the notes which couldn't be read and warnings about the rest, with `--errors json` or
+    /// `lint`
+    pub problems: Vec<Error>,
+}
+
+impl Calendar {
+    pub fn load(options: &Options) -> Result<Self, Error> {
+        let holidays = match &options.holidays {
+            Some(path) => ics::parse_ics(&std::fs::read_to_string(path).map_err(|source| {
+                Error::Holidays {
+                    path: path.clone(),
+                    source,
+                }
+            })?)
+            .into_iter()
+            .filter(|event| matches!(event, Event::AllDay { .. }))
+            .map(|event| SourcedEvent::new(event, path.clone()))
+            .collect(),
+            None => Vec::new(),
+        };
+        let mut events = Vec::new();
+        let (roots, mut problems) = calendar_roots(&options.paths);
+        for path in &roots {
+            let (notes, note_problems) = parse_events(
+                path,
+                options.weekday_locale,
+                options.allday_end,
+                options.infer_category,
+                options.retry_unreadable,
+                &options.ignore,
+                options.relative_dates_today(),
+                options.strip_date_prefix,
+                options.filename_date,
+            )?;
+            events.extend(notes);
+            problems.extend(note_problems);
+        }
+        if let Some(index) = &options.index {
+            let (notes, note_problems) = parse_index(
+                index,
+                options.weekday_locale,
+                options.allday_end,
+                options.retry_unreadable,
+                options.relative_dates_today(),
+                options.strip_date_prefix,
+                options.filename_date,
+            )?;
+            events.extend(notes);
+            problems.extend(note_problems);
+        }
+        // normally a broken note stops everything, so that it gets fixed, except for notes whose
+        // template hasn't been filled in yet, which are probably still being written, and notes
+        // which can't be read at all, which there's nothing to fix in
+        if options.errors == ErrorFormat::Text && options.command != Command::Lint {
+            // they were asked for, so they're only worth pointing out by `lint`
+            problems.retain(|problem| !problem.is_relative_date());
+            let (skipped, mut fatal): (Vec<Error>, Vec<Error>) =
+                problems.into_iter().partition(|problem| {
+                    (problem.is_skippable() || problem.is_warning()) && !options.strict
+                });
+            for problem in skipped {
+                if problem.is_warning() {
+                    eprintln!("Warning: {}", problem);
+                } else {
+                    eprintln!("Warning: skipping {}", problem);
+                }
+            }
+            if !fatal.is_empty() {
+                return Err(fatal.remove(0));
+            }
+            problems = Vec::new();
+        }
+        events.extend(
+            options
+                .sources
+                .iter()
+                .flat_map(|source| source.load(options.offline)),
+        );
+        Ok(Calendar {
+            events,
+            holidays,
+            problems,
+        })
+    }
+
+    pub fn is_holiday(&self, date: NaiveDate) -> bool {
+        self.holidays
+            .iter()
+            .any(|holiday| holiday.event.occurs_on(date))
+    }
+
+    /// The all-day events marked `suppressRecurring: true`, during which recurring events are
+    /// hidden. Hidden vacations, like someone else's, don't count.
+    pub fn vacations(&self) -> impl Iterator<Item = &SourcedEvent> {
+        self.events.iter().filter(|sourced| {
+            !sourced.hidden
+                && matches!(
+                    sourced.event,
+                    Event::AllDay {
+                        suppress_recurring: true,
+                        ..
+                    }
+                )
+        })
+    }
+
+    /// The vacation on `date` which hides recurring events, if there is one
+    pub fn vacation_on(&self, date: NaiveDate) -> Option<&SourcedEvent> {
+        self.vacations()
+            .find(|vacation| vacation.event.occurs_on(date))
+    }
+
+    /// Whether the event is shown on `date`, which is when it occurs on that day unless it's a
+    /// recurring event skipped on holidays or during a vacation
+    pub fn shown_on(&self, event: &Event, date: NaiveDate, skip_holidays: bool) -> bool {
+        event.occurs_on(date)
+            && !skipped_on_holiday(event, self.is_holiday(date), skip_holidays)
+            && !suppressed_by_vacation(event, self.vacation_on(date).is_some())
+    }
+
+    /// Which occurrence of a recurring event with a `startRecur` the one on `date` is, counting
+    /// from 1 and only counting the days it was shown on, so that one cancelled by a holiday or a
+    /// vacation doesn't take up a number. None if it isn't shown on `date`.
+    pub fn occurrence_number(
+        &self,
+        event: &Event,
+        date: NaiveDate,
+        skip_holidays: bool,
+    ) -> Option<usize> {
+        let Event::Recurring {
+            begin_recur: Some(begin_recur),
+            ..
+        } = event
+        else {
+            return None;
+        };
+        self.shown_on(event, date, skip_holidays).then(|| {
+            event
+                .occurrences(*begin_recur, date)
+                .filter(|date| self.shown_on(event, *date, skip_holidays))
+                .count()
+        })
+    }
+
+    /// The events shown on `date` which aren't removed by `--no-allday`/`--allday-only`, in the
+    /// order they're listed
+    pub fn events_on(&self, date: NaiveDate, options: &Options) -> Vec<&SourcedEvent> {
+        EventQuery::new()
+            .on_date(date)
+            .with_options(options)
+            .apply(self)
+    }
+
+    /// The first event on the first day after `date` which has any, looking up to a year ahead
+    pub fn next_occurrence(
+        &self,
+        date: NaiveDate,
+        options: &Options,
+    ) -> Option<(NaiveDate, &SourcedEvent)> {
+        date.iter_days()
+            .skip(1)
+            .take(366)
+            .find_map(|date| Some((date, *self.events_on(date, options).first()?)))
+    }
+
+    /// Every occurrence of every event from `from` to `to` (inclusive), in order. See
+    /// `Occurrences`.
+    pub fn occurrences(&self, from: NaiveDate, to: NaiveDate) -> Occurrences<'_> {
+        Occurrences::new(&self.events, from, to)
+    }
+
+    /// The times between `from` and `to` at which events begin which don't exist in `zone`,
+    /// because the clocks go forward past them, earliest first. Events are still shown at those
+    /// times, since they're kept in local time, but they probably need fixing. Hidden events are
+    /// left out.
+    pub fn nonexistent_times<'a, Tz: TimeZone>(
+        &'a self,
+        zone: &'a Tz,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> impl Iterator<Item = (&'a SourcedEvent, NaiveDateTime)> + 'a {
+        self.occurrences(from, to)
+            .filter(|(sourced, _)| !sourced.hidden)
+            .filter_map(|(sourced, date)| Some((sourced, sourced.event.times_on(date)?.0)))
+            .filter(|(_, begin)| zone.from_local_datetime(begin).earliest().is_none())
+    }
+
+    /// The recurring events which are probably copies of an earlier one in another note, each
+    /// with the one it's a copy of. Hidden events are left out, since they're often copies of
+    /// someone else's events on purpose.
+    pub fn duplicate_recurrences(&self) -> Vec<(&SourcedEvent, &SourcedEvent)> {
+        self.events
+            .iter()
+            .enumerate()
+            .filter(|(_, sourced)| !sourced.hidden)
+            .filter_map(|(i, sourced)| {
+                let original = self.events[..i].iter().find(|earlier| {
+                    !earlier.hidden
+                        && earlier.path != sourced.path
+                        && earlier.event.same_recurrence(&sourced.event)
+                })?;
+                Some((sourced, original))
+            })
+            .collect()
+    }
+
+    pub fn all(&self) -> impl Iterator<Item = &SourcedEvent> {
+        self.events.iter().chain(&self.holidays)
+    }
+}
+
+/// When an occurrence on `date` begins, with all-day events and deadlines at the start of the day
+fn begins_at(sourced: &SourcedEvent, date: NaiveDate) -> NaiveDateTime {
+    sourced
+        .event
+        .times_on(date)
+        .map_or(date.and_time(NaiveTime::MIN), |(begin, _)| begin)
+}
+
+/// The occurrences of some events over a range of days, each with the day it's on, in order of
+/// when they begin and then by title, with events that still tie in the order they were read.
+/// Each event's occurrences are already in order, so they're merged rather than collected and
+/// sorted: only the next occurrence of each event is held at a time, however long the range is,
+/// and the first ones come out without looking at the rest.
+pub struct Occurrences<'a> {
+    events: Vec<(&'a SourcedEvent, Box<dyn Iterator<Item = NaiveDate> + 'a>)>,
+    /// the next occurrence of each event which has any left, by when it begins, its title and
+    /// which event it is
+    next: BinaryHeap<Reverse<(NaiveDateTime, &'a str, usize, NaiveDate)>>,
+}
+
+impl<'a> Occurrences<'a> {
+    pub fn new(
+        events: impl IntoIterator<Item = &'a SourcedEvent>,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Self {
+        let mut occurrences = Occurrences {
+            events: events
+                .into_iter()
+                .map(|sourced| {
+                    let dates: Box<dyn Iterator<Item = NaiveDate>> =
+                        Box::new(sourced.event.occurrences(from, to));
+                    (sourced, dates)
+                })
+                .collect(),
+            next: BinaryHeap::new(),
+        };
+        for i in 0..occurrences.events.len() {
+            occurrences.advance(i);
+        }
+        occurrences
+    }
+
+    /// Queues the next occurrence of the `i`th event, if it has one
+    fn advance(&mut self, i: usize) {
+        let (sourced, dates) = &mut self.events[i];
+        if let Some(date) = dates.next() {
+            self.next.push(Reverse((
+                begins_at(sourced, date),
+                sourced.event.title(),
+                i,
+                date,
+            )));
+        }
+    }
+}
+
+impl<'a> Iterator for Occurrences<'a> {
+    type Item = (&'a SourcedEvent, NaiveDate);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Reverse((_, _, i, date)) = self.next.pop()?;
+        self.advance(i);
+        Some((self.events[i].0, date))
+    }
+}
+
+/// Whether the event is hidden because it's a recurring event during a vacation
+pub fn suppressed_by_vacation(event: &Event, on_vacation: bool) -> bool {
+    on_vacation && matches!(event, Event::Recurring { .. })
+}
+
+/// Whether the event is hidden because it's a recurring event on a holiday and either it or the
+/// config (`skip_all`) says to skip holidays
+pub fn skipped_on_holiday(event: &Event, is_holiday: bool, skip_all: bool) -> bool {
+    match event {
+        Event::Recurring { skip_holidays, .. } => is_holiday && (*skip_holidays || skip_all),
+        _ => false,
+    }
+}
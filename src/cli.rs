@@ -0,0 +1,975 @@
+use crate::{
+    config::Config,
+    error::Error,
+    event::Event,
+    glob::Glob,
+    lang::Lang,
+    month::{parse_month, parse_week_start},
+    output::Destination,
+    parse::{parse_time, AllDayEnd, WeekdayLocale},
+    remote::Source,
+    theme::{Palette, Theme},
+    timeline::parse_clock,
+    week::{parse_iso_week, week_containing},
+    SortSpec,
+};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// Where all-day events are placed relative to timed events in the output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AllDayPosition {
+    Top,
+    Bottom,
+    /// sort all-day events as if they started at 00:00
+    Inline,
+}
+
+impl FromStr for AllDayPosition {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "top" => Ok(Self::Top),
+            "bottom" => Ok(Self::Bottom),
+            "inline" => Ok(Self::Inline),
+            _ => Err(Error::Usage(format!(
+                "Unknown all-day position '{}' (expected top, bottom or inline)",
+                s
+            ))),
+        }
+    }
+}
+
+/// Which time timed events are listed in order of
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortBy {
+    Start,
+    /// what finishes first, with the countdown to when each event ends
+    End,
+}
+
+impl FromStr for SortBy {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "start" => Ok(Self::Start),
+            "end" => Ok(Self::End),
+            _ => Err(Error::Usage(format!(
+                "Unknown sort '{}' (expected start or end)",
+                s
+            ))),
+        }
+    }
+}
+
+/// How events which start (or with `SortBy::End`, end) at the same time are ordered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tiebreak {
+    Title,
+    /// the most recently edited note first
+    Mtime,
+    Path,
+}
+
+impl FromStr for Tiebreak {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "title" => Ok(Self::Title),
+            "mtime" => Ok(Self::Mtime),
+            "path" => Ok(Self::Path),
+            _ => Err(Error::Usage(format!(
+                "Unknown tiebreak '{}' (expected mtime, title or path)",
+                s
+            ))),
+        }
+    }
+}
+
+/// Which events are kept based on whether they are all-day events
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AllDayFilter {
+    Show,
+    Hide,
+    Only,
+}
+
+impl AllDayFilter {
+    pub fn keep(&self, event: &Event) -> bool {
+        let is_allday = matches!(event, Event::AllDay { .. });
+        match self {
+            Self::Show => true,
+            Self::Hide => !is_allday,
+            Self::Only => is_allday,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    /// a single line coloured with tmux style codes, for use in the status line
+    Tmux,
+    /// a single line with Polybar formatting tags
+    Polybar,
+    /// the i3blocks protocol: full text, short text and colour on separate lines
+    I3blocks,
+    /// a JSON array of events
+    Json,
+    /// every event in remind(1) syntax
+    Remind,
+    /// every event as a task for `task import`
+    Taskwarrior,
+    /// every event as an org-mode headline
+    Org,
+    /// just `busy`, `soon` or `free`
+    Status,
+    /// a single short line of plain ASCII, e.g. `0900-0915 Standup;AD Conference`, for QR codes
+    Compact,
+}
+
+impl FromStr for Format {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Self::Text),
+            "tmux" => Ok(Self::Tmux),
+            "polybar" => Ok(Self::Polybar),
+            "i3blocks" => Ok(Self::I3blocks),
+            "json" => Ok(Self::Json),
+            "remind" => Ok(Self::Remind),
+            "taskwarrior" => Ok(Self::Taskwarrior),
+            "org" => Ok(Self::Org),
+            "status" => Ok(Self::Status),
+            "compact" => Ok(Self::Compact),
+            _ => Err(Error::Usage(format!(
+                "Unknown format '{}' (expected text, tmux, polybar, i3blocks, json, status, compact, remind, taskwarrior or org)",
+                s
+            ))),
+        }
+    }
+}
+
+/// How problems with the notes are reported on stderr
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorFormat {
+    /// a message for the first broken note, which stops the program
+    Text,
+    /// a JSON object listing every broken note, which are skipped
+    Json,
+}
+
+impl FromStr for ErrorFormat {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(Error::Usage(format!(
+                "Unknown error format '{}' (expected text or json)",
+                s
+            ))),
+        }
+    }
+}
+
+/// Which Saturdays and Sundays the week view lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Weekends {
+    Show,
+    /// only those with events
+    SkipEmpty,
+    Hide,
+}
+
+/// What the program has been asked to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    /// list the rest of today's events
+    Today,
+    /// draw a calendar of a month, defaulting to the current one
+    Month(Option<NaiveDate>),
+    /// list the events on each day of a week
+    Week,
+    /// print the JSON Schema of the event format in `schema.rs`
+    Schema,
+    /// list every note which can't be read
+    Lint,
+    /// hide an event for the rest of the day, or manage the hidden events
+    Snooze,
+    /// move the notes of events which are over into an archive folder
+    Archive,
+    /// list the free time on each of a range of days
+    Avail,
+    /// open the link of the ongoing or next event which has one
+    OpenNext,
+    /// a plain-text digest of a week, e.g. to send by email
+    Digest,
+}
+
+/// What `snooze` does
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnoozeAction {
+    /// hide the events with this title, or in this note, for the rest of today
+    Add(String),
+    /// list what's snoozed today
+    List,
+    /// unsnooze everything
+    Clear,
+}
+
+#[derive(Debug)]
+pub struct Options {
+    pub command: Command,
+    pub paths: Vec<String>,
+    /// notes in the calendar directories which aren't read, from `--ignore` and the config file
+    pub ignore: Vec<Glob>,
+    pub sort: SortSpec,
+    pub allday_filter: AllDayFilter,
+    pub format: Format,
+    /// join all events onto one line
+    pub oneline: bool,
+    /// print the date, the week and how many events there are above the list
+    pub header: bool,
+    pub separator: String,
+    /// events starting within this many minutes are highlighted as imminent
+    pub soon: i64,
+    /// truncate single-line formats to this many characters
+    pub max_width: Option<usize>,
+    /// cut titles down to this many characters in `--format compact`
+    pub max_title: Option<usize>,
+    /// print `--format json` as one object per line for each event instead of an array
+    pub json_lines: bool,
+    /// end `--json-lines` with an object giving the number of events and when it was made
+    pub summary: bool,
+    /// make events clickable in polybar, opening their note in Obsidian
+    pub click: bool,
+    /// follow `--format status` with the title of the event that's on or coming up
+    pub status_with_title: bool,
+    /// the config file given with `--config`, instead of the default location
+    pub config: Option<String>,
+    /// remote calendars from the config file
+    pub sources: Vec<Source>,
+    /// use the cached copy of remote calendars instead of downloading them
+    pub offline: bool,
+    /// an ICS file of public holidays, which are shown as all-day events
+    pub holidays: Option<PathBuf>,
+    /// hide every recurring event on holidays, not just those with `skipHolidays: true`
+    pub skip_holidays: bool,
+    /// show recurring events which are the same as one in another note only once
+    pub dedup_fuzzy: bool,
+    /// only print the time until the next timed event
+    pub next_in: bool,
+    /// write `--next-in` as e.g. "1h 20m" instead of a number of minutes
+    pub humanize: bool,
+    /// printed instead of nothing when there are no events left
+    pub empty_text: Option<String>,
+    /// when there are no events left today, show the first event of the next day which has any
+    pub show_next_day: bool,
+    /// include the events from notes with `hidden: true`
+    pub show_hidden: bool,
+    /// show deadlines this many days before they're due
+    pub deadline_lookahead: u64,
+    /// keep showing deadlines once they're past
+    pub show_overdue: bool,
+    /// `lint` prints a tab-separated line for each problem, and only fails for errors
+    pub porcelain: bool,
+    /// print to stderr which sort key put each pair of events next to each other in order
+    pub explain_sort: bool,
+    /// name other days as e.g. "Thu" rather than "Thu 16 May" when they're within the week
+    pub relative_dates: bool,
+    /// show timed events' times widened out to a multiple of this
+    pub round: Option<chrono::Duration>,
+    /// where the output goes, which is stdout unless it's `--output`
+    pub output: Destination,
+    /// keep timed events this long after they end
+    pub grace: Option<chrono::Duration>,
+    /// hide timed events which end by this time, unless they're ongoing
+    pub from: Option<NaiveTime>,
+    /// hide timed events which start after this time, unless they're ongoing
+    pub until: Option<NaiveTime>,
+    /// hide timed events shorter than this
+    pub min_duration: Option<chrono::Duration>,
+    /// show how far through the ongoing event we are
+    pub progress: bool,
+    /// show how long each event lasts
+    pub show_duration: bool,
+    /// count down to the second, for displays which refresh often
+    pub seconds: bool,
+    /// show e.g. "(3 of 10)" after events which recur a fixed number of times
+    pub show_occurrence_index: bool,
+    /// draw the day as a timeline instead of listing the events
+    pub timeline: bool,
+    /// the start and end of the timeline, in seconds since midnight
+    pub day_start: i64,
+    pub day_end: i64,
+    /// the width of the timeline, defaulting to the width of the terminal
+    pub width: Option<usize>,
+    pub week_start: Weekday,
+    pub weekends: Weekends,
+    /// list the all-day events below the month calendar
+    pub spans: bool,
+    /// draw how busy each hour of each weekday is over the month instead of its calendar
+    pub heatmap: bool,
+    /// list recurring events once in the week view, with the days they're on
+    pub collapse_recurring: bool,
+    /// the Monday of the ISO week to show in the week view and `digest`, instead of the current
+    /// week
+    pub week_of: Option<NaiveDate>,
+    /// which language's abbreviations are accepted in `daysOfWeek`
+    pub weekday_locale: WeekdayLocale,
+    /// whether notes' all-day `endDate` is the last day of the event or the day after
+    pub allday_end: AllDayEnd,
+    /// how many days ahead the export formats list the days of recurring events they can't
+    /// express as a recurrence
+    pub horizon: u64,
+    pub errors: ErrorFormat,
+    /// fill free gaps at least this long with "Focus block" events
+    pub suggest_focus: Option<chrono::Duration>,
+    /// list made-up events like focus blocks in `--format json` too
+    pub include_synthetic: bool,
+    /// stop at notes with unexpanded template placeholders or which can't be read too, instead of
+    /// skipping them
+    pub strict: bool,
+    /// try notes which can't be read once more after a moment
+    pub retry_unreadable: bool,
+    /// the time to show the events as of, instead of the current time
+    pub now: Option<NaiveDateTime>,
+    /// read the folders below the calendar directories too, and give their notes the folder's
+    /// name as their category
+    pub infer_category: bool,
+    /// read dates like `tomorrow` in notes relative to today, instead of them being errors
+    pub allow_relative_dates: bool,
+    /// call events without a title or alias named after notes like `2024-05-03 Dentist.md` just
+    /// `Dentist`
+    pub strip_date_prefix: bool,
+    /// put events without a `date` in notes named like `2024-05-03 Dentist.md` on that day
+    pub filename_date: bool,
+    /// a note whose wiki-links lead to the notes to read, anywhere in its vault
+    pub index: Option<PathBuf>,
+    /// what to do with the `snooze` command
+    pub snooze: Option<SnoozeAction>,
+    /// `archive` moves the notes of events which are over before this day
+    pub archive_before: Option<NaiveDate>,
+    /// where `archive` moves them to
+    pub archive_dest: Option<PathBuf>,
+    /// print what `archive` would move without moving anything, or the link `open-next` would
+    /// open without opening it
+    pub dry_run: bool,
+    /// the first and last days `avail` lists, defaulting to today
+    pub avail_from: Option<NaiveDate>,
+    pub avail_to: Option<NaiveDate>,
+    /// the shortest free time `avail` lists
+    pub slot: chrono::Duration,
+    /// all-day events take up the whole day for `avail`, not just vacations
+    pub allday_busy: bool,
+    /// print how the events have changed since the snapshot in this file, then update it
+    pub diff_state: Option<PathBuf>,
+    /// don't print the events, e.g. so that only the changes from `--diff-state` are printed
+    pub quiet: bool,
+    /// the words the output is written in
+    pub lang: Lang,
+    /// the colours the coloured formats highlight events with, from `--theme` and the config file
+    pub palette: Palette,
+}
+
+/// How many days `--offset` can go either way, about ten years
+const MAX_OFFSET: i64 = 3650;
+
+fn parse_number<T: FromStr>(flag: &str, value: String) -> Result<T, Error> {
+    value
+        .parse()
+        .map_err(|_| Error::Usage(format!("'{}' expects a number, not '{}'", flag, value)))
+}
+
+/// Parses a length of time like "50m", "2h" or "1h30m", or a bare number of minutes
+fn parse_duration_flag(flag: &str, value: String) -> Result<chrono::Duration, Error> {
+    let error = || {
+        Error::Usage(format!(
+            "'{}' expects a length of time like 50m or 1h30m, not '{}'",
+            flag, value
+        ))
+    };
+    if let Ok(minutes) = value.parse::<u32>() {
+        return chrono::Duration::try_minutes(minutes.into()).ok_or_else(error);
+    }
+    let mut minutes: i64 = 0;
+    let mut number = String::new();
+    for c in value.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'h' | 'm' if !number.is_empty() => {
+                let n: i64 = number.parse().map_err(|_| error())?;
+                minutes = minutes.saturating_add(if c == 'h' { n.saturating_mul(60) } else { n });
+                number.clear();
+            }
+            _ => return Err(error()),
+        }
+    }
+    if !number.is_empty() || value.is_empty() {
+        return Err(error());
+    }
+    chrono::Duration::try_minutes(minutes).ok_or_else(error)
+}
+
+fn parse_datetime_flag(flag: &str, value: String) -> Result<NaiveDateTime, Error> {
+    ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(&value, format).ok())
+        .ok_or_else(|| {
+            Error::Usage(format!(
+                "'{}' expects a date and time like 2024-05-03T09:30, not '{}'",
+                flag, value
+            ))
+        })
+}
+
+fn parse_time_flag(flag: &str, value: String) -> Result<i64, Error> {
+    parse_clock(&value).map_err(|_| {
+        Error::Usage(format!(
+            "'{}' expects a time like 09:30, not '{}'",
+            flag, value
+        ))
+    })
+}
+
+/// Parses a time of day like 18:00 for `--from` and `--until`, which unlike `--day-end` can't be
+/// 24:00
+fn parse_time_of_day(value: &str) -> Option<NaiveTime> {
+    parse_time(value).ok()
+}
+
+fn parse_time_of_day_flag(flag: &str, value: String) -> Result<NaiveTime, Error> {
+    parse_time_of_day(&value).ok_or_else(|| {
+        Error::Usage(format!(
+            "'{}' expects a time like 18:00, not '{}'",
+            flag, value
+        ))
+    })
+}
+
+/// The directories listed in a `--dirs-from` file, one to a line, leaving out blank lines and
+/// `#` comments. Relative paths are relative to `base`, the folder the list is in.
+pub fn parse_dir_list(contents: &str, base: &Path) -> Vec<String> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| base.join(line).to_string_lossy().into_owned())
+        .collect()
+}
+
+/// Reads the list of directories for `--dirs-from` from `file`, or stdin if it's `-`
+fn read_dir_list(file: &str) -> Result<Vec<String>, Error> {
+    if file == "-" {
+        let contents = std::io::read_to_string(std::io::stdin()).map_err(|source| Error::Io {
+            path: PathBuf::from("-"),
+            source,
+        })?;
+        return Ok(parse_dir_list(&contents, Path::new("")));
+    }
+    let path = Path::new(file);
+    let contents = std::fs::read_to_string(path).map_err(|source| Error::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    Ok(parse_dir_list(
+        &contents,
+        path.parent().unwrap_or(Path::new("")),
+    ))
+}
+
+impl Options {
+    /// The day relative dates in notes are read relative to, with `--allow-relative-dates`
+    pub fn relative_dates_today(&self) -> Option<NaiveDate> {
+        self.allow_relative_dates.then(|| {
+            self.now
+                .unwrap_or_else(|| chrono::Local::now().naive_local())
+                .date()
+        })
+    }
+
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, Error> {
+        let mut options = Options {
+            command: Command::Today,
+            paths: Vec::new(),
+            ignore: Vec::new(),
+            sort: SortSpec::default(),
+            allday_filter: AllDayFilter::Show,
+            format: Format::Text,
+            oneline: false,
+            header: false,
+            separator: " | ".into(),
+            soon: 15,
+            max_width: None,
+            max_title: None,
+            json_lines: false,
+            summary: false,
+            click: false,
+            status_with_title: false,
+            config: None,
+            sources: Vec::new(),
+            offline: false,
+            holidays: None,
+            skip_holidays: false,
+            dedup_fuzzy: false,
+            next_in: false,
+            humanize: false,
+            empty_text: None,
+            show_next_day: false,
+            show_hidden: false,
+            explain_sort: false,
+            porcelain: false,
+            deadline_lookahead: 0,
+            show_overdue: false,
+            relative_dates: false,
+            round: None,
+            output: Destination::Stdout,
+            grace: None,
+            from: None,
+            until: None,
+            min_duration: None,
+            progress: false,
+            show_duration: false,
+            seconds: false,
+            show_occurrence_index: false,
+            timeline: false,
+            day_start: 8 * 60 * 60,
+            day_end: 20 * 60 * 60,
+            width: None,
+            week_start: Weekday::Mon,
+            weekends: Weekends::Show,
+            spans: false,
+            heatmap: false,
+            collapse_recurring: false,
+            week_of: None,
+            weekday_locale: WeekdayLocale::English,
+            allday_end: AllDayEnd::Exclusive,
+            horizon: 30,
+            errors: ErrorFormat::Text,
+            strict: false,
+            retry_unreadable: false,
+            suggest_focus: None,
+            include_synthetic: false,
+            now: None,
+            infer_category: false,
+            allow_relative_dates: false,
+            strip_date_prefix: false,
+            filename_date: true,
+            index: None,
+            snooze: None,
+            archive_before: None,
+            archive_dest: None,
+            dry_run: false,
+            avail_from: None,
+            avail_to: None,
+            slot: chrono::Duration::try_minutes(30).unwrap_or_default(),
+            allday_busy: false,
+            diff_state: None,
+            quiet: false,
+            lang: Lang::english(),
+            palette: Palette::default(),
+        };
+        let mut weekday_locale = None;
+        let mut allday_end = None;
+        let mut infer_category = false;
+        let mut allday_position = None;
+        let mut lang: Option<Lang> = None;
+        let mut theme: Option<Theme> = None;
+        let mut listed_dirs = Vec::new();
+        let mut offset: i64 = 0;
+        let mut allday_busy = false;
+        let mut append = false;
+        // `--week-of this` or `next`, which depend on `--now` and `--offset`
+        let mut weeks_ahead: Option<u64> = None;
+        while let Some(arg) = args.next() {
+            // accept both `--flag value` and `--flag=value`
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) if arg.starts_with("--") => {
+                    (flag.to_string(), Some(value.into()))
+                }
+                _ => (arg, None),
+            };
+            let mut value = || {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or(Error::Usage(format!("'{}' requires a value", flag)))
+            };
+            match flag.as_str() {
+                "--allday-position" => allday_position = Some(value()?.parse()?),
+                "--sort" => options.sort.by = value()?.parse()?,
+                "--reverse" => options.sort.reverse = true,
+                "--tiebreak" => options.sort.tiebreak = value()?.parse()?,
+                "--no-allday" | "--allday-only" => {
+                    let filter = if flag == "--no-allday" {
+                        AllDayFilter::Hide
+                    } else {
+                        AllDayFilter::Only
+                    };
+                    if options.allday_filter != AllDayFilter::Show
+                        && options.allday_filter != filter
+                    {
+                        return Err(Error::Usage(
+                            "'--no-allday' and '--allday-only' cannot be used together".into(),
+                        ));
+                    }
+                    options.allday_filter = filter;
+                }
+                "--format" => options.format = value()?.parse()?,
+                "--oneline" => options.oneline = true,
+                "--header" => options.header = true,
+                "--separator" => {
+                    options.separator = value()?;
+                    options.oneline = true;
+                }
+                "--soon" => options.soon = parse_number(&flag, value()?)?,
+                "--max-width" => options.max_width = Some(parse_number(&flag, value()?)?),
+                "--max-title" => options.max_title = Some(parse_number(&flag, value()?)?),
+                "--json-lines" => options.json_lines = true,
+                "--summary" => options.summary = true,
+                "--click" => options.click = true,
+                "--status-with-title" => options.status_with_title = true,
+                "--config" => options.config = Some(value()?),
+                "--offline" => options.offline = true,
+                "--dedup-fuzzy" => options.dedup_fuzzy = true,
+                "--next-in" => options.next_in = true,
+                "--humanize" => options.humanize = true,
+                "--empty-text" => options.empty_text = Some(value()?),
+                "--show-next-day" => options.show_next_day = true,
+                "--show-hidden" => options.show_hidden = true,
+                "--explain-sort" => options.explain_sort = true,
+                "--porcelain" => options.porcelain = true,
+                "--deadline-lookahead" => {
+                    options.deadline_lookahead = parse_number(&flag, value()?)?
+                }
+                "--show-overdue" => options.show_overdue = true,
+                "--relative-dates" => options.relative_dates = true,
+                "--output" => options.output = Destination::File(value()?.into()),
+                "--append" => append = true,
+                "--round" => options.round = Some(parse_duration_flag(&flag, value()?)?),
+                "--grace" => options.grace = Some(parse_duration_flag(&flag, value()?)?),
+                // days rather than times of day for `avail`
+                "--from" | "--to" if options.command == Command::Avail => {
+                    let value = value()?;
+                    let date = value.parse().map_err(|_| {
+                        Error::Usage(format!(
+                            "'{}' expects a date like 2024-05-06, not '{}'",
+                            flag, value
+                        ))
+                    })?;
+                    if flag == "--from" {
+                        options.avail_from = Some(date);
+                    } else {
+                        options.avail_to = Some(date);
+                    }
+                }
+                "--from" => options.from = Some(parse_time_of_day_flag(&flag, value()?)?),
+                "--until" => options.until = Some(parse_time_of_day_flag(&flag, value()?)?),
+                "--min-duration" => {
+                    options.min_duration = Some(parse_duration_flag(&flag, value()?)?)
+                }
+                "--progress" => options.progress = true,
+                "--show-duration" => options.show_duration = true,
+                "--seconds" => options.seconds = true,
+                "--show-occurrence-index" => options.show_occurrence_index = true,
+                "--timeline" => options.timeline = true,
+                "--day-start" => options.day_start = parse_time_flag(&flag, value()?)?,
+                "--day-end" => options.day_end = parse_time_flag(&flag, value()?)?,
+                "--width" => options.width = Some(parse_number(&flag, value()?)?),
+                "--week-start" => {
+                    let value = value()?;
+                    options.week_start = parse_week_start(&value).ok_or(Error::Usage(format!(
+                        "Unknown week start '{}' (expected mon or sun)",
+                        value
+                    )))?
+                }
+                "--spans" => options.spans = true,
+                "--heatmap" => options.heatmap = true,
+                "--collapse-recurring" => options.collapse_recurring = true,
+                "--skip-weekends" => options.weekends = Weekends::SkipEmpty,
+                "--hide-weekends" => options.weekends = Weekends::Hide,
+                "--week-of" => match value()?.as_str() {
+                    "this" => weeks_ahead = Some(0),
+                    "next" => weeks_ahead = Some(1),
+                    value => options.week_of = Some(parse_iso_week(value)?),
+                },
+                "--errors" => options.errors = value()?.parse()?,
+                "--strict" => options.strict = true,
+                "--retry-unreadable" => options.retry_unreadable = true,
+                "--suggest-focus" => {
+                    options.suggest_focus = Some(parse_duration_flag(&flag, value()?)?)
+                }
+                "--include-synthetic" => options.include_synthetic = true,
+                "--horizon" => options.horizon = parse_number(&flag, value()?)?,
+                "--weekday-locale" => weekday_locale = Some(value()?.parse()?),
+                "--allday-end" => allday_end = Some(value()?.parse()?),
+                "--now" => options.now = Some(parse_datetime_flag(&flag, value()?)?),
+                "--offset" => offset = parse_number(&flag, value()?)?,
+                "--infer-category-from-dir" => infer_category = true,
+                "--allow-relative-dates" => options.allow_relative_dates = true,
+                "--strip-date-prefix" => options.strip_date_prefix = true,
+                "--no-filename-date" => options.filename_date = false,
+                "--ignore" => options.ignore.push(Glob::new(&value()?)?),
+                "--dirs-from" => listed_dirs.extend(read_dir_list(&value()?)?),
+                "--index" => options.index = Some(PathBuf::from(value()?)),
+                "--diff-state" => options.diff_state = Some(PathBuf::from(value()?)),
+                "--quiet" => options.quiet = true,
+                "--lang" => lang = Some(value()?.parse()?),
+                "--theme" => theme = Some(value()?.parse()?),
+                "--list" | "--clear" if options.command == Command::Snooze => {
+                    if options.snooze.is_some() {
+                        return Err(Error::Usage(
+                            "'snooze' takes one of an event, '--list' or '--clear'".into(),
+                        ));
+                    }
+                    options.snooze = Some(if flag == "--list" {
+                        SnoozeAction::List
+                    } else {
+                        SnoozeAction::Clear
+                    });
+                }
+                "--before" if options.command == Command::Archive => {
+                    let value = value()?;
+                    options.archive_before = Some(value.parse().map_err(|_| {
+                        Error::Usage(format!(
+                            "'--before' expects a date like 2024-01-01, not '{}'",
+                            value
+                        ))
+                    })?)
+                }
+                "--dest" if options.command == Command::Archive => {
+                    options.archive_dest = Some(PathBuf::from(value()?))
+                }
+                "--dry-run"
+                    if options.command == Command::Archive
+                        || options.command == Command::OpenNext =>
+                {
+                    options.dry_run = true
+                }
+                "--slot" if options.command == Command::Avail => {
+                    options.slot = parse_duration_flag(&flag, value()?)?
+                }
+                "--allday-busy" if options.command == Command::Avail => allday_busy = true,
+                _ if flag.starts_with("--") => {
+                    return Err(Error::Usage(format!("Unknown option '{}'", flag)))
+                }
+                "month" if options.command == Command::Today && options.paths.is_empty() => {
+                    options.command = Command::Month(None)
+                }
+                "week" if options.command == Command::Today && options.paths.is_empty() => {
+                    options.command = Command::Week
+                }
+                "schema" if options.command == Command::Today && options.paths.is_empty() => {
+                    options.command = Command::Schema
+                }
+                "lint" if options.command == Command::Today && options.paths.is_empty() => {
+                    options.command = Command::Lint
+                }
+                "snooze" if options.command == Command::Today && options.paths.is_empty() => {
+                    options.command = Command::Snooze
+                }
+                "archive" if options.command == Command::Today && options.paths.is_empty() => {
+                    options.command = Command::Archive
+                }
+                "avail" if options.command == Command::Today && options.paths.is_empty() => {
+                    options.command = Command::Avail
+                }
+                "digest" if options.command == Command::Today && options.paths.is_empty() => {
+                    options.command = Command::Digest
+                }
+                "open-next" if options.command == Command::Today && options.paths.is_empty() => {
+                    options.command = Command::OpenNext
+                }
+                _ if options.command == Command::Snooze && options.snooze.is_none() => {
+                    options.snooze = Some(SnoozeAction::Add(flag))
+                }
+                _ if options.command == Command::Month(None) && options.paths.is_empty() => {
+                    match parse_month(&flag) {
+                        Some(month) => options.command = Command::Month(Some(month)),
+                        None => options.paths.push(flag),
+                    }
+                }
+                _ => options.paths.push(flag),
+            }
+        }
+        // after the loop, so that a command can still come after `--dirs-from`
+        options.paths.extend(listed_dirs);
+
+        // reversing the list puts all-day events at the bottom, unless they were put somewhere
+        options.sort.allday_position = allday_position.unwrap_or(if options.sort.reverse {
+            AllDayPosition::Bottom
+        } else {
+            AllDayPosition::Top
+        });
+
+        if options.porcelain && options.command != Command::Lint {
+            return Err(Error::Usage("'--porcelain' only works with 'lint'".into()));
+        }
+
+        if options.max_title.is_some() && options.format != Format::Compact {
+            return Err(Error::Usage(
+                "'--max-title' only works with '--format compact'".into(),
+            ));
+        }
+
+        if options.json_lines
+            && (options.format != Format::Json || options.command != Command::Today)
+        {
+            return Err(Error::Usage(
+                "'--json-lines' only works with '--format json' and today's events".into(),
+            ));
+        }
+
+        if options.summary && !options.json_lines {
+            return Err(Error::Usage(
+                "'--summary' only works with '--json-lines'".into(),
+            ));
+        }
+
+        if options.command == Command::Snooze && options.snooze.is_none() {
+            return Err(Error::Usage(
+                "'snooze' needs an event's title or note, '--list' or '--clear'".into(),
+            ));
+        }
+
+        if options.command == Command::Archive
+            && (options.archive_before.is_none() || options.archive_dest.is_none())
+        {
+            return Err(Error::Usage(
+                "'archive' needs '--before' and '--dest'".into(),
+            ));
+        }
+
+        if let (Some(from), Some(to)) = (options.avail_from, options.avail_to) {
+            if to < from {
+                return Err(Error::Usage(format!(
+                    "'--to {}' is before '--from {}'",
+                    to, from
+                )));
+            }
+        }
+
+        if append {
+            let Destination::File(path) = options.output else {
+                return Err(Error::Usage("'--append' needs '--output'".into()));
+            };
+            options.output = Destination::Append(path);
+        }
+
+        if options.day_start >= options.day_end {
+            return Err(Error::Usage(
+                "'--day-start' must be before '--day-end'".into(),
+            ));
+        }
+
+        // another day is shown from its start, so that none of its events are over yet
+        if offset != 0 {
+            if offset.abs() > MAX_OFFSET {
+                return Err(Error::Usage(format!(
+                    "'--offset' can be at most {} days either way, not {}",
+                    MAX_OFFSET, offset
+                )));
+            }
+            let today = options
+                .now
+                .unwrap_or_else(|| chrono::Local::now().naive_local())
+                .date();
+            let date = if offset > 0 {
+                today.checked_add_days(chrono::Days::new(offset.unsigned_abs()))
+            } else {
+                today.checked_sub_days(chrono::Days::new(offset.unsigned_abs()))
+            };
+            options.now = Some(
+                date.ok_or(Error::Usage(format!(
+                    "'--offset {}' is out of range",
+                    offset
+                )))?
+                .and_time(NaiveTime::MIN),
+            );
+        }
+
+        if let Some(weeks) = weeks_ahead {
+            let today = options
+                .now
+                .unwrap_or_else(|| chrono::Local::now().naive_local())
+                .date();
+            options.week_of =
+                Some(week_containing(today, Weekday::Mon) + chrono::Days::new(7 * weeks));
+        }
+
+        let config = Config::load(options.config.as_deref())?;
+        options.sources = Source::from_config(&config)?;
+        options.holidays = config.get_str("holidays")?.map(PathBuf::from);
+        options.skip_holidays = config.get_bool("skip_holidays")?.unwrap_or(false);
+        for pattern in config.get_list("ignore")?.unwrap_or_default() {
+            options.ignore.push(Glob::new(pattern).map_err(|_| {
+                Error::Config(format!(
+                    "Config key 'ignore' has an invalid pattern '{}'",
+                    pattern
+                ))
+            })?);
+        }
+        options.allday_busy = allday_busy || config.get_bool("allday_busy")?.unwrap_or(false);
+        if options.empty_text.is_none() {
+            options.empty_text = config.get_str("empty_text")?.map(String::from);
+        }
+        options.infer_category =
+            infer_category || config.get_bool("infer_category_from_dir")?.unwrap_or(false);
+        for (key, time) in [("from", &mut options.from), ("until", &mut options.until)] {
+            if time.is_none() {
+                *time = config
+                    .get_str(key)?
+                    .map(|value| {
+                        parse_time_of_day(value).ok_or_else(|| {
+                            Error::Config(format!(
+                                "Config key '{}' should be a time like 18:00, not '{}'",
+                                key, value
+                            ))
+                        })
+                    })
+                    .transpose()?;
+            }
+        }
+        options.weekday_locale = match weekday_locale {
+            Some(locale) => locale,
+            None => config
+                .get_str("weekday_locale")?
+                .map(str::parse)
+                .transpose()?
+                .unwrap_or(WeekdayLocale::English),
+        };
+        options.allday_end = match allday_end {
+            Some(allday_end) => allday_end,
+            None => config
+                .get_str("allday_end")?
+                .map(str::parse)
+                .transpose()?
+                .unwrap_or(AllDayEnd::Exclusive),
+        };
+        options.lang = match lang {
+            Some(lang) => lang,
+            None => config
+                .get_str("lang")?
+                .map(str::parse)
+                .transpose()?
+                .unwrap_or_default(),
+        }
+        .with_overrides(&config)?;
+        let theme = match theme {
+            Some(theme) => theme,
+            None => config
+                .get_str("theme")?
+                .map(str::parse)
+                .transpose()?
+                .unwrap_or(Theme::Auto),
+        };
+        options.palette =
+            Palette::for_theme(theme.resolve(std::env::var("COLORFGBG").ok().as_deref()))
+                .with_overrides(&config)?;
+        Ok(options)
+    }
+}
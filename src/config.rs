@@ -0,0 +1,181 @@
+use crate::error::Error;
+use std::{collections::BTreeMap, path::PathBuf};
+
+/// A value in the config file
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigValue {
+    String(String),
+    Bool(bool),
+    Integer(i64),
+    List(Vec<String>),
+}
+
+/// The config file, which uses the small subset of TOML needed for it: `[section]` headers and
+/// `key = value` lines, where the value is a string, a boolean, an integer or a list of strings.
+/// Keys inside a section are stored with the section name prefixed, so `url` in `[source.work]`
+/// is `source.work.url`.
+#[derive(Debug, Default)]
+pub struct Config {
+    values: BTreeMap<String, ConfigValue>,
+}
+
+/// Removes a `#` comment from the end of the line, ignoring any `#` inside a string
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    for (i, c) in line.char_indices() {
+        match (c, quote) {
+            ('"' | '\'', None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            ('#', None) => return &line[..i],
+            _ => (),
+        }
+    }
+    line
+}
+
+fn parse_string(value: &str) -> Option<String> {
+    let inner = value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))?;
+    Some(inner.replace("\\\"", "\"").replace("\\\\", "\\"))
+}
+
+fn parse_value(value: &str) -> Option<ConfigValue> {
+    if let Some(s) = parse_string(value) {
+        Some(ConfigValue::String(s))
+    } else if let Some(list) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+        list.split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(parse_string)
+            .collect::<Option<Vec<String>>>()
+            .map(ConfigValue::List)
+    } else if value == "true" || value == "false" {
+        Some(ConfigValue::Bool(value == "true"))
+    } else {
+        value.parse().ok().map(ConfigValue::Integer)
+    }
+}
+
+impl Config {
+    pub fn parse(contents: &str) -> Result<Self, Error> {
+        let mut config = Config::default();
+        let mut section = String::new();
+        for (number, line) in contents.lines().enumerate() {
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                section = format!("{}.", name.trim());
+                continue;
+            }
+            let (key, value) = line.split_once('=').ok_or(Error::Config(format!(
+                "Config line {} is not a section or a 'key = value' pair",
+                number + 1
+            )))?;
+            let value = parse_value(value.trim()).ok_or(Error::Config(format!(
+                "Config line {} has an invalid value for '{}'",
+                number + 1,
+                key.trim()
+            )))?;
+            config
+                .values
+                .insert(format!("{}{}", section, key.trim()), value);
+        }
+        Ok(config)
+    }
+
+    /// Loads the config from `path`, or from `$XDG_CONFIG_HOME/obsidian-calendar-summary/config.toml`
+    /// if no path is given. A missing default config file is the same as an empty one.
+    pub fn load(path: Option<&str>) -> Result<Self, Error> {
+        let (path, explicit) = match path {
+            Some(path) => (PathBuf::from(path), true),
+            None => match config_dir() {
+                Some(dir) => (
+                    dir.join("obsidian-calendar-summary").join("config.toml"),
+                    false,
+                ),
+                None => return Ok(Config::default()),
+            },
+        };
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => Config::parse(&contents),
+            Err(e) if !explicit && e.kind() == std::io::ErrorKind::NotFound => {
+                Ok(Config::default())
+            }
+            Err(e) => Err(Error::Config(format!(
+                "Cannot read config file '{}': {}",
+                path.display(),
+                e
+            ))),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&ConfigValue> {
+        self.values.get(key)
+    }
+
+    pub fn get_str(&self, key: &str) -> Result<Option<&str>, Error> {
+        match self.get(key) {
+            None => Ok(None),
+            Some(ConfigValue::String(s)) => Ok(Some(s)),
+            Some(_) => Err(Error::Config(format!(
+                "Config key '{}' should be a string",
+                key
+            ))),
+        }
+    }
+
+    pub fn get_bool(&self, key: &str) -> Result<Option<bool>, Error> {
+        match self.get(key) {
+            None => Ok(None),
+            Some(ConfigValue::Bool(b)) => Ok(Some(*b)),
+            Some(_) => Err(Error::Config(format!(
+                "Config key '{}' should be true or false",
+                key
+            ))),
+        }
+    }
+
+    pub fn get_list(&self, key: &str) -> Result<Option<&[String]>, Error> {
+        match self.get(key) {
+            None => Ok(None),
+            Some(ConfigValue::List(items)) => Ok(Some(items)),
+            Some(_) => Err(Error::Config(format!(
+                "Config key '{}' should be a list of strings",
+                key
+            ))),
+        }
+    }
+
+    /// The keys and values in the `[name]` section, without the section's name
+    pub fn section<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> impl Iterator<Item = (&'a str, &'a ConfigValue)> {
+        self.values.iter().filter_map(move |(key, value)| {
+            Some((key.strip_prefix(name)?.strip_prefix('.')?, value))
+        })
+    }
+
+    /// The names of all the `[prefix.name]` sections, e.g. `sections("source")` gives the name of
+    /// every configured source
+    pub fn sections(&self, prefix: &str) -> Vec<&str> {
+        let mut names: Vec<&str> = self
+            .values
+            .keys()
+            .filter_map(|key| key.strip_prefix(prefix)?.strip_prefix('.'))
+            .filter_map(|rest| Some(rest.rsplit_once('.')?.0))
+            .collect();
+        names.dedup();
+        names
+    }
+}
+
+fn config_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+}
@@ -0,0 +1,306 @@
+use crate::{
+    error::Error,
+    event::{timed_span, SourcedEvent},
+    lang::Lang,
+    parse::parse_time,
+    render::json_string,
+    schema::{parse_json, Json},
+};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use std::path::Path;
+
+/// An event as it was listed, which is all that's kept of it between runs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub title: String,
+    /// the day it was listed for, which is tomorrow or later with `--show-next-day`
+    pub date: NaiveDate,
+    /// both `None` for all-day events
+    pub begin: Option<NaiveTime>,
+    pub end: Option<NaiveTime>,
+}
+
+impl Entry {
+    pub fn new(sourced: &SourcedEvent, date: NaiveDate) -> Self {
+        Self {
+            title: sourced.event.title().into(),
+            date,
+            begin: sourced.event.begin(),
+            end: sourced.event.end(),
+        }
+    }
+
+    /// Whether the timed event is over by `now`, so it's no longer listed without anything having
+    /// changed
+    fn ended(&self, now: NaiveDateTime) -> bool {
+        match (self.begin, self.end) {
+            (Some(begin), Some(end)) => timed_span(self.date, begin, end).1 < now,
+            _ => false,
+        }
+    }
+
+    /// "14:00 - 15:00" or "All day", after the day if it isn't `today`
+    pub fn when(&self, today: NaiveDate, lang: &Lang) -> String {
+        let times = match (self.begin, self.end) {
+            (Some(begin), Some(end)) => {
+                format!("{} - {}", begin.format("%H:%M"), end.format("%H:%M"))
+            }
+            _ => lang.all_day.clone(),
+        };
+        if self.date == today {
+            times
+        } else {
+            format!("{} {}", lang.weekday_date(self.date), times)
+        }
+    }
+
+    /// `"date":"2024-05-08","start":"14:00","end":"15:00"`, without the braces so the title can
+    /// go alongside. Times with seconds keep them, so that they're read back the same.
+    fn json_times(&self) -> String {
+        let time = |time: Option<NaiveTime>| {
+            time.map_or("null".into(), |time| {
+                let format = if time.second() == 0 && time.nanosecond() == 0 {
+                    "%H:%M"
+                } else {
+                    "%H:%M:%S%.f"
+                };
+                format!("\"{}\"", time.format(format))
+            })
+        };
+        format!(
+            "\"date\":\"{}\",\"start\":{},\"end\":{}",
+            self.date,
+            time(self.begin),
+            time(self.end)
+        )
+    }
+
+    fn to_json(&self) -> String {
+        format!(
+            "{{\"title\":{},{}}}",
+            json_string(&self.title),
+            self.json_times()
+        )
+    }
+}
+
+/// The events listed by a run, kept in the `--diff-state` file as a JSON object like
+/// `{"date":"2024-05-08","events":[{"title":"Standup","date":"2024-05-08","start":"09:45","end":"10:15"}]}`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    /// the day of the run
+    pub date: NaiveDate,
+    pub events: Vec<Entry>,
+}
+
+impl Snapshot {
+    /// The events listed on `today`, leaving out made-up ones like focus blocks, which move
+    /// whenever the events around them do
+    pub fn new(events: &[&SourcedEvent], date: NaiveDate, today: NaiveDate) -> Self {
+        Self {
+            date: today,
+            events: events
+                .iter()
+                .filter(|sourced| !sourced.synthetic)
+                .map(|sourced| Entry::new(sourced, date))
+                .collect(),
+        }
+    }
+
+    pub fn parse(s: &str) -> Result<Self, Error> {
+        let Json::Object(fields) = parse_json(s)? else {
+            return Err(Error::Json("The snapshot must be a JSON object".into()));
+        };
+        let field = |fields: &[(String, Json)], key: &str| {
+            fields
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value.clone())
+        };
+        let date = |value: Option<Json>| match value {
+            Some(Json::String(date)) => date
+                .parse()
+                .map_err(|e| Error::Json(format!("'date': {}", e))),
+            _ => Err(Error::Json(
+                "'date' must be a date like \"2024-05-08\"".into(),
+            )),
+        };
+        let time = |value: Option<Json>, key: &str| match value {
+            Some(Json::String(time)) => parse_time(&time)
+                .map(Some)
+                .map_err(|e| Error::Json(format!("'{}': {}", key, e))),
+            Some(Json::Null) | None => Ok(None),
+            _ => Err(Error::Json(format!(
+                "'{}' must be a time like \"09:30\"",
+                key
+            ))),
+        };
+        let Some(Json::Array(items)) = field(&fields, "events") else {
+            return Err(Error::Json("The snapshot has no 'events' array".into()));
+        };
+        let events = items
+            .iter()
+            .map(|item| {
+                let Json::Object(fields) = item else {
+                    return Err(Error::Json("An event must be a JSON object".into()));
+                };
+                let Some(Json::String(title)) = field(fields, "title") else {
+                    return Err(Error::Json("An event has no 'title'".into()));
+                };
+                Ok(Entry {
+                    title,
+                    date: date(field(fields, "date"))?,
+                    begin: time(field(fields, "start"), "start")?,
+                    end: time(field(fields, "end"), "end")?,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            date: date(field(&fields, "date"))?,
+            events,
+        })
+    }
+
+    /// The snapshot as JSON, one event to a line
+    pub fn to_json(&self) -> String {
+        let events: Vec<String> = self
+            .events
+            .iter()
+            .map(|entry| format!("  {}", entry.to_json()))
+            .collect();
+        if events.is_empty() {
+            return format!("{{\"date\":\"{}\",\"events\":[]}}\n", self.date);
+        }
+        format!(
+            "{{\"date\":\"{}\",\"events\":[\n{}\n]}}\n",
+            self.date,
+            events.join(",\n")
+        )
+    }
+
+    /// Reads the snapshot from the last run, if there was one
+    pub fn load(path: &Path) -> Result<Option<Self>, Error> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => Self::parse(&contents).map(Some).map_err(|e| {
+                Error::Json(format!("Cannot read snapshot '{}': {}", path.display(), e))
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(source) => Err(Error::Io {
+                path: path.to_path_buf(),
+                source,
+            }),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        path.parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(path, self.to_json()))
+            .map_err(|source| Error::Io {
+                path: path.to_path_buf(),
+                source,
+            })
+    }
+}
+
+/// How the list of events differs from the last run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    Added(Entry),
+    Removed(Entry),
+    /// the same title at different times
+    Retimed {
+        before: Entry,
+        after: Entry,
+    },
+}
+
+/// What's changed between two runs on the same day. An event which is in both with the same title
+/// but different times has been moved, and several with the same title are paired up in order.
+/// Timed events which have ended by `now` have only dropped off the list, so they don't count as
+/// removed. Nothing has changed on a new day, when everything would be new.
+pub fn diff(before: &Snapshot, after: &Snapshot, now: NaiveDateTime) -> Vec<Change> {
+    if before.date != after.date {
+        return Vec::new();
+    }
+    let mut removed: Vec<&Entry> = before.events.iter().collect();
+    let mut added = Vec::new();
+    for entry in &after.events {
+        match removed.iter().position(|old| *old == entry) {
+            Some(i) => {
+                removed.remove(i);
+            }
+            None => added.push(entry),
+        }
+    }
+    let mut changes: Vec<Change> = added
+        .into_iter()
+        .map(
+            |entry| match removed.iter().position(|old| old.title == entry.title) {
+                Some(i) => Change::Retimed {
+                    before: removed.remove(i).clone(),
+                    after: entry.clone(),
+                },
+                None => Change::Added(entry.clone()),
+            },
+        )
+        .collect();
+    changes.extend(
+        removed
+            .into_iter()
+            .filter(|entry| !entry.ended(now))
+            .map(|entry| Change::Removed(entry.clone())),
+    );
+    changes
+}
+
+/// A line for each change, e.g. `Moved: Review from 14:00 - 15:00 to 16:00 - 17:00`
+pub fn text(changes: &[Change], today: NaiveDate, lang: &Lang) -> String {
+    changes
+        .iter()
+        .map(|change| {
+            let line = match change {
+                Change::Added(entry) => lang
+                    .added
+                    .replace("{when}", &entry.when(today, lang))
+                    .replace("{title}", &entry.title),
+                Change::Removed(entry) => lang
+                    .removed
+                    .replace("{when}", &entry.when(today, lang))
+                    .replace("{title}", &entry.title),
+                Change::Retimed { before, after } => lang
+                    .moved
+                    .replace("{before}", &before.when(today, lang))
+                    .replace("{after}", &after.when(today, lang))
+                    .replace("{title}", &after.title),
+            };
+            line + "\n"
+        })
+        .collect()
+}
+
+/// The changes as a JSON object with `added`, `removed` and `changed` arrays
+pub fn json(changes: &[Change]) -> String {
+    let mut added = Vec::new();
+    let mut removed = Vec::new();
+    let mut changed = Vec::new();
+    for change in changes {
+        match change {
+            Change::Added(entry) => added.push(entry.to_json()),
+            Change::Removed(entry) => removed.push(entry.to_json()),
+            Change::Retimed { before, after } => changed.push(format!(
+                "{{\"title\":{},\"before\":{{{}}},\"after\":{{{}}}}}",
+                json_string(&after.title),
+                before.json_times(),
+                after.json_times()
+            )),
+        }
+    }
+    format!(
+        "{{\"added\":[{}],\"removed\":[{}],\"changed\":[{}]}}",
+        added.join(","),
+        removed.join(","),
+        changed.join(",")
+    )
+}
@@ -0,0 +1,146 @@
+use crate::{
+    calendar::Calendar,
+    cli::Options,
+    event::{Event, SourcedEvent},
+    lang::Lang,
+    render::humanize_minutes,
+};
+use chrono::{Datelike, Days, NaiveDate, Timelike};
+
+/// How wide the digest's lines are at most, to suit email
+pub const WIDTH: usize = 72;
+
+/// `text` broken into lines at most `width` characters wide, the first starting with `first` and
+/// the rest indented to line up with where the text begins. Words longer than a line are left
+/// whole.
+pub fn wrap(first: &str, text: &str, width: usize) -> String {
+    let indent = " ".repeat(first.chars().count());
+    let mut out = String::new();
+    let mut line = first.to_string();
+    let mut empty = true;
+    for word in text.split_whitespace() {
+        if !empty && line.chars().count() + 1 + word.chars().count() > width {
+            out += line.trim_end();
+            out.push('\n');
+            line = indent.clone();
+            empty = true;
+        }
+        if !empty {
+            line.push(' ');
+        }
+        line += word;
+        empty = false;
+    }
+    out += line.trim_end();
+    out.push('\n');
+    out
+}
+
+/// How long the timed events on a day last altogether, in minutes. All-day events don't count.
+pub fn scheduled_minutes(events: &[&SourcedEvent]) -> i64 {
+    events
+        .iter()
+        .filter(|sourced| !matches!(sourced.event, Event::AllDay { .. }))
+        .map(|sourced| sourced.event.duration().num_minutes())
+        .sum()
+}
+
+/// The recurring events whose `endRecur` is in the 7 days from `first_day`, each with the last
+/// day it happens on, in the order they end
+pub fn ending(calendar: &Calendar, first_day: NaiveDate) -> Vec<(&SourcedEvent, NaiveDate)> {
+    let last_day = first_day + Days::new(6);
+    let mut ending: Vec<(&SourcedEvent, NaiveDate)> = calendar
+        .events
+        .iter()
+        .filter_map(|sourced| {
+            let Event::Recurring {
+                end_recur: Some(end_recur),
+                ..
+            } = sourced.event
+            else {
+                return None;
+            };
+            if end_recur < first_day || end_recur > last_day {
+                return None;
+            }
+            // a monthly event can last have happened weeks before
+            let from = end_recur
+                .checked_sub_days(Days::new(366))
+                .unwrap_or(end_recur);
+            let last = sourced.event.occurrences(from, end_recur).last()?;
+            Some((sourced, last))
+        })
+        .collect();
+    ending.sort_by_key(|(sourced, last)| (*last, sourced.event.title().to_string()));
+    ending
+}
+
+fn event_text(event: &Event, lang: &Lang) -> (String, String) {
+    match event {
+        Event::Once { begin, end, .. } | Event::Recurring { begin, end, .. } => (
+            format!(
+                "  {:02}:{:02} - {:02}:{:02}  ",
+                begin.hour(),
+                begin.minute(),
+                end.hour(),
+                end.minute()
+            ),
+            event.title().to_string(),
+        ),
+        Event::AllDay { title, .. } => (format!("  {:<13}  ", lang.all_day), title.to_string()),
+        Event::Deadline { title, .. } => (format!("  {:<13}  ", lang.due), title.to_string()),
+    }
+}
+
+/// A plain-text digest of the 7 days from `first_day`, e.g. to send by email: a heading, then
+/// each day with how long is scheduled on it and its events, then the recurring events which
+/// end that week. Lines are wrapped at `WIDTH`.
+pub fn digest(calendar: &Calendar, options: &Options, first_day: NaiveDate) -> String {
+    let lang = &options.lang;
+    let last_day = first_day + Days::new(6);
+    let mut out = wrap(
+        "",
+        &lang
+            .digest_heading
+            .replace("{week}", &first_day.iso_week().week().to_string())
+            .replace("{first}", &lang.weekday_date(first_day))
+            .replace("{last}", &lang.weekday_date(last_day))
+            .replace("{year}", &last_day.year().to_string()),
+        WIDTH,
+    );
+    for date in first_day.iter_days().take(7) {
+        let events = calendar.events_on(date, options);
+        let minutes = scheduled_minutes(&events);
+        out.push('\n');
+        let total = if events.is_empty() {
+            lang.nothing_scheduled.clone()
+        } else {
+            lang.scheduled
+                .replace("{time}", &humanize_minutes(minutes, lang))
+        };
+        out += &wrap(
+            "",
+            &format!("{}: {}", lang.weekday_date(date), total),
+            WIDTH,
+        );
+        for sourced in events {
+            let (when, title) = event_text(&sourced.event, lang);
+            out += &wrap(&when, &title, WIDTH);
+        }
+    }
+    let ending = ending(calendar, first_day);
+    if !ending.is_empty() {
+        out += &format!("\n{}:\n", lang.ending_this_week);
+        for (sourced, last) in ending {
+            out += &wrap(
+                "  ",
+                &lang
+                    .last_on
+                    .replace("{title}", sourced.event.title())
+                    .replace("{date}", &lang.weekday_date(last)),
+                WIDTH,
+            );
+        }
+    }
+    out
+}
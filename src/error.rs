@@ -0,0 +1,348 @@
+use crate::render::json_string;
+use chrono::NaiveDate;
+use std::path::{Path, PathBuf};
+
+/// What's wrong with a note's frontmatter, or with the links in an index note
+#[derive(Debug)]
+pub enum ParseErrorKind {
+    /// a key the event needs isn't there
+    MissingField,
+    /// the value, and why it can't be read
+    BadDate {
+        value: String,
+        error: chrono::ParseError,
+    },
+    BadTime {
+        value: String,
+        error: chrono::ParseError,
+    },
+    /// a combined date and time like `startDate: 2024-05-03T14:00` which can't be read
+    BadDateTime {
+        value: String,
+        error: chrono::ParseError,
+    },
+    /// a day in `daysOfWeek` which isn't one of `expected`
+    BadWeekday { token: String, expected: String },
+    /// the event can't ever happen, e.g. because `endRecur` is before `startRecur`
+    NeverHappens(String),
+    /// frontmatter which this parser can't read
+    Yaml(String),
+    /// a key which the kind of event doesn't use, e.g. `daysOfWeek` on a single event
+    IgnoredKey(String),
+    /// a day in `daysOfWeek` which other tools might have meant as a different day, like `S`
+    AmbiguousWeekday(String),
+    /// a wiki-link in an index note which doesn't lead to any note
+    DanglingLink(String),
+    /// a wiki-link in an index note which could lead to several notes, and the one it's taken to
+    /// lead to
+    AmbiguousLink {
+        link: String,
+        matches: usize,
+        note: PathBuf,
+    },
+    /// a key which is in the frontmatter more than once, e.g. after a sloppy merge, of which only
+    /// the first is read
+    DuplicateKey,
+    /// two keys which say different things about the event, e.g. `startDate` and `date`
+    Conflict(String),
+    /// a value like `{{date}}` or `<% tp.file.title %>` left behind by a template which wasn't
+    /// filled in
+    Placeholder(String),
+    /// a date like `tomorrow`, which is only read with `--allow-relative-dates`, when it's
+    /// `resolved` to a different day every day
+    RelativeDate {
+        value: String,
+        resolved: Option<NaiveDate>,
+    },
+}
+
+impl std::fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::MissingField => write!(f, "missing"),
+            Self::DuplicateKey => write!(f, "duplicate key, using the first value"),
+            Self::DanglingLink(link) => write!(f, "[[{}]] doesn't lead to a note", link),
+            Self::AmbiguousLink {
+                link,
+                matches,
+                note,
+            } => write!(
+                f,
+                "[[{}]] could be any of {} notes, using {}",
+                link,
+                matches,
+                note.display()
+            ),
+            Self::BadDate { value, error } => write!(f, "'{}', not a date ({})", value, error),
+            Self::BadTime { value, error } => write!(f, "'{}', not a time ({})", value, error),
+            Self::BadDateTime { value, error } => {
+                write!(f, "'{}', not a date and time ({})", value, error)
+            }
+            Self::BadWeekday { token, expected } => {
+                write!(f, "unknown weekday '{}' (expected {})", token, expected)
+            }
+            Self::NeverHappens(reason)
+            | Self::Yaml(reason)
+            | Self::IgnoredKey(reason)
+            | Self::AmbiguousWeekday(reason)
+            | Self::Conflict(reason) => write!(f, "{}", reason),
+            Self::Placeholder(value) => write!(f, "unexpanded template placeholder '{}'", value),
+            Self::RelativeDate {
+                value,
+                resolved: Some(resolved),
+            } => write!(
+                f,
+                "'{}' is relative, so it's {} today but changes from day to day",
+                value, resolved
+            ),
+            Self::RelativeDate {
+                value,
+                resolved: None,
+            } => write!(
+                f,
+                "'{}' is a relative date, which needs --allow-relative-dates",
+                value
+            ),
+        }
+    }
+}
+
+/// Everything that can go wrong. Problems with the notes can be reported to scripts with
+/// `--errors json`.
+#[derive(Debug)]
+pub enum Error {
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        /// which of the note's events the problem is with, counting from 1, if it has several
+        event: Option<usize>,
+        /// the frontmatter key the problem is with, if it's about one key
+        field: Option<String>,
+        kind: ParseErrorKind,
+    },
+    /// a command line argument which doesn't make sense
+    Usage(String),
+    /// a problem with the config file
+    Config(String),
+    Holidays {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// an event in an ICS file which can't be shown
+    Ics(String),
+    /// a remote calendar which couldn't be downloaded
+    Fetch(String),
+    /// a link `open-next` couldn't open
+    Open(String),
+    /// JSON in the format of `schema` which can't be read
+    Json(String),
+}
+
+impl Error {
+    /// A short name for the kind of problem which scripts can match on
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Io { .. } => "io",
+            Self::Parse { kind, .. } => match kind {
+                ParseErrorKind::MissingField => "missing_field",
+                ParseErrorKind::BadDate { .. } => "bad_date",
+                ParseErrorKind::BadTime { .. } => "bad_time",
+                ParseErrorKind::BadDateTime { .. } => "bad_datetime",
+                ParseErrorKind::BadWeekday { .. } => "bad_weekday",
+                ParseErrorKind::NeverHappens(_) => "never_happens",
+                ParseErrorKind::Yaml(_) => "yaml",
+                ParseErrorKind::IgnoredKey(_) => "ignored_key",
+                ParseErrorKind::AmbiguousWeekday(_) => "ambiguous_weekday",
+                ParseErrorKind::DuplicateKey => "duplicate_key",
+                ParseErrorKind::DanglingLink(_) => "dangling_link",
+                ParseErrorKind::AmbiguousLink { .. } => "ambiguous_link",
+                ParseErrorKind::Conflict(_) => "conflict",
+                ParseErrorKind::Placeholder(_) => "placeholder",
+                ParseErrorKind::RelativeDate { .. } => "relative_date",
+            },
+            Self::Usage(_) => "usage",
+            Self::Config(_) => "config",
+            Self::Holidays { .. } => "holidays",
+            Self::Ics(_) => "ics",
+            Self::Fetch(_) => "fetch",
+            Self::Open(_) => "open",
+            Self::Json(_) => "json",
+        }
+    }
+
+    /// Whether this is a note which hasn't had its template filled in, or which couldn't be read
+    /// at all (e.g. because of its permissions or a sync tool writing it), which is skipped with a
+    /// warning rather than stopping the program unless `--strict` is given
+    pub fn is_skippable(&self) -> bool {
+        matches!(
+            self,
+            Self::Io { .. }
+                | Self::Parse {
+                    kind: ParseErrorKind::Placeholder(_),
+                    ..
+                }
+        )
+    }
+
+    /// Whether this is only a warning about a note which was still read, like a key its event
+    /// doesn't use, which stops the program with `--strict`
+    pub fn is_warning(&self) -> bool {
+        matches!(
+            self,
+            Self::Parse {
+                kind: ParseErrorKind::IgnoredKey(_)
+                    | ParseErrorKind::AmbiguousWeekday(_)
+                    | ParseErrorKind::DuplicateKey
+                    | ParseErrorKind::DanglingLink(_)
+                    | ParseErrorKind::AmbiguousLink { .. },
+                ..
+            }
+        ) || self.is_relative_date()
+    }
+
+    /// "warning" or "error", as `lint --porcelain` reports it
+    pub fn severity(&self) -> &'static str {
+        if self.is_warning() {
+            "warning"
+        } else {
+            "error"
+        }
+    }
+
+    /// Whether this is a date like `tomorrow` which was read with `--allow-relative-dates`. These
+    /// are only listed by `lint` and `--errors json`, since they were asked for.
+    pub fn is_relative_date(&self) -> bool {
+        matches!(
+            self,
+            Self::Parse {
+                kind: ParseErrorKind::RelativeDate {
+                    resolved: Some(_),
+                    ..
+                },
+                ..
+            }
+        )
+    }
+
+    /// The file the problem is with, if it's about a file
+    pub fn path(&self) -> Option<&PathBuf> {
+        match self {
+            Self::Io { path, .. } | Self::Parse { path, .. } | Self::Holidays { path, .. } => {
+                Some(path)
+            }
+            _ => None,
+        }
+    }
+
+    /// The problem as a JSON object with its code, field, path, event and message
+    pub fn to_json(&self) -> String {
+        let field = match self {
+            Self::Parse {
+                field: Some(field), ..
+            } => json_string(field),
+            _ => "null".into(),
+        };
+        let event = match self {
+            Self::Parse {
+                event: Some(event), ..
+            } => event.to_string(),
+            _ => "null".into(),
+        };
+        format!(
+            "{{\"code\":\"{}\",\"field\":{},\"path\":{},\"event\":{},\"message\":{}}}",
+            self.code(),
+            field,
+            self.path()
+                .map_or("null".into(), |path| json_string(&path.to_string_lossy())),
+            event,
+            json_string(&self.to_string())
+        )
+    }
+}
+
+/// The note's path, followed by which event in it for notes with several, e.g.
+/// "schedule.md (event 3)"
+fn note_name(path: &Path, event: Option<usize>) -> String {
+    match event {
+        Some(event) => format!("{} (event {})", path.display(), event),
+        None => path.display().to_string(),
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            Self::Parse {
+                path,
+                event,
+                field: Some(field),
+                kind: ParseErrorKind::MissingField,
+            } => write!(f, "{}: has no '{}'", note_name(path, *event), field),
+            Self::Parse {
+                path,
+                event,
+                field: Some(field),
+                kind: ParseErrorKind::Placeholder(value),
+            } => write!(
+                f,
+                "{}: unexpanded template placeholder in '{}' ({})",
+                note_name(path, *event),
+                field,
+                value
+            ),
+            Self::Parse {
+                path,
+                event,
+                field: Some(field),
+                kind:
+                    kind @ (ParseErrorKind::BadDate { .. }
+                    | ParseErrorKind::BadTime { .. }
+                    | ParseErrorKind::BadDateTime { .. }),
+            } => write!(f, "{}: '{}' is {}", note_name(path, *event), field, kind),
+            Self::Parse {
+                path,
+                event,
+                field: Some(field),
+                kind,
+            } => write!(f, "{}: '{}': {}", note_name(path, *event), field, kind),
+            Self::Parse {
+                path,
+                event,
+                field: None,
+                kind,
+            } => write!(f, "{}: {}", note_name(path, *event), kind),
+            Self::Holidays { path, source } => write!(
+                f,
+                "Cannot read holidays file '{}': {}",
+                path.display(),
+                source
+            ),
+            Self::Usage(message)
+            | Self::Config(message)
+            | Self::Ics(message)
+            | Self::Fetch(message)
+            | Self::Open(message)
+            | Self::Json(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } | Self::Holidays { source, .. } => Some(source),
+            Self::Parse {
+                kind:
+                    ParseErrorKind::BadDate { error, .. }
+                    | ParseErrorKind::BadTime { error, .. }
+                    | ParseErrorKind::BadDateTime { error, .. },
+                ..
+            } => Some(error),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,365 @@
+use chrono::{Datelike, Days, NaiveDate, NaiveDateTime, NaiveTime};
+use std::{path::PathBuf, sync::Arc, time::SystemTime};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    Once {
+        title: Arc<str>,
+        begin: NaiveTime,
+        end: NaiveTime,
+        day: NaiveDate,
+        /// how many minutes before it begins to leave for it
+        travel_minutes: Option<u32>,
+        /// when it's really going to end, if it's running over
+        actual_end: Option<NaiveTime>,
+    },
+    Recurring {
+        title: Arc<str>,
+        begin: NaiveTime,
+        end: NaiveTime,
+        /// None if the event has always been recurring
+        begin_recur: Option<NaiveDate>,
+        end_recur: Option<NaiveDate>,
+        recur_days: Vec<chrono::Weekday>,
+        /// don't show this event on public holidays
+        skip_holidays: bool,
+        travel_minutes: Option<u32>,
+        /// for `repeat: monthly`, which of the month's e.g. Mondays it's on: 1 for the first, or
+        /// -1 for the last counting from the end. None if it's on every one.
+        week_of_month: Option<i8>,
+    },
+    AllDay {
+        title: Arc<str>,
+        begin_date: NaiveDate,
+        end_date: NaiveDate,
+        /// a vacation: hide recurring events while it's on
+        suppress_recurring: bool,
+    },
+    /// Something due on a day, like a tax return, from `deadline: true`. It has no time, and is
+    /// shown ahead of the day by `--deadline-lookahead`.
+    Deadline {
+        title: Arc<str>,
+        due: NaiveDate,
+        /// done, from `completed`, so it's no longer shown
+        completed: bool,
+    },
+}
+
+impl Event {
+    /// The time a timed event begins at
+    pub fn begin(&self) -> Option<NaiveTime> {
+        match self {
+            Self::Once { begin, .. } | Self::Recurring { begin, .. } => Some(*begin),
+            Self::AllDay { .. } | Self::Deadline { .. } => None,
+        }
+    }
+    /// The time a timed event ends at, which is on the next day if it's before `begin`
+    pub fn end(&self) -> Option<NaiveTime> {
+        match self {
+            Self::Once { end, .. } | Self::Recurring { end, .. } => Some(*end),
+            Self::AllDay { .. } | Self::Deadline { .. } => None,
+        }
+    }
+    /// Whether the event happens at some point on `date`
+    pub fn occurs_on(&self, date: NaiveDate) -> bool {
+        match self {
+            Self::Once { day, .. } => *day == date,
+            Self::Recurring {
+                begin_recur,
+                end_recur,
+                recur_days,
+                week_of_month,
+                ..
+            } => {
+                recur_days.contains(&date.weekday())
+                    && begin_recur.map(|day| date >= day).unwrap_or(true)
+                    && end_recur.map(|day| date <= day).unwrap_or(true)
+                    && week_of_month.is_none_or(|n| is_nth_weekday(date, n))
+            }
+            Self::AllDay {
+                begin_date,
+                end_date,
+                ..
+            } => date >= *begin_date && date < *end_date,
+            Self::Deadline { due, .. } => *due == date,
+        }
+    }
+
+    /// The first and last days the event could happen on, or None where it has no limit
+    pub fn span(&self) -> (Option<NaiveDate>, Option<NaiveDate>) {
+        match self {
+            Self::Once { day, .. } | Self::Deadline { due: day, .. } => (Some(*day), Some(*day)),
+            Self::Recurring {
+                begin_recur,
+                end_recur,
+                ..
+            } => (*begin_recur, *end_recur),
+            Self::AllDay {
+                begin_date,
+                end_date,
+                ..
+            } => (
+                Some(*begin_date),
+                Some(end_date.pred_opt().unwrap_or(*end_date)),
+            ),
+        }
+    }
+
+    /// When the event begins and ends if it happens on `date`, or None for all-day events and
+    /// deadlines
+    pub fn times_on(&self, date: NaiveDate) -> Option<(NaiveDateTime, NaiveDateTime)> {
+        match self {
+            Self::Once { begin, end, .. } | Self::Recurring { begin, end, .. } => {
+                Some(timed_span(date, *begin, *end))
+            }
+            Self::AllDay { .. } | Self::Deadline { .. } => None,
+        }
+    }
+
+    /// When the event begins and ends on `date`, or None if it doesn't happen that day. All-day
+    /// events last from midnight to midnight, and on each day of a longer one just that day, and
+    /// so do deadlines on the day they're due.
+    pub fn interval_on(&self, date: NaiveDate) -> Option<(NaiveDateTime, NaiveDateTime)> {
+        if !self.occurs_on(date) {
+            return None;
+        }
+        self.times_on(date).or_else(|| {
+            let midnight = date.and_time(NaiveTime::MIN);
+            Some((midnight, midnight + Days::new(1)))
+        })
+    }
+
+    /// When the timed event on `date` is over: at its `actualEnd` if that's after its end, since
+    /// it's running over, and otherwise at its end
+    pub fn running_until(&self, date: NaiveDate) -> Option<NaiveDateTime> {
+        let (_, end) = self.times_on(date)?;
+        match self {
+            Self::Once {
+                begin,
+                actual_end: Some(actual_end),
+                ..
+            } => Some(end.max(timed_span(date, *begin, *actual_end).1)),
+            _ => Some(end),
+        }
+    }
+
+    /// Whether the event on `date` is over at `now`, which is once it's past its `actualEnd` if
+    /// it's running over. All-day events never are.
+    pub fn ended(&self, date: NaiveDate, now: NaiveDateTime) -> bool {
+        self.running_until(date).is_some_and(|end| end < now)
+    }
+
+    /// Whether the event on `date` has begun or begins within `duration` of `now`. All-day events
+    /// have always begun.
+    pub fn begins_within(
+        &self,
+        date: NaiveDate,
+        now: NaiveDateTime,
+        duration: chrono::Duration,
+    ) -> bool {
+        self.times_on(date)
+            .is_none_or(|(begin, _)| begin <= now + duration)
+    }
+
+    /// The days from `from` to `to` (inclusive) on which the event happens. Only the days in both
+    /// the window and the event's span are looked at, so a window which ends before it begins, or
+    /// one entirely outside the span, yields nothing without looking at any day, and an event
+    /// recurring forever can be asked about any window, even one from `NaiveDate::MIN`.
+    pub fn occurrences(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> impl Iterator<Item = NaiveDate> + '_ {
+        // no need to look at the days outside the event's span
+        let (first, last) = self.span();
+        let from = first.map_or(from, |first| first.max(from));
+        let to = last.map_or(to, |last| last.min(to));
+        from.iter_days()
+            .take_while(move |date| *date <= to)
+            .filter(|date| self.occurs_on(*date))
+    }
+
+    /// How many times a recurring event with both a `startRecur` and an `endRecur` happens.
+    /// Holidays and vacations aren't taken into account.
+    pub fn total_occurrences(&self) -> Option<usize> {
+        match self {
+            Self::Recurring {
+                begin_recur: Some(begin_recur),
+                end_recur: Some(end_recur),
+                ..
+            } => Some(self.occurrences(*begin_recur, *end_recur).count()),
+            _ => None,
+        }
+    }
+
+    /// Which occurrence of a bounded recurring event the one on `date` is, counting from 1, e.g.
+    /// 3 for the third session of a course, or None if it doesn't happen on `date`
+    pub fn occurrence_index(&self, date: NaiveDate) -> Option<usize> {
+        match self {
+            Self::Recurring {
+                begin_recur: Some(begin_recur),
+                end_recur: Some(_),
+                ..
+            } if self.occurs_on(date) => Some(self.occurrences(*begin_recur, date).count()),
+            _ => None,
+        }
+    }
+
+    /// How long the event lasts, in whole days for all-day events, and no time at all for
+    /// deadlines
+    pub fn duration(&self) -> chrono::Duration {
+        match self {
+            Self::Once { begin, end, .. } | Self::Recurring { begin, end, .. } => {
+                timed_duration(*begin, *end)
+            }
+            Self::AllDay {
+                begin_date,
+                end_date,
+                ..
+            } => *end_date - *begin_date,
+            Self::Deadline { .. } => chrono::Duration::zero(),
+        }
+    }
+
+    /// How many minutes before it begins to leave for the event, from `travelMinutes`
+    pub fn travel_minutes(&self) -> Option<u32> {
+        match self {
+            Self::Once { travel_minutes, .. } | Self::Recurring { travel_minutes, .. } => {
+                *travel_minutes
+            }
+            Self::AllDay { .. } | Self::Deadline { .. } => None,
+        }
+    }
+
+    pub fn title(&self) -> &str {
+        match self {
+            Self::Once { title, .. }
+            | Self::Recurring { title, .. }
+            | Self::AllDay { title, .. }
+            | Self::Deadline { title, .. } => title,
+        }
+    }
+
+    /// Whether both are recurring events with the same title, times and days, like a note and the
+    /// copy of it a sync tool left behind. When they start and stop recurring doesn't matter, and
+    /// neither does the order the days are written in.
+    pub fn same_recurrence(&self, other: &Event) -> bool {
+        let (
+            Self::Recurring {
+                title,
+                begin,
+                end,
+                recur_days,
+                week_of_month,
+                ..
+            },
+            Self::Recurring {
+                title: other_title,
+                begin: other_begin,
+                end: other_end,
+                recur_days: other_days,
+                week_of_month: other_week_of_month,
+                ..
+            },
+        ) = (self, other)
+        else {
+            return false;
+        };
+        let days = |days: &[chrono::Weekday]| {
+            let mut days: Vec<u32> = days.iter().map(|day| day.num_days_from_monday()).collect();
+            days.sort_unstable();
+            days.dedup();
+            days
+        };
+        title == other_title
+            && begin == other_begin
+            && end == other_end
+            && week_of_month == other_week_of_month
+            && days(recur_days) == days(other_days)
+    }
+}
+
+/// Whether `date` is the `n`th of its weekday in its month, counting from the end for negative
+/// `n`. The 5th Monday of a month with only four is no day at all.
+pub fn is_nth_weekday(date: NaiveDate, n: i8) -> bool {
+    let in_month =
+        |other: Option<NaiveDate>| other.is_some_and(|other| other.month() == date.month());
+    let weeks = |weeks: u8| Days::new(7 * u64::from(weeks));
+    let n_abs = n.unsigned_abs();
+    if n == 0 {
+        false
+    } else if n > 0 {
+        in_month(date.checked_sub_days(weeks(n_abs - 1)))
+            && !in_month(date.checked_sub_days(weeks(n_abs)))
+    } else {
+        in_month(date.checked_add_days(weeks(n_abs - 1)))
+            && !in_month(date.checked_add_days(weeks(n_abs)))
+    }
+}
+
+/// When an event from `begin` to `end` on `date` begins and ends. Events which end before they
+/// begin run past midnight into the next day.
+pub fn timed_span(
+    date: NaiveDate,
+    begin: NaiveTime,
+    end: NaiveTime,
+) -> (NaiveDateTime, NaiveDateTime) {
+    let end_date = if end < begin {
+        date.succ_opt().unwrap_or(date)
+    } else {
+        date
+    };
+    (date.and_time(begin), end_date.and_time(end))
+}
+
+/// How long an event from `begin` to `end` lasts, running past midnight if it ends before it
+/// begins
+pub fn timed_duration(begin: NaiveTime, end: NaiveTime) -> chrono::Duration {
+    let (begin_at, end_at) = timed_span(NaiveDate::default(), begin, end);
+    end_at - begin_at
+}
+
+/// An event along with the note it was read from
+#[derive(Debug)]
+pub struct SourcedEvent {
+    pub event: Event,
+    pub path: PathBuf,
+    /// made up by the program, like a suggested focus block, rather than read from a note
+    pub synthetic: bool,
+    /// shown on the days it happens even once it's over or outside `--from`/`--until`, from the
+    /// note's `pinned: true`
+    pub pinned: bool,
+    /// left out of everything unless `--show-hidden` is given, from the note's `hidden: true`, for
+    /// events which are only there to be seen in Obsidian
+    pub hidden: bool,
+    /// e.g. "Work", from the note's `category` or with `--infer-category-from-dir` its folder
+    pub category: Option<Arc<str>>,
+    /// e.g. "#2e8fb8", from the note's `color` or worked out from the folder's name
+    pub color: Option<Arc<str>>,
+    /// when the note was last edited, for `--tiebreak mtime`
+    pub modified: Option<SystemTime>,
+    /// e.g. "Lecture", from the note's `numberOccurrences`, to put the number of each occurrence
+    /// of a recurring event after its title, like "Algorithms (Lecture 7)"
+    pub numbered: Option<Arc<str>>,
+    /// the meeting's link (e.g. Zoom or Meet) from the note's `url`, which `open-next` opens
+    pub url: Option<Arc<str>>,
+}
+
+impl SourcedEvent {
+    /// The event read from the note at `path`, with none of the note's other keys: not pinned or
+    /// hidden, and without a category, colour, numbering or link
+    pub fn new(event: Event, path: impl Into<PathBuf>) -> Self {
+        Self {
+            event,
+            path: path.into(),
+            synthetic: false,
+            pinned: false,
+            hidden: false,
+            category: None,
+            color: None,
+            modified: None,
+            numbered: None,
+            url: None,
+        }
+    }
+}
@@ -0,0 +1,284 @@
+use crate::{
+    calendar::Calendar,
+    cli::Options,
+    event::{timed_duration, Event, SourcedEvent},
+    query::EventQuery,
+    render::json_string,
+};
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+
+/// How long an event lasts as remind's `H:MM`. Events which end before they begin are taken to
+/// run past midnight.
+pub fn remind_duration(begin: NaiveTime, end: NaiveTime) -> String {
+    let minutes = timed_duration(begin, end).num_minutes();
+    format!("{}:{:02}", minutes / 60, minutes % 60)
+}
+
+/// Escapes text for a remind `MSG`, where `%` starts a substitution and `[` an expression, and
+/// which has to stay on one line
+pub fn remind_escape(text: &str) -> String {
+    let mut out = String::new();
+    for c in text.chars() {
+        match c {
+            '%' => out += "%%",
+            '[' => out += "[\"[\"]",
+            '\n' | '\r' => out.push(' '),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn remind_date(date: NaiveDate) -> String {
+    date.format("%b %-d %Y").to_string()
+}
+
+fn remind_time(begin: NaiveTime, end: NaiveTime) -> String {
+    format!(
+        "AT {:02}:{:02} DURATION {}",
+        begin.hour(),
+        begin.minute(),
+        remind_duration(begin, end)
+    )
+}
+
+/// The events to export, with recurring events which are skipped on holidays or during vacations,
+/// or which are monthly, expanded into the days they're actually shown on between `today` and
+/// `--horizon` days later, because the formats can't express that
+fn exported<'a>(
+    calendar: &'a Calendar,
+    options: &Options,
+    today: NaiveDate,
+) -> Vec<(&'a SourcedEvent, Option<Vec<NaiveDate>>)> {
+    let horizon = today + chrono::Days::new(options.horizon);
+    EventQuery::new()
+        .with_options(options)
+        // short events like markers are only left out of what's shown
+        .min_duration(chrono::Duration::zero())
+        .apply(calendar)
+        .into_iter()
+        .map(|sourced| match &sourced.event {
+            Event::Recurring {
+                skip_holidays,
+                week_of_month,
+                ..
+            } if ((*skip_holidays || options.skip_holidays) && !calendar.holidays.is_empty())
+                || calendar.vacations().next().is_some()
+                || week_of_month.is_some() =>
+            {
+                let dates = sourced
+                    .event
+                    .occurrences(today, horizon)
+                    .filter(|date| calendar.shown_on(&sourced.event, *date, options.skip_holidays))
+                    .collect();
+                (sourced, Some(dates))
+            }
+            _ => (sourced, None),
+        })
+        .collect()
+}
+
+/// Every event as a line of remind(1) syntax, e.g.
+/// `REM May 3 2024 AT 14:00 DURATION 1:00 MSG Dentist`
+pub fn remind(calendar: &Calendar, options: &Options, today: NaiveDate) -> String {
+    let mut out = String::new();
+    for (sourced, dates) in exported(calendar, options, today) {
+        let event = &sourced.event;
+        let msg = remind_escape(event.title());
+        match (event, dates) {
+            (
+                Event::Once {
+                    begin, end, day, ..
+                },
+                _,
+            ) => {
+                out += &format!(
+                    "REM {} {} MSG {}\n",
+                    remind_date(*day),
+                    remind_time(*begin, *end),
+                    msg
+                )
+            }
+            (Event::Recurring { begin, end, .. }, Some(dates)) => {
+                for date in dates {
+                    out += &format!(
+                        "REM {} {} MSG {}\n",
+                        remind_date(date),
+                        remind_time(*begin, *end),
+                        msg
+                    )
+                }
+            }
+            (
+                Event::Recurring {
+                    begin,
+                    end,
+                    begin_recur,
+                    end_recur,
+                    recur_days,
+                    ..
+                },
+                None,
+            ) => {
+                let mut rem = String::from("REM");
+                for day in recur_days {
+                    rem += &format!(" {}", day);
+                }
+                if let Some(begin_recur) = begin_recur {
+                    rem += &format!(" FROM {}", remind_date(*begin_recur));
+                }
+                if let Some(end_recur) = end_recur {
+                    rem += &format!(" UNTIL {}", remind_date(*end_recur));
+                }
+                out += &format!("{} {} MSG {}\n", rem, remind_time(*begin, *end), msg);
+            }
+            (
+                Event::AllDay {
+                    begin_date,
+                    end_date,
+                    ..
+                },
+                _,
+            ) => {
+                let last_day = end_date.pred_opt().unwrap_or(*end_date);
+                if last_day <= *begin_date {
+                    out += &format!("REM {} MSG {}\n", remind_date(*begin_date), msg);
+                } else {
+                    out += &format!(
+                        "REM {} THROUGH {} MSG {}\n",
+                        remind_date(*begin_date),
+                        remind_date(last_day),
+                        msg
+                    );
+                }
+            }
+            (Event::Deadline { due, .. }, _) => {
+                out += &format!("REM {} MSG {}\n", remind_date(*due), msg);
+            }
+        }
+    }
+    out
+}
+
+fn taskwarrior_task(title: &str, due: NaiveDateTime) -> String {
+    format!(
+        "{{\"description\":{},\"status\":\"pending\",\"due\":\"{}\"}}",
+        json_string(title),
+        due.format("%Y-%m-%dT%H:%M:%S")
+    )
+}
+
+/// Every event as a task for `task import`, one JSON object per line, due when the event starts.
+/// Taskwarrior can't repeat a task on some weekdays only, so recurring events become a task for
+/// each day they happen on between `today` and `--horizon` days later.
+pub fn taskwarrior(calendar: &Calendar, options: &Options, today: NaiveDate) -> String {
+    let horizon = today + chrono::Days::new(options.horizon);
+    let mut out = String::new();
+    for (sourced, dates) in exported(calendar, options, today) {
+        let event = &sourced.event;
+        let days = match event {
+            Event::Once { day, .. } => vec![*day],
+            Event::Recurring { .. } => {
+                dates.unwrap_or_else(|| event.occurrences(today, horizon).collect())
+            }
+            Event::AllDay { begin_date, .. } => vec![*begin_date],
+            Event::Deadline { due, .. } => vec![*due],
+        };
+        let due = days
+            .into_iter()
+            .filter_map(|date| Some(event.interval_on(date)?.0));
+        for due in due {
+            out += &taskwarrior_task(event.title(), due);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// An org-mode timestamp like `<2024-05-03 Fri 14:00-15:00 +1w>`
+fn org_timestamp(date: NaiveDate, times: Option<(NaiveTime, NaiveTime)>, repeater: &str) -> String {
+    let mut out = format!("<{}", date.format("%Y-%m-%d %a"));
+    if let Some((begin, end)) = times {
+        out += &format!(" {}-{}", begin.format("%H:%M"), end.format("%H:%M"));
+    }
+    if !repeater.is_empty() {
+        out += &format!(" {}", repeater);
+    }
+    out.push('>');
+    out
+}
+
+/// Every event as an org-mode headline with an active timestamp under it, so that org-agenda
+/// picks them up. Recurring events get a `+1w` (or `+1d`) repeater for each weekday, except
+/// those with an end, skipped on holidays or monthly, which org can't express and so get a
+/// timestamp for each day they happen on up to `--horizon` days from today.
+pub fn org(calendar: &Calendar, options: &Options, today: NaiveDate) -> String {
+    let horizon = today + chrono::Days::new(options.horizon);
+    let mut out = String::new();
+    for (sourced, dates) in exported(calendar, options, today) {
+        let event = &sourced.event;
+        let timestamps: Vec<String> = match event {
+            Event::Once {
+                begin, end, day, ..
+            } => {
+                vec![org_timestamp(*day, Some((*begin, *end)), "")]
+            }
+            Event::Recurring {
+                begin,
+                end,
+                begin_recur,
+                end_recur,
+                recur_days,
+                ..
+            } => {
+                let times = Some((*begin, *end));
+                // with no start, repeat from the last time it happened
+                let first = begin_recur.unwrap_or(today - chrono::Days::new(6));
+                match dates
+                    .or_else(|| end_recur.map(|_| event.occurrences(today, horizon).collect()))
+                {
+                    Some(dates) => dates
+                        .into_iter()
+                        .map(|date| org_timestamp(date, times, ""))
+                        .collect(),
+                    None if recur_days.len() == 7 => vec![org_timestamp(first, times, "+1d")],
+                    None => first
+                        .iter_days()
+                        .take(7)
+                        .filter(|date| recur_days.contains(&date.weekday()))
+                        .map(|date| org_timestamp(date, times, "+1w"))
+                        .collect(),
+                }
+            }
+            Event::AllDay {
+                begin_date,
+                end_date,
+                ..
+            } => {
+                let last_day = end_date.pred_opt().unwrap_or(*end_date);
+                if last_day <= *begin_date {
+                    vec![org_timestamp(*begin_date, None, "")]
+                } else {
+                    vec![format!(
+                        "{}--{}",
+                        org_timestamp(*begin_date, None, ""),
+                        org_timestamp(last_day, None, "")
+                    )]
+                }
+            }
+            Event::Deadline { due, .. } => {
+                vec![format!("DEADLINE: {}", org_timestamp(*due, None, ""))]
+            }
+        };
+        out += &org_headline(event.title(), &timestamps);
+    }
+    out
+}
+
+fn org_headline(title: &str, timestamps: &[String]) -> String {
+    let mut out = format!("* {}\n", title.replace(['\n', '\r'], " "));
+    for timestamp in timestamps {
+        out += &format!("  {}\n", timestamp);
+    }
+    out
+}
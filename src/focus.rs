@@ -0,0 +1,80 @@
+use crate::event::{Event, SourcedEvent};
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use std::path::PathBuf;
+
+/// The most focus blocks suggested in a day
+pub const MAX_FOCUS_BLOCKS: usize = 2;
+
+pub const FOCUS_TITLE: &str = "Focus block";
+
+/// The free time between `from` and `to` which no timed event overlaps, in order
+pub fn free_gaps(
+    events: &[&SourcedEvent],
+    date: NaiveDate,
+    from: NaiveDateTime,
+    to: NaiveDateTime,
+) -> Vec<(NaiveDateTime, NaiveDateTime)> {
+    let busy = events
+        .iter()
+        .filter_map(|sourced| sourced.event.times_on(date))
+        .collect();
+    gaps_between(busy, from, to)
+}
+
+/// The time between `from` and `to` outside all of the `busy` intervals, in order. Intervals can
+/// overlap and come in any order, and ones which touch leave no gap between them.
+pub fn gaps_between(
+    mut busy: Vec<(NaiveDateTime, NaiveDateTime)>,
+    from: NaiveDateTime,
+    to: NaiveDateTime,
+) -> Vec<(NaiveDateTime, NaiveDateTime)> {
+    busy.sort();
+    let mut gaps = Vec::new();
+    let mut free_from = from;
+    for (begin, end) in busy {
+        if begin > free_from && free_from < to {
+            gaps.push((free_from, begin.min(to)));
+        }
+        free_from = free_from.max(end);
+    }
+    if free_from < to {
+        gaps.push((free_from, to));
+    }
+    gaps
+}
+
+/// The gaps at least `min` long to suggest focus blocks in: the longest `max` of them, with the
+/// earlier of two equally long gaps first, listed in order of time
+pub fn suggest(
+    gaps: &[(NaiveDateTime, NaiveDateTime)],
+    min: Duration,
+    max: usize,
+) -> Vec<(NaiveDateTime, NaiveDateTime)> {
+    let mut suggestions: Vec<(NaiveDateTime, NaiveDateTime)> = gaps
+        .iter()
+        .copied()
+        .filter(|(begin, end)| *end - *begin >= min)
+        .collect();
+    suggestions.sort_by_key(|(begin, end)| (std::cmp::Reverse(*end - *begin), *begin));
+    suggestions.truncate(max);
+    suggestions.sort();
+    suggestions
+}
+
+/// A "Focus block" event filling the gap, which isn't from any note
+pub fn focus_event((begin, end): (NaiveDateTime, NaiveDateTime)) -> SourcedEvent {
+    SourcedEvent {
+        synthetic: true,
+        ..SourcedEvent::new(
+            Event::Once {
+                title: FOCUS_TITLE.into(),
+                begin: begin.time(),
+                end: end.time(),
+                day: begin.date(),
+                travel_minutes: None,
+                actual_end: None,
+            },
+            PathBuf::new(),
+        )
+    }
+}
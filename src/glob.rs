@@ -0,0 +1,107 @@
+use crate::error::Error;
+use std::path::Path;
+
+/// A pattern for notes to leave out, like `*.archive.md` or `archive/**`, matched against the
+/// path of the note relative to the calendar directory it's in, with `/` between folders.
+///
+/// `*` matches any run of characters within a name and `?` any one character, while `**` on its
+/// own between slashes matches any number of folders. Like in a `.gitignore`, a pattern without
+/// a `/` matches the name of a note or folder anywhere below the directory, and matching a folder
+/// leaves out everything in it. A leading `/` only anchors the pattern to the directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Glob {
+    pattern: String,
+    components: Vec<String>,
+    /// matches the whole relative path rather than any one name in it
+    anchored: bool,
+}
+
+impl Glob {
+    pub fn new(pattern: &str) -> Result<Self, Error> {
+        let trimmed = pattern.trim_end_matches('/');
+        let anchored = trimmed.contains('/');
+        let components: Vec<String> = trimmed
+            .trim_start_matches('/')
+            .split('/')
+            .map(String::from)
+            .collect();
+        if components.iter().any(String::is_empty) {
+            return Err(Error::Usage(format!(
+                "Invalid ignore pattern '{}'",
+                pattern
+            )));
+        }
+        Ok(Self {
+            pattern: pattern.into(),
+            components,
+            anchored,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Whether the note at `path`, relative to its calendar directory, is left out, either itself
+    /// or because one of the folders it's in is
+    pub fn matches(&self, path: &Path) -> bool {
+        let names: Vec<String> = path
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        if !self.anchored {
+            return names
+                .iter()
+                .any(|name| matches_name(&self.components[0], name));
+        }
+        (1..=names.len()).any(|len| matches_components(&self.components, &names[..len]))
+    }
+}
+
+/// Matches a pattern split at its slashes against a path split at its slashes, where `**` matches
+/// any number of whole names
+fn matches_components(pattern: &[String], names: &[String]) -> bool {
+    match pattern.split_first() {
+        None => names.is_empty(),
+        Some((first, rest)) if first == "**" => {
+            (0..=names.len()).any(|skip| matches_components(rest, &names[skip..]))
+        }
+        Some((first, rest)) => names.split_first().is_some_and(|(name, names)| {
+            matches_name(first, name) && matches_components(rest, names)
+        }),
+    }
+}
+
+/// Matches a single name against a pattern with `*` and `?` wildcards
+fn matches_name(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    // the usual backtracking to the last `*`, which only ever has to go back to the latest one
+    let (mut p, mut n) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some('?') => {
+                p += 1;
+                n += 1;
+            }
+            Some(c) if *c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    p = star_p + 1;
+                    n = star_n + 1;
+                    star = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
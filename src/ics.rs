@@ -0,0 +1,236 @@
+use crate::{error::Error, event::Event};
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Weekday};
+use std::sync::Arc;
+
+/// A date or date-time value as written in an ICS file
+enum IcsTime {
+    Date(NaiveDate),
+    DateTime(NaiveDateTime),
+}
+
+fn parse_ics_time(value: &str) -> Result<IcsTime, Error> {
+    let invalid = |e| Error::Ics(format!("Invalid date or time '{}' ({})", value, e));
+    if let Some(utc) = value.strip_suffix('Z') {
+        let utc = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").map_err(invalid)?;
+        Ok(IcsTime::DateTime(
+            chrono::Utc
+                .from_utc_datetime(&utc)
+                .with_timezone(&chrono::Local)
+                .naive_local(),
+        ))
+    } else if value.contains('T') {
+        // times with a TZID are treated as local time, as there's no timezone database available
+        Ok(IcsTime::DateTime(
+            NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").map_err(invalid)?,
+        ))
+    } else {
+        Ok(IcsTime::Date(
+            NaiveDate::parse_from_str(value, "%Y%m%d").map_err(invalid)?,
+        ))
+    }
+}
+
+fn unescape_text(value: &str) -> String {
+    let mut out = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n' | 'N') => out.push(' '),
+                Some(c) => out.push(c),
+                None => (),
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn parse_byday(day: &str) -> Result<Weekday, Error> {
+    match day {
+        "MO" => Ok(Weekday::Mon),
+        "TU" => Ok(Weekday::Tue),
+        "WE" => Ok(Weekday::Wed),
+        "TH" => Ok(Weekday::Thu),
+        "FR" => Ok(Weekday::Fri),
+        "SA" => Ok(Weekday::Sat),
+        "SU" => Ok(Weekday::Sun),
+        _ => Err(Error::Ics(format!("Unsupported BYDAY '{}'", day))),
+    }
+}
+
+/// Converts the properties of one VEVENT into an Event. Only weekly and daily recurrences, and
+/// monthly ones on e.g. the first Monday (`FREQ=MONTHLY;BYDAY=1MO`), can be represented, without
+/// an INTERVAL or COUNT.
+fn parse_vevent(properties: &[(&str, String)]) -> Result<Event, Error> {
+    let get = |name| {
+        properties
+            .iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value.as_str())
+    };
+    let title: Arc<str> = unescape_text(get("SUMMARY").unwrap_or("")).into();
+    let start = parse_ics_time(get("DTSTART").ok_or(Error::Ics("VEVENT has no DTSTART".into()))?)?;
+    let end = get("DTEND").map(parse_ics_time).transpose()?;
+
+    match (start, get("RRULE")) {
+        (IcsTime::Date(begin_date), None) => Ok(Event::AllDay {
+            title,
+            begin_date,
+            end_date: match end {
+                Some(IcsTime::Date(end_date)) => end_date,
+                _ => begin_date + chrono::Days::new(1),
+            },
+            suppress_recurring: false,
+        }),
+        (IcsTime::Date(_), Some(_)) => Err(Error::Ics(format!(
+            "Recurring all-day event '{}' is not supported",
+            title
+        ))),
+        (IcsTime::DateTime(begin), rrule) => {
+            let end = match end {
+                // events that go on past midnight are cut off at the end of their first day
+                Some(IcsTime::DateTime(end)) if end.date() == begin.date() => end.time(),
+                Some(IcsTime::DateTime(_)) => NaiveTime::from_hms_opt(23, 59, 59).unwrap(),
+                _ => begin.time(),
+            };
+            let Some(rrule) = rrule else {
+                return Ok(Event::Once {
+                    title,
+                    begin: begin.time(),
+                    end,
+                    day: begin.date(),
+                    travel_minutes: None,
+                    actual_end: None,
+                });
+            };
+            let mut freq = None;
+            let mut recur_days = Vec::new();
+            // the number before each BYDAY day, like the 1 in 1MO
+            let mut ordinals = Vec::new();
+            let mut end_recur = None;
+            for part in rrule.split(';') {
+                match part.split_once('=') {
+                    Some(("FREQ", value)) => freq = Some(value),
+                    Some(("BYDAY", value)) => {
+                        for day in value.split(',') {
+                            let split = day.len().saturating_sub(2);
+                            let (ordinal, day) = day.split_at_checked(split).unwrap_or(("", day));
+                            ordinals.push(match ordinal {
+                                "" => None,
+                                ordinal => {
+                                    Some(ordinal.trim_start_matches('+').parse::<i8>().map_err(
+                                        |_| {
+                                            Error::Ics(format!(
+                                                "Unsupported BYDAY '{}{}'",
+                                                ordinal, day
+                                            ))
+                                        },
+                                    )?)
+                                }
+                            });
+                            recur_days.push(parse_byday(day)?);
+                        }
+                    }
+                    Some(("UNTIL", value)) => {
+                        end_recur = Some(match parse_ics_time(value)? {
+                            IcsTime::Date(date) => date,
+                            IcsTime::DateTime(datetime) => datetime.date(),
+                        })
+                    }
+                    Some(("WKST", _)) => (),
+                    Some(("INTERVAL", "1")) => (),
+                    _ => return Err(Error::Ics(format!("Unsupported RRULE part '{}'", part))),
+                }
+            }
+            let mut week_of_month = None;
+            match freq {
+                Some("WEEKLY" | "DAILY") if ordinals.iter().any(Option::is_some) => {
+                    return Err(Error::Ics(format!("Unsupported RRULE '{}'", rrule)))
+                }
+                // every day has to be e.g. the first one of the month, since that's all an event
+                // can say
+                Some("MONTHLY") => match ordinals.first() {
+                    Some(Some(n))
+                        if (1..=5).contains(&n.unsigned_abs())
+                            && ordinals.iter().all(|ordinal| ordinal == &Some(*n)) =>
+                    {
+                        week_of_month = Some(*n)
+                    }
+                    _ => return Err(Error::Ics(format!("Unsupported RRULE '{}'", rrule))),
+                },
+                Some("WEEKLY") if recur_days.is_empty() => recur_days.push(begin.weekday()),
+                Some("WEEKLY") => (),
+                Some("DAILY") => {
+                    recur_days = vec![
+                        Weekday::Mon,
+                        Weekday::Tue,
+                        Weekday::Wed,
+                        Weekday::Thu,
+                        Weekday::Fri,
+                        Weekday::Sat,
+                        Weekday::Sun,
+                    ]
+                }
+                _ => return Err(Error::Ics(format!("Unsupported RRULE '{}'", rrule))),
+            }
+            Ok(Event::Recurring {
+                title,
+                begin: begin.time(),
+                end,
+                begin_recur: Some(begin.date()),
+                end_recur,
+                recur_days,
+                skip_holidays: false,
+                travel_minutes: None,
+                week_of_month,
+            })
+        }
+    }
+}
+
+/// Parses every VEVENT in an ICS file. Events which can't be represented (e.g. monthly
+/// recurrences) are skipped with a warning rather than failing the whole calendar.
+pub fn parse_ics(contents: &str) -> Vec<Event> {
+    // long lines are folded by starting the continuation line with a space or tab
+    let mut lines: Vec<String> = Vec::new();
+    for line in contents.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => *last += rest,
+            _ => lines.push(line.into()),
+        }
+    }
+
+    let mut events = Vec::new();
+    let mut properties: Option<Vec<(&str, String)>> = None;
+    // how deep we are inside components nested in the VEVENT (like VALARM), which are ignored
+    let mut nested = 0;
+    for line in &lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        // drop any parameters, e.g. `DTSTART;TZID=Europe/London`
+        let name = name.split(';').next().unwrap_or(name);
+        match (name, value) {
+            ("BEGIN", "VEVENT") => properties = Some(Vec::new()),
+            ("BEGIN", _) if properties.is_some() => nested += 1,
+            ("END", _) if nested > 0 => nested -= 1,
+            _ if nested > 0 => (),
+            ("END", "VEVENT") => {
+                if let Some(properties) = properties.take() {
+                    match parse_vevent(&properties) {
+                        Ok(event) => events.push(event),
+                        Err(e) => eprintln!("Skipping calendar event: {}", e),
+                    }
+                }
+            }
+            _ => {
+                if let Some(properties) = properties.as_mut() {
+                    properties.push((name, value.into()));
+                }
+            }
+        }
+    }
+    events
+}
@@ -0,0 +1,140 @@
+use crate::{
+    error::{Error, ParseErrorKind},
+    event::SourcedEvent,
+    parse::{note_paths, parse_note, AllDayEnd, WeekdayLocale},
+};
+use chrono::NaiveDate;
+use std::path::{Path, PathBuf};
+
+/// The targets of the wiki-links (`[[target]]`) in an index note, in order and without
+/// duplicates. An alias (`[[target|label]]`) or a heading or block (`[[target#Notes]]`) is left
+/// off, since it's the whole note which is read.
+pub fn wiki_links(contents: &str) -> Vec<&str> {
+    let mut links: Vec<&str> = Vec::new();
+    let mut rest = contents;
+    while let Some(start) = rest.find("[[") {
+        rest = &rest[start + 2..];
+        let Some(end) = rest.find("]]") else {
+            break;
+        };
+        let link = &rest[..end];
+        rest = &rest[end + 2..];
+        let target = link
+            .split(['|', '#', '^'])
+            .next()
+            .unwrap_or_default()
+            .trim();
+        if !target.is_empty() && !links.contains(&target) {
+            links.push(target);
+        }
+    }
+    links
+}
+
+/// The root of the vault the index is in: the nearest folder above it with an `.obsidian`
+/// folder, or the index's own folder if there isn't one
+pub fn vault_root(index: &Path) -> PathBuf {
+    let folder = index.parent().unwrap_or(Path::new(""));
+    folder
+        .ancestors()
+        .find(|dir| dir.join(".obsidian").is_dir())
+        .unwrap_or(folder)
+        .to_path_buf()
+}
+
+/// The note which a link leads to, out of the `notes` in the vault (relative to its root), like
+/// Obsidian resolves them. `.md` can be left off. A link with a folder in it is relative to the
+/// root, while a bare name can be the note of that name in any folder, preferring the one in the
+/// fewest folders and then the first by path. Returns how many notes had the name along with it,
+/// since a link to one of several is ambiguous.
+pub fn resolve_link<'a>(notes: &'a [PathBuf], link: &str) -> Option<(&'a Path, usize)> {
+    let file = if link.ends_with(".md") {
+        link.to_string()
+    } else {
+        format!("{}.md", link)
+    };
+    let target = Path::new(&file);
+    if link.contains('/') {
+        return notes
+            .iter()
+            .find(|note| note.as_path() == target)
+            .map(|note| (note.as_path(), 1));
+    }
+    let mut candidates: Vec<&PathBuf> = notes
+        .iter()
+        .filter(|note| note.file_name() == target.file_name())
+        .collect();
+    candidates.sort_by_key(|note| (note.components().count(), *note));
+    Some((candidates.first()?.as_path(), candidates.len()))
+}
+
+/// Reads the notes linked from an index note as events, instead of every note in a directory.
+/// Links which don't lead to a note are skipped, and they and links which could lead to several
+/// are returned as warnings about the index. Like `parse_events`, the
+/// notes which couldn't be read are returned separately, after trying them twice with `retry`.
+pub fn parse_index(
+    index: &Path,
+    locale: WeekdayLocale,
+    allday_end: AllDayEnd,
+    retry: bool,
+    today: Option<NaiveDate>,
+    strip_date_prefix: bool,
+    filename_date: bool,
+) -> Result<(Vec<SourcedEvent>, Vec<Error>), Error> {
+    let contents = std::fs::read_to_string(index).map_err(|source| Error::Io {
+        path: index.to_path_buf(),
+        source,
+    })?;
+    let root = vault_root(index);
+    let mut found = Vec::new();
+    note_paths(&root, None, true, &mut found)?;
+    let mut notes: Vec<PathBuf> = found
+        .into_iter()
+        .filter_map(|(path, _)| Some(path.strip_prefix(&root).ok()?.to_path_buf()))
+        .collect();
+    notes.sort();
+    let mut read: Vec<&Path> = Vec::new();
+    let mut events = Vec::new();
+    let mut problems = Vec::new();
+    let warning = |kind| Error::Parse {
+        path: index.to_path_buf(),
+        event: None,
+        field: None,
+        kind,
+    };
+    for link in wiki_links(&contents) {
+        let Some((note, matches)) = resolve_link(&notes, link) else {
+            problems.push(warning(ParseErrorKind::DanglingLink(link.into())));
+            continue;
+        };
+        if matches > 1 {
+            problems.push(warning(ParseErrorKind::AmbiguousLink {
+                link: link.into(),
+                matches,
+                note: note.to_path_buf(),
+            }));
+        }
+        // two links, e.g. with and without `.md`, can lead to the same note
+        if read.contains(&note) {
+            continue;
+        }
+        read.push(note);
+        match parse_note(
+            root.join(note),
+            None,
+            locale,
+            allday_end,
+            retry,
+            today,
+            strip_date_prefix,
+            filename_date,
+        ) {
+            Ok((note_events, warnings)) => {
+                events.extend(note_events);
+                problems.extend(warnings);
+            }
+            Err(e) => problems.push(e),
+        }
+    }
+    Ok((events, problems))
+}
@@ -0,0 +1,358 @@
+use crate::{
+    config::{Config, ConfigValue},
+    error::Error,
+};
+use chrono::{Datelike, NaiveDate};
+use std::str::FromStr;
+
+/// A phrase with a number in it, which has a form for exactly one and a form for everything else,
+/// e.g. "{n} min" and "{n} mins"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Plural {
+    pub one: String,
+    pub other: String,
+}
+
+impl Plural {
+    fn new(one: &str, other: &str) -> Self {
+        Self {
+            one: one.into(),
+            other: other.into(),
+        }
+    }
+
+    pub fn format(&self, n: i64) -> String {
+        let phrase = if n == 1 { &self.one } else { &self.other };
+        phrase.replace("{n}", &n.to_string())
+    }
+}
+
+/// The words the output is written in. Phrases with something filled in mark where it goes with
+/// `{n}`, `{time}`, `{title}` and so on, so that it can go wherever the language puts it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lang {
+    /// single-day all-day events
+    pub today: String,
+    /// events shown from tomorrow by `--show-next-day`
+    pub tomorrow: String,
+    /// the countdown of an event which has started
+    pub now: String,
+    /// the countdown of a pinned event which is over
+    pub ended: String,
+    /// what `--next-in` prints while an event is ongoing
+    pub next_in_now: String,
+    /// only used by `--seconds`
+    pub seconds: Plural,
+    pub minutes: Plural,
+    pub hours: Plural,
+    /// how long an all-day event lasts
+    pub days: Plural,
+    /// how long is left of an all-day event
+    pub days_left: Plural,
+    /// how many events `--header` says there are
+    pub events: Plural,
+    pub last_day: String,
+    pub ends_tomorrow: String,
+    pub leave_now: String,
+    /// "leave in {time}", where the time is e.g. "5 mins"
+    pub leave_in: String,
+    /// "ends in {time}"
+    pub ends_in: String,
+    /// "over by {time}", for events kept by `--grace` after they end
+    pub over_by: String,
+    /// "running over, ends {time}", for events with an `actualEnd` after their end, where the
+    /// time is e.g. "15:20"
+    pub running_over: String,
+    /// e.g. "1h 20m", as used by `--humanize` and `--show-duration`
+    pub short_hours: String,
+    pub short_minutes: String,
+    /// "{n} of {total}", for `--show-occurrence-index`
+    pub occurrence: String,
+    /// in place of the times of all-day events in the week view
+    pub all_day: String,
+    /// in place of the times of deadlines in the week view
+    pub due: String,
+    /// a deadline on the day it's due
+    pub due_today: String,
+    /// a deadline shown ahead of its day by `--deadline-lookahead`
+    pub due_in: Plural,
+    /// a deadline shown after its day by `--show-overdue`
+    pub overdue: Plural,
+    /// the lines printed by `--diff-state`
+    pub added: String,
+    pub removed: String,
+    pub moved: String,
+    /// what the bar formats show when there are no events left, unless `--empty-text` says
+    /// otherwise
+    pub no_events: String,
+    /// what `avail` says about a day without any free time
+    pub no_free_time: String,
+    /// the top of `digest`, "Week {week}: {first} – {last} {year}"
+    pub digest_heading: String,
+    /// how long is scheduled on a day in `digest`, "{time} scheduled"
+    pub scheduled: String,
+    pub nothing_scheduled: String,
+    /// the heading of the recurring events which end that week in `digest`
+    pub ending_this_week: String,
+    /// "{title}, last on {date}"
+    pub last_on: String,
+    /// abbreviated, Monday first
+    pub weekdays: [String; 7],
+    /// abbreviated, January first
+    pub months: [String; 12],
+    /// in full, January first
+    pub month_names: [String; 12],
+}
+
+fn strings<const N: usize>(strings: [&str; N]) -> [String; N] {
+    strings.map(String::from)
+}
+
+impl Default for Lang {
+    fn default() -> Self {
+        Self::english()
+    }
+}
+
+impl Lang {
+    pub fn english() -> Self {
+        Self {
+            today: "Today".into(),
+            tomorrow: "Tomorrow".into(),
+            now: "Now".into(),
+            ended: "Ended".into(),
+            next_in_now: "now".into(),
+            seconds: Plural::new("{n} sec", "{n} secs"),
+            minutes: Plural::new("{n} min", "{n} mins"),
+            hours: Plural::new("{n} hour", "{n} hours"),
+            days: Plural::new("{n} day", "{n} days"),
+            days_left: Plural::new("{n} day left", "{n} days left"),
+            events: Plural::new("{n} event", "{n} events"),
+            last_day: "last day".into(),
+            ends_tomorrow: "ends tomorrow".into(),
+            leave_now: "leave now".into(),
+            leave_in: "leave in {time}".into(),
+            ends_in: "ends in {time}".into(),
+            over_by: "over by {time}".into(),
+            running_over: "running over, ends {time}".into(),
+            short_hours: "{n}h".into(),
+            short_minutes: "{n}m".into(),
+            occurrence: "{n} of {total}".into(),
+            all_day: "All day".into(),
+            due: "Due".into(),
+            due_today: "Due today".into(),
+            due_in: Plural::new("Due in {n} day", "Due in {n} days"),
+            overdue: Plural::new("Overdue {n} day", "Overdue {n} days"),
+            added: "Added: {when} {title}".into(),
+            removed: "Removed: {when} {title}".into(),
+            moved: "Moved: {title} from {before} to {after}".into(),
+            no_events: "No events".into(),
+            no_free_time: "no free time".into(),
+            digest_heading: "Week {week}: {first} – {last} {year}".into(),
+            scheduled: "{time} scheduled".into(),
+            nothing_scheduled: "nothing scheduled".into(),
+            ending_this_week: "Ending this week".into(),
+            last_on: "{title}, last on {date}".into(),
+            weekdays: strings(["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"]),
+            months: strings([
+                "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+            ]),
+            month_names: strings([
+                "January",
+                "February",
+                "March",
+                "April",
+                "May",
+                "June",
+                "July",
+                "August",
+                "September",
+                "October",
+                "November",
+                "December",
+            ]),
+        }
+    }
+
+    pub fn german() -> Self {
+        Self {
+            today: "Heute".into(),
+            tomorrow: "Morgen".into(),
+            now: "Jetzt".into(),
+            ended: "Vorbei".into(),
+            next_in_now: "jetzt".into(),
+            seconds: Plural::new("{n} Sek.", "{n} Sek."),
+            minutes: Plural::new("{n} Min.", "{n} Min."),
+            hours: Plural::new("{n} Std.", "{n} Std."),
+            days: Plural::new("{n} Tag", "{n} Tage"),
+            days_left: Plural::new("noch {n} Tag", "noch {n} Tage"),
+            events: Plural::new("{n} Termin", "{n} Termine"),
+            last_day: "letzter Tag".into(),
+            ends_tomorrow: "endet morgen".into(),
+            leave_now: "jetzt losgehen".into(),
+            leave_in: "losgehen in {time}".into(),
+            ends_in: "endet in {time}".into(),
+            over_by: "{time} überzogen".into(),
+            running_over: "überzieht, endet {time}".into(),
+            short_hours: "{n} Std.".into(),
+            short_minutes: "{n} Min.".into(),
+            occurrence: "{n} von {total}".into(),
+            all_day: "Ganztägig".into(),
+            due: "Fällig".into(),
+            due_today: "Heute fällig".into(),
+            due_in: Plural::new("Fällig in {n} Tag", "Fällig in {n} Tagen"),
+            overdue: Plural::new("{n} Tag überfällig", "{n} Tage überfällig"),
+            added: "Neu: {when} {title}".into(),
+            removed: "Entfernt: {when} {title}".into(),
+            moved: "Verschoben: {title} von {before} auf {after}".into(),
+            no_events: "Keine Termine".into(),
+            no_free_time: "keine freie Zeit".into(),
+            digest_heading: "KW {week}: {first} – {last} {year}".into(),
+            scheduled: "{time} geplant".into(),
+            nothing_scheduled: "nichts geplant".into(),
+            ending_this_week: "Endet diese Woche".into(),
+            last_on: "{title}, zuletzt am {date}".into(),
+            weekdays: strings(["Mo", "Di", "Mi", "Do", "Fr", "Sa", "So"]),
+            months: strings([
+                "Jan", "Feb", "Mär", "Apr", "Mai", "Jun", "Jul", "Aug", "Sep", "Okt", "Nov", "Dez",
+            ]),
+            month_names: strings([
+                "Januar",
+                "Februar",
+                "März",
+                "April",
+                "Mai",
+                "Juni",
+                "Juli",
+                "August",
+                "September",
+                "Oktober",
+                "November",
+                "Dezember",
+            ]),
+        }
+    }
+
+    /// Replaces the phrases set in the config file's `[strings]` section, e.g. `now = "Jetzt"`.
+    /// Plurals are a list of the form for one and the form for everything else, e.g.
+    /// `minutes = ["{n} Minute", "{n} Minuten"]`, and the names of days and months are lists too.
+    pub fn with_overrides(mut self, config: &Config) -> Result<Self, Error> {
+        for (key, value) in config.section("strings") {
+            let error = |expected: &str| {
+                Error::Config(format!(
+                    "Config key 'strings.{}' should be {}",
+                    key, expected
+                ))
+            };
+            let text = || match value {
+                ConfigValue::String(s) => Ok(s.clone()),
+                _ => Err(error("a string")),
+            };
+            let list = |len: usize| match value {
+                ConfigValue::List(items) if items.len() == len => Ok(items.clone()),
+                _ => Err(error(&format!("a list of {} strings", len))),
+            };
+            let plural = || {
+                list(2).map(|forms| Plural {
+                    one: forms[0].clone(),
+                    other: forms[1].clone(),
+                })
+            };
+            let array = |target: &mut [String]| -> Result<(), Error> {
+                target.clone_from_slice(&list(target.len())?);
+                Ok(())
+            };
+            match key {
+                "today" => self.today = text()?,
+                "tomorrow" => self.tomorrow = text()?,
+                "now" => self.now = text()?,
+                "ended" => self.ended = text()?,
+                "next_in_now" => self.next_in_now = text()?,
+                "seconds" => self.seconds = plural()?,
+                "minutes" => self.minutes = plural()?,
+                "hours" => self.hours = plural()?,
+                "days" => self.days = plural()?,
+                "days_left" => self.days_left = plural()?,
+                "events" => self.events = plural()?,
+                "last_day" => self.last_day = text()?,
+                "ends_tomorrow" => self.ends_tomorrow = text()?,
+                "leave_now" => self.leave_now = text()?,
+                "leave_in" => self.leave_in = text()?,
+                "ends_in" => self.ends_in = text()?,
+                "over_by" => self.over_by = text()?,
+                "running_over" => self.running_over = text()?,
+                "short_hours" => self.short_hours = text()?,
+                "short_minutes" => self.short_minutes = text()?,
+                "occurrence" => self.occurrence = text()?,
+                "all_day" => self.all_day = text()?,
+                "due" => self.due = text()?,
+                "due_today" => self.due_today = text()?,
+                "due_in" => self.due_in = plural()?,
+                "overdue" => self.overdue = plural()?,
+                "added" => self.added = text()?,
+                "removed" => self.removed = text()?,
+                "moved" => self.moved = text()?,
+                "no_events" => self.no_events = text()?,
+                "no_free_time" => self.no_free_time = text()?,
+                "digest_heading" => self.digest_heading = text()?,
+                "scheduled" => self.scheduled = text()?,
+                "nothing_scheduled" => self.nothing_scheduled = text()?,
+                "ending_this_week" => self.ending_this_week = text()?,
+                "last_on" => self.last_on = text()?,
+                "weekdays" => array(&mut self.weekdays)?,
+                "months" => array(&mut self.months)?,
+                "month_names" => array(&mut self.month_names)?,
+                _ => {
+                    return Err(Error::Config(format!(
+                        "Unknown config key 'strings.{}'",
+                        key
+                    )))
+                }
+            }
+        }
+        Ok(self)
+    }
+
+    /// e.g. "Mon"
+    pub fn weekday(&self, weekday: chrono::Weekday) -> &str {
+        &self.weekdays[weekday.num_days_from_monday() as usize]
+    }
+
+    /// e.g. "May 03", in place of `%b %d`
+    pub fn month_day(&self, date: NaiveDate) -> String {
+        format!("{} {:02}", self.months[date.month0() as usize], date.day())
+    }
+
+    /// e.g. "Fri 03 May", in place of `%a %d %b`
+    pub fn weekday_date(&self, date: NaiveDate) -> String {
+        format!(
+            "{} {:02} {}",
+            self.weekday(date.weekday()),
+            date.day(),
+            self.months[date.month0() as usize]
+        )
+    }
+
+    /// e.g. "May 2024", in place of `%B %Y`
+    pub fn month_year(&self, date: NaiveDate) -> String {
+        format!(
+            "{} {}",
+            self.month_names[date.month0() as usize],
+            date.year()
+        )
+    }
+}
+
+impl FromStr for Lang {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "en" => Ok(Self::english()),
+            "de" => Ok(Self::german()),
+            _ => Err(Error::Usage(format!(
+                "Unknown language '{}' (expected en or de)",
+                s
+            ))),
+        }
+    }
+}
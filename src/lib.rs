@@ -0,0 +1,203 @@
+pub mod archive;
+pub mod avail;
+pub mod calendar;
+pub mod cli;
+pub mod config;
+pub mod diff;
+pub mod digest;
+pub mod error;
+pub mod event;
+pub mod export;
+pub mod focus;
+pub mod glob;
+pub mod ics;
+pub mod index;
+pub mod lang;
+pub mod month;
+pub mod open;
+pub mod output;
+pub mod parse;
+pub mod query;
+pub mod remote;
+pub mod render;
+pub mod schema;
+pub mod snooze;
+pub mod theme;
+pub mod timeline;
+pub mod week;
+
+use chrono::NaiveTime;
+use cli::{AllDayPosition, SortBy, Tiebreak};
+use event::{Event, SourcedEvent};
+use std::cmp::Ordering;
+
+/// How events are ordered: by which of their times, where all-day events go, and which way
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortSpec {
+    pub by: SortBy,
+    pub allday_position: AllDayPosition,
+    /// latest first. All-day events are still placed by `allday_position`, except with
+    /// `AllDayPosition::Inline`, where they're the last thing of the day.
+    pub reverse: bool,
+    /// how events at the same time are ordered, which `reverse` doesn't change
+    pub tiebreak: Tiebreak,
+}
+
+impl Default for SortSpec {
+    fn default() -> Self {
+        Self {
+            by: SortBy::Start,
+            allday_position: AllDayPosition::Top,
+            reverse: false,
+            tiebreak: Tiebreak::Title,
+        }
+    }
+}
+
+/// One of the things events are ordered by, in the order `SortSpec` tries them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    /// all-day events before or after timed ones, as `allday_position` says, with deadlines
+    /// between them
+    AllDay,
+    /// the time given by `by`, latest first with `reverse`
+    Time,
+    /// `tiebreak`, for events at the same time
+    Tiebreak,
+}
+
+impl SortSpec {
+    /// The keys in the order they're tried. The first which tells two events apart decides which
+    /// comes first.
+    pub const KEYS: [SortKey; 3] = [SortKey::AllDay, SortKey::Time, SortKey::Tiebreak];
+
+    /// The time a timed event is ordered by, and whether it's on the next day, since an event
+    /// which ends past midnight ends after every event which ends today
+    fn time(&self, event: &Event) -> Option<(bool, NaiveTime)> {
+        match self.by {
+            SortBy::Start => Some((false, event.begin()?)),
+            SortBy::End => Some((event.end()? < event.begin()?, event.end()?)),
+        }
+    }
+
+    /// `time`, with all-day events at 00:00 when they're placed inline
+    fn sort_time(&self, event: &Event) -> Option<(bool, NaiveTime)> {
+        self.time(event)
+            .or((self.allday_position == AllDayPosition::Inline).then_some((false, NaiveTime::MIN)))
+    }
+
+    fn compare_placement(&self, a: &Event, b: &Event) -> Ordering {
+        let deadline = |event: &Event| matches!(event, Event::Deadline { .. });
+        match (self.time(a), self.time(b), self.allday_position) {
+            (None, None, AllDayPosition::Bottom) => deadline(b).cmp(&deadline(a)),
+            (None, None, _) => deadline(a).cmp(&deadline(b)),
+            (None, Some(_), AllDayPosition::Top) | (Some(_), None, AllDayPosition::Bottom) => {
+                Ordering::Less
+            }
+            (Some(_), None, AllDayPosition::Top) | (None, Some(_), AllDayPosition::Bottom) => {
+                Ordering::Greater
+            }
+            _ => Ordering::Equal,
+        }
+    }
+
+    fn compare_time(&self, a: &Event, b: &Event) -> Ordering {
+        match (self.sort_time(a), self.sort_time(b)) {
+            (Some(a_time), Some(b_time)) if self.reverse => b_time.cmp(&a_time),
+            (Some(a_time), Some(b_time)) => a_time.cmp(&b_time),
+            _ => Ordering::Equal,
+        }
+    }
+
+    /// How `key` alone orders the events, or `Equal` if it doesn't tell them apart. Events
+    /// without a note to have been edited, like focus blocks, count as edited longest ago.
+    pub fn compare_by(&self, key: SortKey, a: &SourcedEvent, b: &SourcedEvent) -> Ordering {
+        match key {
+            SortKey::AllDay => self.compare_placement(&a.event, &b.event),
+            SortKey::Time => self.compare_time(&a.event, &b.event),
+            SortKey::Tiebreak => match self.tiebreak {
+                Tiebreak::Title => a.event.title().cmp(b.event.title()),
+                Tiebreak::Mtime => b.modified.cmp(&a.modified),
+                Tiebreak::Path => a.path.cmp(&b.path),
+            },
+        }
+    }
+
+    /// The order of the events by where all-day events go and their times, without `tiebreak`
+    pub fn compare(&self, a: &Event, b: &Event) -> Ordering {
+        self.compare_placement(a, b)
+            .then_with(|| self.compare_time(a, b))
+    }
+
+    /// The order of the events by each of `KEYS` in turn
+    pub fn compare_sourced(&self, a: &SourcedEvent, b: &SourcedEvent) -> Ordering {
+        self.deciding_key(a, b)
+            .map_or(Ordering::Equal, |key| self.compare_by(key, a, b))
+    }
+
+    /// The first of `KEYS` which tells the events apart, or None if they're tied on all of them
+    pub fn deciding_key(&self, a: &SourcedEvent, b: &SourcedEvent) -> Option<SortKey> {
+        Self::KEYS
+            .into_iter()
+            .find(|key| self.compare_by(*key, a, b) != Ordering::Equal)
+    }
+
+    /// Why `a` comes before `b`, e.g. "Standup < Planning: begin 09:00 < 10:00", for
+    /// `--explain-sort`
+    pub fn explain(&self, a: &SourcedEvent, b: &SourcedEvent) -> String {
+        let titles = format!("{} < {}", a.event.title(), b.event.title());
+        let time = |event: &Event| match self.sort_time(event) {
+            _ if self.time(event).is_none() => "00:00 (all day)".to_string(),
+            Some((true, time)) => format!("{} (next day)", time.format("%H:%M")),
+            Some((false, time)) => time.format("%H:%M").to_string(),
+            None => String::new(),
+        };
+        match self.deciding_key(a, b) {
+            None => format!(
+                "{} = {}: tied, so in the order they were read",
+                a.event.title(),
+                b.event.title()
+            ),
+            Some(SortKey::AllDay)
+                if self.time(&a.event).is_none() && self.time(&b.event).is_none() =>
+            {
+                format!("{}: deadlines go between all-day and timed events", titles)
+            }
+            Some(SortKey::AllDay) => format!(
+                "{}: all-day events {}",
+                titles,
+                if self.allday_position == AllDayPosition::Top {
+                    "first"
+                } else {
+                    "last"
+                }
+            ),
+            Some(SortKey::Time) => format!(
+                "{}: {} {} {} {}{}",
+                titles,
+                match self.by {
+                    SortBy::Start => "begin",
+                    SortBy::End => "end",
+                },
+                time(&a.event),
+                if self.reverse { ">" } else { "<" },
+                time(&b.event),
+                if self.reverse { " (reversed)" } else { "" }
+            ),
+            Some(SortKey::Tiebreak) => match self.tiebreak {
+                Tiebreak::Title => format!("{}: same time, by title", titles),
+                Tiebreak::Mtime => format!(
+                    "{}: same time, {} edited more recently",
+                    titles,
+                    a.path.display()
+                ),
+                Tiebreak::Path => format!(
+                    "{}: same time, path {} < {}",
+                    titles,
+                    a.path.display(),
+                    b.path.display()
+                ),
+            },
+        }
+    }
+}
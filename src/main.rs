@@ -1,324 +1,556 @@
-use chrono::{Datelike, NaiveDate, NaiveTime, Timelike, Weekday};
-use std::{collections::HashMap, io::Read};
+use chrono::{NaiveDate, NaiveDateTime};
+use obsidian_calendar_summary::{
+    archive, avail,
+    calendar::Calendar,
+    cli::{Command, ErrorFormat, Format, Options, SnoozeAction},
+    diff::{self, Snapshot},
+    digest,
+    error::Error,
+    event::SourcedEvent,
+    export, focus, month, open,
+    query::{self, EventQuery},
+    render, schema,
+    snooze::{self, SnoozeTarget, Snoozed},
+    timeline, week,
+};
+use std::{io::IsTerminal, path::Path};
 
-#[derive(Debug)]
-enum Event {
-    Once {
-        title: String,
-        begin: NaiveTime,
-        end: NaiveTime,
-        day: NaiveDate,
-    },
-    Recurring {
-        title: String,
-        begin: NaiveTime,
-        end: NaiveTime,
-        begin_recur: NaiveDate,
-        end_recur: Option<NaiveDate>,
-        recur_days: Vec<chrono::Weekday>,
-    },
-    AllDay {
-        title: String,
-        begin_date: NaiveDate,
-        end_date: NaiveDate,
-    },
+/// What's shown of each of the events on `date`
+fn fields<'a>(
+    events: &[&'a SourcedEvent],
+    date: NaiveDate,
+    options: &'a Options,
+    now: NaiveDateTime,
+) -> Vec<render::Fields<'a>> {
+    events
+        .iter()
+        .map(|event| {
+            render::Fields::new(
+                event,
+                date,
+                now,
+                options.sort.by,
+                &options.lang,
+                options.seconds,
+                options.grace.unwrap_or_default(),
+            )
+        })
+        .collect()
 }
 
-impl Event {
-    // fn begin(&self) -> &NaiveTime {
-    //     match self {
-    //         Self::Once { begin, .. } | Self::Recurring { begin, .. } => begin,
-    //     }
-    // }
-    // fn end(&self) -> &NaiveTime {
-    //     match self {
-    //         Self::Once { end, .. } | Self::Recurring { end, .. } => end,
-    //     }
-    // }
-    fn title(&self) -> &str {
-        match self {
-            Self::Once { title, .. }
-            | Self::Recurring { title, .. }
-            | Self::AllDay { title, .. } => title,
+/// Writes the events on `date` to `out` in the format chosen by the options, returning false if
+/// there was nothing to print
+fn print_events(
+    calendar: &Calendar,
+    events: &[&SourcedEvent],
+    date: NaiveDate,
+    options: &Options,
+    now: NaiveDateTime,
+    out: &mut String,
+) -> bool {
+    let mut fields = fields(events, date, options, now);
+    for (fields, sourced) in fields.iter_mut().zip(events) {
+        fields.numbered = sourced.numbered.as_deref().zip(calendar.occurrence_number(
+            &sourced.event,
+            date,
+            options.skip_holidays,
+        ));
+    }
+    if options.progress {
+        render::keep_soonest_progress(&mut fields);
+    } else {
+        fields.iter_mut().for_each(|fields| fields.progress = None);
+    }
+    if options.relative_dates {
+        render::relative_days(&mut fields, now.date());
+    }
+    if let Some(step) = options.round {
+        render::round_times(&mut fields, events, step);
+    }
+    if !options.show_duration {
+        fields.iter_mut().for_each(|fields| fields.duration = None);
+    }
+    // always listed in JSON, where it's a field of its own
+    if !options.show_occurrence_index && options.format != Format::Json {
+        fields
+            .iter_mut()
+            .for_each(|fields| fields.occurrence = None);
+    }
+    // only in the terminal's format, since anything reading the others wouldn't expect it
+    if options.header && options.format == Format::Text && !options.next_in {
+        let count = events.iter().filter(|sourced| !sourced.synthetic).count();
+        out.push_str(&format!("{}\n", render::header(date, count, &options.lang)));
+    }
+    if options.timeline {
+        let width = options
+            .width
+            .or_else(|| std::env::var("COLUMNS").ok()?.parse().ok())
+            .unwrap_or(80);
+        let scale = timeline::Scale {
+            day_start: options.day_start,
+            day_end: options.day_end,
+            width,
+        };
+        out.push_str(&timeline::timeline(events, scale, now.time()));
+        return !events.is_empty();
+    }
+    let day = options.lang.weekday_date(date);
+    let empty_text = options
+        .empty_text
+        .as_ref()
+        .map(|empty_text| empty_text.replace("{date}", &day));
+    if options.next_in {
+        let next_in = render::next_in(&fields, options.humanize, &options.lang);
+        out.push_str(&format!(
+            "{}\n",
+            next_in
+                .as_deref()
+                .or(empty_text.as_deref())
+                .unwrap_or_default()
+        ));
+        return next_in.is_some();
+    }
+    if fields.is_empty() {
+        // a bar would show an empty module, which looks broken, so they say so by default
+        let empty_text = match options.format {
+            Format::Json | Format::Status => None,
+            Format::Tmux | Format::Polybar | Format::I3blocks => {
+                empty_text.or_else(|| Some(options.lang.no_events.clone()))
+            }
+            _ => empty_text,
+        };
+        if let Some(empty_text) = empty_text {
+            out.push_str(&format!("{}\n", empty_text));
+            return false;
+        }
+    }
+    match options.format {
+        Format::Text if options.oneline => {
+            if !fields.is_empty() {
+                out.push_str(&format!(
+                    "{}\n",
+                    render::oneline(&fields, &options.separator, options.max_width)
+                ));
+            }
+        }
+        Format::Text => {
+            for fields in &fields {
+                out.push_str(&format!("{}\n", render::line(fields)))
+            }
+        }
+        Format::Tmux => out.push_str(&format!(
+            "{}\n",
+            render::tmux(
+                &fields,
+                &options.separator,
+                options.soon,
+                options.max_width,
+                &options.palette
+            )
+        )),
+        Format::Polybar => out.push_str(&format!(
+            "{}\n",
+            render::polybar(
+                &fields,
+                &options.separator,
+                options.soon,
+                options.max_width,
+                options.click,
+                &options.palette
+            )
+        )),
+        Format::I3blocks => out.push_str(&render::i3blocks(
+            &fields,
+            &options.separator,
+            options.soon,
+            &options.palette,
+        )),
+        Format::Json if options.json_lines => {
+            out.push_str(&render::json_lines(&fields, options.summary.then_some(now)))
         }
+        Format::Json => out.push_str(&format!("{}\n", render::json(&fields))),
+        Format::Status => out.push_str(&format!(
+            "{}\n",
+            render::status(&fields, options.soon, options.status_with_title)
+        )),
+        Format::Compact => {
+            let line = render::compact(&fields, options.max_title);
+            if !line.is_empty() {
+                out.push_str(&format!("{}\n", line));
+            }
+        }
+        // these export the whole calendar, which main does before getting here
+        Format::Remind | Format::Taskwarrior | Format::Org => (),
     }
+    !fields.is_empty()
 }
 
-impl std::fmt::Display for Event {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let now = chrono::Local::now().naive_local().time();
-
-        match self {
-            Self::Once { begin, end, .. } | Self::Recurring { begin, end, .. } => {
-                let delta = *begin - now;
-                let delta_text = if delta.num_minutes() < 0 {
-                    "(Now)".into()
-                } else if delta.num_minutes() < 60 {
-                    format!(
-                        "({} min{})",
-                        delta.num_minutes(),
-                        if delta.num_minutes() != 1 { "s" } else { "" }
+fn main() {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("Error parsing arguments: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let now = options
+        .now
+        .unwrap_or_else(|| chrono::Local::now().naive_local());
+    if options.command == Command::Schema {
+        print!("{}", schema::SCHEMA);
+        return;
+    }
+    if options.command == Command::Snooze {
+        if let Err(e) = snooze(&options, now) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+    if options.command == Command::Archive {
+        if let Err(e) = archive(&options) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+    let calendar = match Calendar::load(&options) {
+        Ok(calendar) => calendar,
+        Err(e) => {
+            report_errors(&options, &[], Some(&e));
+            std::process::exit(1);
+        }
+    };
+    let mut exit_status = 0;
+    // written all at once at the end, so that `--output` never leaves half of it in the file
+    let mut out = String::new();
+    match options.command {
+        // done before loading the calendar, which they don't need
+        Command::Schema | Command::Snooze | Command::Archive => (),
+        Command::Lint => {
+            // each problem's path, severity, code and message
+            let mut found: Vec<(String, &str, &str, String)> = calendar
+                .problems
+                .iter()
+                .map(|problem| {
+                    (
+                        problem
+                            .path()
+                            .map_or("-".into(), |path| path.display().to_string()),
+                        problem.severity(),
+                        problem.code(),
+                        problem.to_string(),
                     )
-                } else {
+                })
+                .collect();
+            // events which are still shown, but probably aren't what was meant
+            let horizon = now.date() + chrono::Days::new(options.horizon);
+            for (sourced, begin) in calendar.nonexistent_times(&chrono::Local, now.date(), horizon)
+            {
+                found.push((
+                    sourced.path.display().to_string(),
+                    "warning",
+                    "nonexistent_time",
                     format!(
-                        "({} hour{})",
-                        delta.num_hours(),
-                        if delta.num_hours() != 1 { "s" } else { "" }
-                    )
-                };
-                write!(
-                    f,
-                    "{:02}:{:02} - {:02}:{:02} {:<10} | {}",
-                    begin.hour(),
-                    begin.minute(),
-                    end.hour(),
-                    end.minute(),
-                    delta_text,
-                    self.title()
-                )
+                        "{}: {} doesn't exist in local time, because the clocks go forward",
+                        sourced.path.display(),
+                        begin.format("%Y-%m-%d %H:%M")
+                    ),
+                ));
+            }
+            for (duplicate, original) in calendar.duplicate_recurrences() {
+                found.push((
+                    duplicate.path.display().to_string(),
+                    "warning",
+                    "duplicate",
+                    format!(
+                        "{}: probably a duplicate of {}, with the same title, times and days",
+                        duplicate.path.display(),
+                        original.path.display()
+                    ),
+                ));
             }
-            Self::AllDay {
-                title,
-                begin_date,
-                end_date,
-            } => {
-                if (*end_date - *begin_date).num_days() == 1 {
-                    write!(f, "Today                    | {}", title)
+            for (path, severity, code, message) in &found {
+                if options.porcelain {
+                    out.push_str(&porcelain_line(&[path, severity, code, message]));
                 } else {
-                    write!(
-                        f,
-                        "{} - {}          | {}",
-                        begin_date.format("%b %d"),
-                        end_date
-                            .checked_sub_days(chrono::Days::new(1))
-                            .unwrap() // this is unlikely to go past the limits of what chrono can handle as a date
-                            .format("%b %d"),
-                        title
-                    )
+                    out.push_str(&format!("{}\n", message));
+                }
+            }
+            // a pre-commit hook should only be stopped by notes which are actually broken
+            if found
+                .iter()
+                .any(|(_, severity, ..)| !options.porcelain || *severity == "error")
+            {
+                exit_status = 1;
+            }
+        }
+        Command::Month(month) => {
+            let month = month.unwrap_or(now.date());
+            if options.heatmap {
+                let heatmap = month::Heatmap::new(&calendar, month, options.skip_holidays);
+                match options.format {
+                    Format::Json => out.push_str(&format!("{}\n", heatmap.json())),
+                    _ => out.push_str(&heatmap.text(&options.lang)),
+                }
+            } else {
+                let highlight = std::io::stdout().is_terminal();
+                out.push_str(&month::month_grid(
+                    &calendar,
+                    &options,
+                    month,
+                    now.date(),
+                    highlight,
+                ));
+            }
+            if options.spans && !(options.heatmap && options.format == Format::Json) {
+                out.push_str(&format!(
+                    "\n{}",
+                    month::month_spans(&calendar, month, &options.lang)
+                ));
+            }
+        }
+        Command::Avail => {
+            let days = avail::free_days(&calendar, &options, now);
+            match options.format {
+                Format::Json => out.push_str(&format!("{}\n", avail::json(&days))),
+                _ => out.push_str(&avail::text(&days, &options.lang)),
+            }
+        }
+        Command::Week => {
+            let first_day = match options.week_of {
+                Some(monday) => week::first_day_of_iso_week(monday, options.week_start),
+                None => week::week_containing(now.date(), options.week_start),
+            };
+            out.push_str(&week::week(&calendar, &options, first_day));
+        }
+        Command::Digest => {
+            let first_day = match options.week_of {
+                Some(monday) => week::first_day_of_iso_week(monday, options.week_start),
+                None => week::week_containing(now.date(), options.week_start),
+            };
+            out.push_str(&digest::digest(&calendar, &options, first_day));
+        }
+        Command::OpenNext => {
+            let snoozed = load_snoozed();
+            let events = EventQuery::today(now)
+                .with_options(&options)
+                .without_snoozed(&snoozed)
+                .apply(&calendar);
+            let fields = fields(&events, now.date(), &options, now);
+            match open::open_next(&fields, &mut open::System, options.dry_run) {
+                Ok(Some(done)) => out.push_str(&format!("{}\n", done)),
+                Ok(None) => {
+                    eprintln!("No event left today has a url");
+                    exit_status = 1;
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    exit_status = 1;
                 }
             }
         }
+        Command::Today => match options.format {
+            Format::Remind => out.push_str(&export::remind(&calendar, &options, now.date())),
+            Format::Taskwarrior => {
+                out.push_str(&export::taskwarrior(&calendar, &options, now.date()))
+            }
+            Format::Org => out.push_str(&export::org(&calendar, &options, now.date())),
+            _ => {
+                let focus_blocks: Vec<SourcedEvent>;
+                let snoozed = load_snoozed();
+                let mut events = EventQuery::today(now)
+                    .with_options(&options)
+                    .without_snoozed(&snoozed)
+                    .apply(&calendar);
+                let mut date = now.date();
+                if events.is_empty()
+                    && options.show_next_day
+                    && !options.next_in
+                    && !options.timeline
+                {
+                    if let Some((next_date, event)) = calendar.next_occurrence(now.date(), &options)
+                    {
+                        date = next_date;
+                        events.push(event);
+                    }
+                }
+                // focus blocks only go in lists of today's events, and in JSON if asked for
+                let suggest_focus = options.suggest_focus.filter(|_| {
+                    date == now.date()
+                        && !options.next_in
+                        && !options.timeline
+                        && (options.format != Format::Json || options.include_synthetic)
+                });
+                if let Some(min) = suggest_focus {
+                    let midnight = date.and_time(chrono::NaiveTime::MIN);
+                    let day_start = midnight
+                        + chrono::Duration::try_seconds(options.day_start).unwrap_or_default();
+                    let day_end = midnight
+                        + chrono::Duration::try_seconds(options.day_end).unwrap_or_default();
+                    let gaps = focus::free_gaps(&events, date, now.max(day_start), day_end);
+                    focus_blocks = focus::suggest(&gaps, min, focus::MAX_FOCUS_BLOCKS)
+                        .into_iter()
+                        .map(focus::focus_event)
+                        .collect();
+                    events.extend(&focus_blocks);
+                    events.sort_by(|a, b| options.sort.compare_sourced(a, b));
+                    query::pinned_ended_last(&mut events, date, now);
+                    query::overdue_first(&mut events, date);
+                }
+                if options.explain_sort {
+                    for line in query::explain_order(&events, &options.sort, date, now) {
+                        eprintln!("{}", line);
+                    }
+                }
+                if !options.quiet
+                    && !print_events(&calendar, &events, date, &options, now, &mut out)
+                    && options.next_in
+                {
+                    exit_status = 1;
+                }
+                if let Some(path) = &options.diff_state {
+                    if let Err(e) = print_diff(path, &events, date, &options, now, &mut out) {
+                        eprintln!("Error: {}", e);
+                        exit_status = 1;
+                    }
+                }
+            }
+        },
     }
-}
-
-#[derive(Debug)]
-struct CalError(String);
-impl std::error::Error for CalError {}
-
-impl std::fmt::Display for CalError {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "CalError({})", self.0)
+    if let Err(e) = options.output.write(&out) {
+        eprintln!("Error: {}", e);
+        exit_status = 1;
     }
+    report_errors(&options, &calendar.problems, None);
+    std::process::exit(exit_status);
 }
 
-#[derive(Debug)]
-enum HeaderValue<'a> {
-    One(&'a str),
-    Many(Vec<&'a str>),
+/// The events snoozed for today. A broken snooze file shouldn't stop the events being shown, so
+/// it's only warned about.
+fn load_snoozed() -> Snoozed {
+    snooze::state_path()
+        .map(|path| Snoozed::load(&path))
+        .transpose()
+        .unwrap_or_else(|e| {
+            eprintln!("Warning: ignoring snoozed events: {}", e);
+            None
+        })
+        .unwrap_or_default()
 }
 
-impl<'a> HeaderValue<'a> {
-    fn one(&self) -> Option<&'a str> {
-        match self {
-            Self::One(s) => Some(s),
-            _ => None,
+/// Snoozes an event for the rest of today, or lists or clears the snoozed events
+fn snooze(options: &Options, now: NaiveDateTime) -> Result<(), Error> {
+    let path = snooze::state_path().ok_or(Error::Usage(
+        "Cannot find somewhere to keep snoozed events (set $XDG_STATE_HOME or $HOME)".into(),
+    ))?;
+    let mut snoozed = Snoozed::load(&path)?;
+    snoozed.expire(now.date());
+    match &options.snooze {
+        Some(SnoozeAction::Add(target)) => {
+            snoozed.snooze(SnoozeTarget::from_arg(target), now.date());
+            snoozed.save(&path)
         }
-    }
-    fn many(&self) -> Option<&Vec<&'a str>> {
-        match self {
-            Self::Many(v) => Some(v),
-            _ => None,
+        Some(SnoozeAction::List) => {
+            for snooze in &snoozed.snoozes {
+                println!("{}", snooze.target);
+            }
+            Ok(())
         }
+        Some(SnoozeAction::Clear) | None => Snoozed::default().save(&path),
     }
 }
 
-fn parse_cal_file(contents: &str) -> Result<Event, Box<dyn std::error::Error>> {
-    let mut in_header = false;
-    let mut header_values = HashMap::<&str, HeaderValue>::new();
-    let mut lines = contents.lines().peekable();
-
-    while let Some(line) = lines.next() {
-        if line == "---" {
-            if in_header {
-                // this means it's the end of the header, so we're done
-                break;
-            }
-            // otherwise it must be the start of the header
-            in_header = true;
-            continue;
-        }
-
-        if in_header {
-            let (key, value) = line.split_once(':').unwrap();
-
-            // stupid special case for the one list so I don't have to use a full general yaml parser
-            let header_value = if key == "daysOfWeek" {
-                HeaderValue::Many(if value.is_empty() {
-                    let mut days = Vec::new();
-                    while let Some(next_line) =
-                        lines.next_if(|next_line| next_line.trim_start().starts_with('-'))
-                    {
-                        let day =
-                            next_line.trim_start_matches(|c: char| c.is_whitespace() || c == '-');
-                        days.push(day);
-                    }
-                    days
-                } else {
-                    let start_bytes = value
-                        .find('[')
-                        .ok_or(CalError("Cannot find opening [ on list".into()))?
-                        + 1;
-                    let end_bytes = value
-                        .find(']')
-                        .ok_or(CalError("Cannot find closing ] on list".into()))?;
-                    let without_brackets = &value[start_bytes..end_bytes];
-
-                    // naive method of parsing a yaml list (should work for now)
-                    without_brackets
-                        .split(',')
-                        .map(|x| x.trim_start())
-                        .collect()
-                })
-            } else {
-                HeaderValue::One(value.trim_start())
-            };
-            header_values.insert(key, header_value);
-        }
-    }
-    let get_one = |name| {
-        header_values
-            .get(name)
-            .ok_or(CalError(format!("Has no '{}'", name)))?
-            .one()
-            .ok_or(CalError(format!("'{}' is a list", name)))
+/// Moves the notes of events which are over into the archive, or with `--dry-run` prints which
+/// would be moved. Notes which can't be read or would overwrite another note are never moved.
+fn archive(options: &Options) -> Result<(), Error> {
+    // both are checked when parsing the arguments
+    let (Some(before), Some(dest)) = (options.archive_before, &options.archive_dest) else {
+        return Ok(());
     };
-    let get_many = |name| {
-        header_values
-            .get(name)
-            .ok_or(CalError(format!("Has no '{}'", name)))?
-            .many()
-            .ok_or(CalError(format!("'{}' is not a list", name)))
-    };
-
-    if get_one("allDay").unwrap_or("false") == "true" {
-        Ok(Event::AllDay {
-            title: get_one("title")?.into(),
-            begin_date: get_one("date")?.parse()?,
-            end_date: if let Ok(end_date) = get_one("endDate") {
-                end_date.parse()?
-            } else {
-                get_one("date")?.parse()?
-            },
-        })
-    } else if get_one("type").unwrap_or("single") == "single" {
-        Ok(Event::Once {
-            title: get_one("title")?.into(),
-            begin: get_one("startTime")?.parse()?,
-            end: get_one("endTime")?.parse()?,
-            day: get_one("date")?.parse()?,
-        })
-    } else {
-        Ok(Event::Recurring {
-            title: get_one("title")?.into(),
-            begin: get_one("startTime")?.parse()?,
-            end: get_one("endTime")?.parse()?,
-            begin_recur: get_one("startRecur")?.parse()?,
-            end_recur: get_one("endRecur").ok().map_or_else(
-                || Ok::<Option<NaiveDate>, Box<dyn std::error::Error>>(None),
-                |x| {
-                    if x == "\"\"" {
-                        Ok(None)
-                    } else {
-                        Ok(Some(x.parse()?))
-                    }
-                },
-            )?,
-            recur_days: get_many("daysOfWeek")?
-                .iter()
-                .map(|day| match *day {
-                    "M" => Ok(Weekday::Mon),
-                    "T" => Ok(Weekday::Tue),
-                    "W" => Ok(Weekday::Wed),
-                    "R" => Ok(Weekday::Thu),
-                    "F" => Ok(Weekday::Fri),
-                    "S" => Ok(Weekday::Sat),
-                    "U" => Ok(Weekday::Sun),
-                    _ => Err(CalError(format!("Unknown weekday '{}'", day))),
-                })
-                .collect::<Result<Vec<Weekday>, CalError>>()?,
-        })
+    let plan = archive::plan(options, before, dest)?;
+    for reason in &plan.refused {
+        eprintln!("Not moving {}", reason);
     }
+    for (from, to) in &plan.moves {
+        if options.dry_run {
+            println!("Would move {} -> {}", from.display(), to.display());
+        } else {
+            archive::move_note(from, to)?;
+            println!("Moved {} -> {}", from.display(), to.display());
+        }
+    }
+    println!(
+        "{} {} {}, {} refused, {} kept",
+        if options.dry_run {
+            "Would move"
+        } else {
+            "Moved"
+        },
+        plan.moves.len(),
+        if plan.moves.len() == 1 {
+            "note"
+        } else {
+            "notes"
+        },
+        plan.refused.len(),
+        plan.kept
+    );
+    Ok(())
 }
 
-fn parse_events(
-    path: impl AsRef<std::path::Path>,
-) -> Result<Vec<Event>, Box<dyn std::error::Error>> {
-    std::fs::read_dir(path)?
-        .filter(|x| {
-            x.as_ref()
-                .is_ok_and(|y| y.file_type().is_ok_and(|z| z.is_file()))
-        })
-        .map(|x| {
-            let fname = x.unwrap().path();
-            let mut file = std::fs::File::open(fname)?;
-            let mut buffer = String::new();
-            file.read_to_string(&mut buffer)?;
-            parse_cal_file(&buffer)
-        })
-        .collect()
+/// Writes how the events listed have changed since the snapshot in `path` to `out`, and replaces
+/// the snapshot with the events listed now
+fn print_diff(
+    path: &Path,
+    events: &[&SourcedEvent],
+    date: NaiveDate,
+    options: &Options,
+    now: NaiveDateTime,
+    out: &mut String,
+) -> Result<(), Error> {
+    // a broken snapshot is replaced, as if there wasn't one
+    let before = Snapshot::load(path).unwrap_or_else(|e| {
+        eprintln!("Warning: ignoring the last snapshot: {}", e);
+        None
+    });
+    let after = Snapshot::new(events, date, now.date());
+    let changes = before
+        .map(|before| diff::diff(&before, &after, now))
+        .unwrap_or_default();
+    match options.format {
+        Format::Json => out.push_str(&format!("{}\n", diff::json(&changes))),
+        _ => out.push_str(&diff::text(&changes, now.date(), &options.lang)),
+    }
+    after.save(path)
 }
 
-fn get_valid_events() -> Result<Vec<Event>, Box<dyn std::error::Error>> {
-    let now = chrono::Local::now().naive_local();
-    let mut events: Vec<Event> = std::env::args()
-        .skip(1)
-        .map(parse_events)
-        .collect::<Result<Vec<Vec<Event>>, Box<dyn std::error::Error>>>()? // TODO can I avoid this `collect`?
-        .into_iter()
-        .flatten()
-        .filter(|event| match event {
-            Event::Once { day, end, .. } => day == &now.date() && end >= &now.time(),
-            Event::Recurring {
-                begin_recur,
-                end_recur,
-                recur_days,
-                end,
-                ..
-            } => {
-                recur_days.contains(&now.date().weekday())
-                    && &now.date() >= begin_recur
-                    && end_recur.map(|day| now.date() <= day).unwrap_or(true)
-                    && end >= &now.time()
-            }
-            Event::AllDay {
-                begin_date,
-                end_date,
-                ..
-            } => &now.date() >= begin_date && &now.date() < end_date,
-        })
+/// The fields joined by tabs, with any tabs or line breaks in them turned into spaces so that the
+/// line can always be split on tabs.
+fn porcelain_line(fields: &[&str]) -> String {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|field| field.replace(['\t', '\n', '\r'], " "))
         .collect();
-    events.sort_by(|a, b| match a {
-        // always put all day events at the top!
-        Event::Once { begin: a_begin, .. } | Event::Recurring { begin: a_begin, .. } => match b {
-            Event::Once { begin: b_begin, .. } | Event::Recurring { begin: b_begin, .. } => {
-                a_begin.cmp(b_begin)
-            }
-            Event::AllDay { .. } => std::cmp::Ordering::Greater,
-        },
-        Event::AllDay { .. } => std::cmp::Ordering::Less,
-    });
-    Ok(events)
+    format!("{}\n", fields.join("\t"))
 }
 
-fn main() {
-    match get_valid_events() {
-        Ok(events) => {
-            for event in events {
-                println!("{}", event)
+/// Reports the notes which couldn't be read, and the error which stopped the program if there was
+/// one. With `--errors json` this is a single JSON object on stderr listing all of them.
+fn report_errors(options: &Options, problems: &[Error], fatal: Option<&Error>) {
+    match options.errors {
+        // broken notes are fatal errors in this mode
+        ErrorFormat::Text => {
+            if let Some(e) = fatal {
+                eprintln!("Error processing event files: {}", e);
             }
         }
-        Err(e) => {
-            eprintln!("Error processing event files: {}", e)
+        ErrorFormat::Json => {
+            let mut errors: Vec<String> = problems.iter().map(Error::to_json).collect();
+            if let Some(e) = fatal {
+                errors.push(e.to_json());
+            }
+            eprintln!("{{\"errors\":[{}]}}", errors.join(","));
         }
     }
 }
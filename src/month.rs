@@ -0,0 +1,238 @@
+use crate::{
+    calendar::Calendar,
+    cli::Options,
+    event::Event,
+    lang::Lang,
+    render::{humanize_minutes, json_string},
+};
+use chrono::{Datelike, Months, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Weekday};
+
+/// Draws a calendar grid of the month containing `month`, marking every day which has at least
+/// one event with a `•`. `today` is highlighted if `highlight` is set.
+pub fn month_grid(
+    calendar: &Calendar,
+    options: &Options,
+    month: NaiveDate,
+    today: NaiveDate,
+    highlight: bool,
+) -> String {
+    let first = month.with_day(1).unwrap(); // every month has a 1st
+    let next_month = first + Months::new(1);
+    let week_start = options.week_start;
+
+    let title = options.lang.month_year(first);
+    let mut out = format!("{:^20}", title).trim_end().to_string() + "\n";
+    let mut weekday = week_start;
+    for _ in 0..7 {
+        let name: String = options.lang.weekday(weekday).chars().take(2).collect();
+        out += &format!("{:<3}", name);
+        weekday = weekday.succ();
+    }
+    out = out.trim_end().to_string() + "\n";
+
+    // blank cells before the 1st so that it lines up under the right weekday
+    let offset = ((first.weekday().num_days_from_monday() + 7 - week_start.num_days_from_monday())
+        % 7) as usize;
+    let mut line = "   ".repeat(offset);
+    for date in first.iter_days().take_while(|date| *date < next_month) {
+        let has_event = calendar
+            .all()
+            .any(|sourced| calendar.shown_on(&sourced.event, date, options.skip_holidays));
+        let day = format!("{:>2}", date.day());
+        if highlight && date == today {
+            line += &format!("\x1b[7m{}\x1b[0m", day);
+        } else {
+            line += &day;
+        }
+        line.push(if has_event { '•' } else { ' ' });
+        if date.weekday() == week_start.pred() {
+            out += line.trim_end();
+            out.push('\n');
+            line.clear();
+        }
+    }
+    if !line.is_empty() {
+        out += line.trim_end();
+        out.push('\n');
+    }
+    out
+}
+
+/// A list of the all-day events which overlap the month containing `month`, e.g.
+/// `May 03 - May 10  Conference`
+pub fn month_spans(calendar: &Calendar, month: NaiveDate, lang: &Lang) -> String {
+    let first = month.with_day(1).unwrap(); // every month has a 1st
+    let next_month = first + Months::new(1);
+    let mut spans: Vec<(NaiveDate, NaiveDate, &str)> = calendar
+        .all()
+        .filter_map(|sourced| match &sourced.event {
+            Event::AllDay {
+                title,
+                begin_date,
+                end_date,
+                ..
+            } if *begin_date < next_month && *end_date > first => {
+                Some((*begin_date, *end_date, title.as_ref()))
+            }
+            _ => None,
+        })
+        .collect();
+    spans.sort();
+    spans
+        .into_iter()
+        .map(|(begin_date, end_date, title)| {
+            let last_day = end_date.pred_opt().unwrap_or(end_date);
+            if last_day <= begin_date {
+                format!("{}           {}\n", lang.month_day(begin_date), title)
+            } else {
+                format!(
+                    "{} - {}  {}\n",
+                    lang.month_day(begin_date),
+                    lang.month_day(last_day),
+                    title
+                )
+            }
+        })
+        .collect()
+}
+
+/// How much of the time from `begin` to `end` falls in each hour it overlaps, as the start of
+/// the hour and a number of hours, e.g. 09:30 - 11:15 is half of 09:00, all of 10:00 and a
+/// quarter of 11:00
+pub fn split_into_hours(begin: NaiveDateTime, end: NaiveDateTime) -> Vec<(NaiveDateTime, f64)> {
+    let an_hour = chrono::Duration::try_hours(1).unwrap_or_default();
+    let mut hours = Vec::new();
+    let mut hour = begin.date().and_time(
+        NaiveTime::from_hms_opt(begin.hour(), 0, 0).unwrap(), // the hour of a time is a time
+    );
+    while hour < end {
+        let overlap = end.min(hour + an_hour) - begin.max(hour);
+        hours.push((hour, overlap.num_seconds() as f64 / 3600.0));
+        hour += an_hour;
+    }
+    hours
+}
+
+/// How many hours of timed events fall in each hour of each weekday over a month, Monday first,
+/// for `month --heatmap`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heatmap {
+    pub month: NaiveDate,
+    pub hours: [[f64; 24]; 7],
+}
+
+impl Heatmap {
+    /// The heatmap of the month containing `month`. Events running past midnight count towards
+    /// the next day, and hidden events, all-day events and those skipped on holidays (with
+    /// `skip_holidays`) or during vacations don't count at all.
+    pub fn new(calendar: &Calendar, month: NaiveDate, skip_holidays: bool) -> Self {
+        let first = month.with_day(1).unwrap(); // every month has a 1st
+        let last = (first + Months::new(1)).pred_opt().unwrap_or(first);
+        let mut hours = [[0.0; 24]; 7];
+        for (sourced, date) in calendar.occurrences(first, last) {
+            if sourced.hidden || !calendar.shown_on(&sourced.event, date, skip_holidays) {
+                continue;
+            }
+            let Some((begin, end)) = sourced.event.times_on(date) else {
+                continue;
+            };
+            for (hour, amount) in split_into_hours(begin, end) {
+                hours[hour.weekday().num_days_from_monday() as usize][hour.hour() as usize] +=
+                    amount;
+            }
+        }
+        Self {
+            month: first,
+            hours,
+        }
+    }
+
+    fn busiest(&self) -> f64 {
+        self.hours.iter().flatten().copied().fold(0.0, f64::max)
+    }
+
+    /// A row of shade blocks for each weekday, two characters to an hour, darker the busier the
+    /// hour is compared to the busiest, followed by how many hours each shade goes up to
+    pub fn text(&self, lang: &Lang) -> String {
+        const SHADES: [char; 4] = ['░', '▒', '▓', '█'];
+        let busiest = self.busiest();
+        let mut out = format!("{}\n", lang.month_year(self.month));
+        let mut header = " ".repeat(4);
+        for hour in 0..24 {
+            header += &if hour % 3 == 0 {
+                format!("{:02}", hour)
+            } else {
+                "  ".into()
+            };
+        }
+        out += header.trim_end();
+        out.push('\n');
+        let mut weekday = Weekday::Mon;
+        for row in &self.hours {
+            let name: String = lang.weekday(weekday).chars().take(2).collect();
+            let mut line = format!("{:<4}", name);
+            for amount in row {
+                let shade = if *amount > 0.0 {
+                    // an hour with anything in it gets at least the lightest shade
+                    let level = (amount / busiest * 4.0).ceil() as usize;
+                    SHADES[level.clamp(1, 4) - 1]
+                } else {
+                    ' '
+                };
+                line.push(shade);
+                line.push(shade);
+            }
+            out += line.trim_end();
+            out.push('\n');
+            weekday = weekday.succ();
+        }
+        if busiest > 0.0 {
+            let legend: Vec<String> = SHADES
+                .iter()
+                .zip(1..)
+                .map(|(shade, quarter)| {
+                    let minutes = (busiest * 60.0 * f64::from(quarter) / 4.0).round() as i64;
+                    format!("{} ≤{}", shade, humanize_minutes(minutes, lang))
+                })
+                .collect();
+            out += &legend.join("  ");
+            out.push('\n');
+        }
+        out
+    }
+
+    /// A JSON object with the month and a row of 24 numbers of hours for each weekday, Monday
+    /// first, e.g. `{"month":"2024-05","hours":[[0,0,...,0.5,1,0.25,...],...]}`
+    pub fn json(&self) -> String {
+        let rows: Vec<String> = self
+            .hours
+            .iter()
+            .map(|row| {
+                let cells: Vec<String> = row
+                    .iter()
+                    .map(|amount| ((amount * 100.0).round() / 100.0).to_string())
+                    .collect();
+                format!("[{}]", cells.join(","))
+            })
+            .collect();
+        format!(
+            "{{\"month\":{},\"hours\":[{}]}}",
+            json_string(&self.month.format("%Y-%m").to_string()),
+            rows.join(",")
+        )
+    }
+}
+
+/// Parses a month written like "2024-05"
+pub fn parse_month(s: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(&format!("{}-01", s), "%Y-%m-%d").ok()
+}
+
+/// Parses the day weeks start on, which can be "mon" or "sun"
+pub fn parse_week_start(s: &str) -> Option<Weekday> {
+    match s {
+        "mon" => Some(Weekday::Mon),
+        "sun" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
@@ -0,0 +1,52 @@
+use crate::{error::Error, render::Fields};
+use std::process::{Command, Stdio};
+
+/// Something which opens a URL, so that `open-next` can be tried out without a browser opening
+pub trait Launcher {
+    fn launch(&mut self, url: &str) -> Result<(), Error>;
+}
+
+/// Opens URLs with the desktop's handler for them: `open` on macOS and `xdg-open` elsewhere
+pub struct System;
+
+impl Launcher for System {
+    fn launch(&mut self, url: &str) -> Result<(), Error> {
+        let program = if cfg!(target_os = "macos") {
+            "open"
+        } else {
+            "xdg-open"
+        };
+        let status = Command::new(program)
+            .arg(url)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map_err(|e| Error::Open(format!("Cannot run {}: {}", program, e)))?;
+        if !status.success() {
+            return Err(Error::Open(format!(
+                "{} couldn't open {} ({})",
+                program, url, status
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Opens the link of the ongoing event, or of the next one if none is on, out of the events with
+/// a `url`, and says what it did, e.g. "Opened https://meet.example.com/abc for Standup". With
+/// `dry_run` it only gives the URL. None if no event left today has a link.
+pub fn open_next(
+    fields: &[Fields],
+    launcher: &mut impl Launcher,
+    dry_run: bool,
+) -> Result<Option<String>, Error> {
+    let Some(next) = crate::render::next_timed_where(fields, |fields| fields.url.is_some()) else {
+        return Ok(None);
+    };
+    let url = next.url.unwrap_or_default(); // only events with a url were kept
+    if dry_run {
+        return Ok(Some(url.into()));
+    }
+    launcher.launch(url)?;
+    Ok(Some(format!("Opened {} for {}", url, next.title)))
+}
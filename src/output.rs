@@ -0,0 +1,62 @@
+use crate::error::Error;
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+/// Where the output goes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Destination {
+    Stdout,
+    /// replaced with each run's output, with `--output`
+    File(PathBuf),
+    /// added to the end of, with `--output` and `--append`
+    Append(PathBuf),
+}
+
+impl Destination {
+    /// Writes all of `text` to the destination at once
+    pub fn write(&self, text: &str) -> Result<(), Error> {
+        match self {
+            Self::Stdout => {
+                print!("{}", text);
+                Ok(())
+            }
+            Self::File(path) => replace(path, text),
+            Self::Append(path) => std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .and_then(|mut file| file.write_all(text.as_bytes()))
+                .map_err(|source| Error::Io {
+                    path: path.clone(),
+                    source,
+                }),
+        }
+    }
+}
+
+/// Replaces the file at `path` with `text` by writing it to a file next to it and renaming that
+/// over it, so that something reading the file, like conky, sees either the old contents or the
+/// new ones and never half of them
+pub fn replace(path: &Path, text: &str) -> Result<(), Error> {
+    let io = |source| Error::Io {
+        path: path.to_path_buf(),
+        source,
+    };
+    let name = path
+        .file_name()
+        .ok_or_else(|| io(std::io::ErrorKind::InvalidInput.into()))?;
+    // in the same folder, since renaming doesn't work across filesystems
+    let temp = path.with_file_name(format!(
+        ".{}.{}.tmp",
+        name.to_string_lossy(),
+        std::process::id()
+    ));
+    std::fs::write(&temp, text)
+        .and_then(|_| std::fs::rename(&temp, path))
+        .map_err(|source| {
+            let _ = std::fs::remove_file(&temp);
+            io(source)
+        })
+}
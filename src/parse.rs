@@ -0,0 +1,1268 @@
+use crate::{
+    error::{Error, ParseErrorKind},
+    event::{Event, SourcedEvent},
+    glob::Glob,
+};
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet},
+    fmt::Display,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
+
+const WEEK: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+/// The letters Full Calendar writes in `daysOfWeek`, Monday first
+const ENGLISH_LETTERS: [&str; 7] = ["M", "T", "W", "R", "F", "S", "U"];
+
+/// The two-letter English abbreviations, which other tools write, accepted whatever the locale
+const ENGLISH_ABBREVIATIONS: [&str; 7] = ["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"];
+
+/// Which extra abbreviations are accepted in `daysOfWeek`, on top of Full Calendar's letters and
+/// the English two-letter abbreviations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeekdayLocale {
+    /// only Full Calendar's letters and `Mo`, `Tu`, ...
+    English,
+    German,
+    French,
+    Spanish,
+}
+
+impl FromStr for WeekdayLocale {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "en" => Ok(Self::English),
+            "de" => Ok(Self::German),
+            "fr" => Ok(Self::French),
+            "es" => Ok(Self::Spanish),
+            _ => Err(Error::Usage(format!(
+                "Unknown weekday locale '{}' (expected en, de, fr or es)",
+                s
+            ))),
+        }
+    }
+}
+
+/// Whether the `endDate` of an all-day event is its last day or the day after. Full Calendar has
+/// written both in different versions. Events always use the day after once they're read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AllDayEnd {
+    Inclusive,
+    Exclusive,
+}
+
+impl FromStr for AllDayEnd {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "inclusive" => Ok(Self::Inclusive),
+            "exclusive" => Ok(Self::Exclusive),
+            _ => Err(Error::Usage(format!(
+                "Unknown all-day end '{}' (expected inclusive or exclusive)",
+                s
+            ))),
+        }
+    }
+}
+
+impl WeekdayLocale {
+    /// The two-letter abbreviations of the locale, Monday first
+    fn abbreviations(&self) -> Option<[&'static str; 7]> {
+        match self {
+            Self::English => None,
+            Self::German => Some(["Mo", "Di", "Mi", "Do", "Fr", "Sa", "So"]),
+            Self::French => Some(["Lu", "Ma", "Me", "Je", "Ve", "Sa", "Di"]),
+            Self::Spanish => Some(["Lu", "Ma", "Mi", "Ju", "Vi", "Sa", "Do"]),
+        }
+    }
+
+    /// The day written as `token` in `daysOfWeek`. Tokens like "Di" mean different days in
+    /// different languages, so only the configured locale's abbreviations are understood.
+    pub fn weekday(&self, token: &str) -> Result<Weekday, ParseErrorKind> {
+        let abbreviations = self.abbreviations();
+        ENGLISH_LETTERS
+            .iter()
+            .chain(&ENGLISH_ABBREVIATIONS)
+            .chain(abbreviations.iter().flatten())
+            .zip(WEEK.iter().cycle())
+            .find(|(name, _)| **name == token)
+            .map(|(_, weekday)| *weekday)
+            .ok_or_else(|| ParseErrorKind::BadWeekday {
+                token: token.into(),
+                expected: self.accepted(),
+            })
+    }
+
+    /// Every token accepted for each day, e.g. "M/Mo for Monday, T/Tu/Di for Tuesday, ..."
+    fn accepted(&self) -> String {
+        let abbreviations = self.abbreviations();
+        WEEK.iter()
+            .enumerate()
+            .map(|(i, weekday)| {
+                let mut tokens = vec![ENGLISH_LETTERS[i], ENGLISH_ABBREVIATIONS[i]];
+                if let Some(abbreviations) = abbreviations {
+                    if !tokens.contains(&abbreviations[i]) {
+                        tokens.push(abbreviations[i]);
+                    }
+                }
+                format!("{} for {}", tokens.join("/"), weekday_name(*weekday))
+            })
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+}
+
+fn weekday_name(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "Monday",
+        Weekday::Tue => "Tuesday",
+        Weekday::Wed => "Wednesday",
+        Weekday::Thu => "Thursday",
+        Weekday::Fri => "Friday",
+        Weekday::Sat => "Saturday",
+        Weekday::Sun => "Sunday",
+    }
+}
+
+#[derive(Debug)]
+enum HeaderValue<'a> {
+    One(&'a str),
+    Many(Vec<&'a str>),
+}
+
+/// The keys of the frontmatter which are read into the event
+const KEYS: [&str; 19] = [
+    "title",
+    "allDay",
+    "deadline",
+    "completed",
+    "type",
+    "date",
+    "startDate",
+    "endDate",
+    "startTime",
+    "endTime",
+    "startRecur",
+    "endRecur",
+    "daysOfWeek",
+    "repeat",
+    "weekOfMonth",
+    "skipHolidays",
+    "travelMinutes",
+    "actualEnd",
+    "suppressRecurring",
+];
+
+/// The first template placeholder in the value, like Obsidian's `{{date}}` or Templater's
+/// `<% tp.file.title %>`
+fn placeholder(value: &str) -> Option<&str> {
+    [("{{", "}}"), ("<%", "%>")]
+        .iter()
+        .find_map(|(open, close)| {
+            let start = value.find(open)?;
+            let end = value[start..].find(close)? + start + close.len();
+            Some(&value[start..end])
+        })
+}
+
+/// The width of the whitespace (spaces or tabs) at the start of the line
+fn indent(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// Removes a `# comment` from the end of a value
+fn strip_comment(value: &str) -> &str {
+    match value.find(" #") {
+        Some(i) => &value[..i],
+        None if value.trim_start().starts_with('#') => "",
+        None => value,
+    }
+}
+
+/// An item of a list without its comment, surrounding whitespace or quotes
+fn list_item(item: &str) -> &str {
+    // a ` #` in quotes is part of the value rather than the start of a comment
+    let quoted = ['"', '\''].iter().find_map(|quote| {
+        let (value, rest) = item.trim_start().strip_prefix(*quote)?.split_once(*quote)?;
+        strip_comment(rest).trim().is_empty().then_some(value)
+    });
+    if let Some(value) = quoted {
+        return value;
+    }
+    let item = strip_comment(item).trim();
+    ['"', '\'']
+        .iter()
+        .find_map(|quote| item.strip_prefix(*quote)?.strip_suffix(*quote))
+        .unwrap_or(item)
+}
+
+/// Parses a time of day written as `09:00`, or with seconds as `09:00:00`. Every time in a note,
+/// in event JSON and on the command line is read with this.
+pub fn parse_time(s: &str) -> Result<NaiveTime, chrono::ParseError> {
+    NaiveTime::parse_from_str(s, "%H:%M").or_else(|_| NaiveTime::parse_from_str(s, "%H:%M:%S%.f"))
+}
+
+/// The heading in a note's body under which it can list more events
+const EVENTS_HEADING: &str = "## Events";
+
+/// The `---`-fenced blocks under the note's `## Events` heading, each of which is another event,
+/// up to the next heading
+fn extra_blocks(contents: &str) -> Vec<String> {
+    let mut lines = contents.lines();
+    // skip the frontmatter, which is the first two `---` lines and everything between them
+    for _ in 0..2 {
+        lines.by_ref().find(|line| *line == "---");
+    }
+    if !lines.by_ref().any(|line| line.trim_end() == EVENTS_HEADING) {
+        return Vec::new();
+    }
+    let mut blocks = Vec::new();
+    let mut block: Option<String> = None;
+    for line in lines {
+        match (line == "---", block.take()) {
+            (true, None) => block = Some("---\n".into()),
+            (true, Some(mut lines)) => {
+                lines += "---\n";
+                blocks.push(lines);
+            }
+            (false, Some(mut lines)) => {
+                lines += line;
+                lines.push('\n');
+                block = Some(lines);
+            }
+            (false, None) if line.starts_with('#') => break,
+            (false, None) => (),
+        }
+    }
+    blocks
+}
+
+/// Parses a note into its events: the one in its frontmatter, followed by any listed under a
+/// `## Events` heading in `---`-fenced blocks like the frontmatter. `path` is only used in
+/// warnings and errors. This doesn't touch the filesystem, so it can be used on notes which
+/// haven't been saved yet.
+pub fn parse_cal_file(
+    contents: &str,
+    path: &Path,
+    locale: WeekdayLocale,
+    allday_end: AllDayEnd,
+) -> Result<Vec<Event>, Error> {
+    parse_cal_file_with_warnings(contents, path, locale, allday_end, None, false, false)
+        .map(|(events, _)| events)
+}
+
+/// Like `parse_cal_file`, but also returns the problems which don't stop the note being read, like
+/// keys which the event doesn't use. With `today`, dates like `tomorrow` are read relative to it,
+/// with a warning, as `--allow-relative-dates` does; otherwise they're errors. With
+/// `strip_date_prefix`, an event titled after a note named like `2024-05-03 Dentist.md` is called
+/// `Dentist`, and with `filename_date` an event without a `date` in a note named like that is on
+/// that day.
+pub fn parse_cal_file_with_warnings(
+    contents: &str,
+    path: &Path,
+    locale: WeekdayLocale,
+    allday_end: AllDayEnd,
+    today: Option<NaiveDate>,
+    strip_date_prefix: bool,
+    filename_date: bool,
+) -> Result<(Vec<Event>, Vec<Error>), Error> {
+    let (events, _, warnings) = parse_blocks(
+        contents,
+        path,
+        locale,
+        allday_end,
+        today,
+        strip_date_prefix,
+        filename_date,
+    )?;
+    Ok((events, warnings))
+}
+
+/// The keys which say how an event is shown rather than when it happens. Those in the
+/// frontmatter apply to every event in the note, unless a block under `## Events` has its own.
+#[derive(Debug, Clone, Default)]
+struct Shown {
+    pinned: Option<bool>,
+    hidden: Option<bool>,
+    category: Option<Arc<str>>,
+    color: Option<Arc<str>>,
+    numbered: Option<Arc<str>>,
+    url: Option<Arc<str>>,
+}
+
+impl Shown {
+    fn read(frontmatter: &Frontmatter) -> Result<Self, Error> {
+        let text = |key| -> Result<Option<Arc<str>>, Error> {
+            Ok(frontmatter
+                .get_str(key)?
+                .map(|value| list_item(value).into()))
+        };
+        Ok(Self {
+            pinned: frontmatter.get_bool("pinned")?,
+            hidden: frontmatter.get_bool("hidden")?,
+            category: text("category")?,
+            color: text("color")?,
+            numbered: text("numberOccurrences")?,
+            url: text("url")?,
+        })
+    }
+
+    /// These keys, with the note's for any which the block doesn't have
+    fn or(self, note: &Shown) -> Shown {
+        Shown {
+            pinned: self.pinned.or(note.pinned),
+            hidden: self.hidden.or(note.hidden),
+            category: self.category.or_else(|| note.category.clone()),
+            color: self.color.or_else(|| note.color.clone()),
+            numbered: self.numbered.or_else(|| note.numbered.clone()),
+            url: self.url.or_else(|| note.url.clone()),
+        }
+    }
+}
+
+/// A note's events, how each of them is shown, and the warnings about them
+type Blocks = (Vec<Event>, Vec<Shown>, Vec<Error>);
+
+/// Like `parse_cal_file_with_warnings`, along with the keys saying how each event is shown
+fn parse_blocks(
+    contents: &str,
+    path: &Path,
+    locale: WeekdayLocale,
+    allday_end: AllDayEnd,
+    today: Option<NaiveDate>,
+    strip_date_prefix: bool,
+    filename_date: bool,
+) -> Result<Blocks, Error> {
+    let blocks = extra_blocks(contents);
+    // only number the events when there's more than one
+    let number = |i: usize| (!blocks.is_empty()).then_some(i + 1);
+    let mut events = Vec::new();
+    let mut shown_events: Vec<Shown> = Vec::new();
+    let mut warnings = Vec::new();
+    let all_blocks = std::iter::once(contents).chain(blocks.iter().map(String::as_str));
+    for (i, block) in all_blocks.enumerate() {
+        let (event, shown, event_warnings) = parse_event(
+            block,
+            path,
+            number(i),
+            locale,
+            allday_end,
+            today,
+            strip_date_prefix,
+            filename_date,
+        )?;
+        let shown = match shown_events.first() {
+            Some(note) => shown.or(note),
+            None => shown,
+        };
+        // nothing about a hidden event is shown, so its leftover keys don't matter either
+        if shown.hidden != Some(true) {
+            warnings.extend(event_warnings);
+        }
+        events.push(event);
+        shown_events.push(shown);
+    }
+    Ok((events, shown_events, warnings))
+}
+
+/// The keys of a block of frontmatter, read as what the event needs them to be. A key which is
+/// empty, `""`, `''` or `null` counts as not being there, whichever getter reads it, and every
+/// error says which key it was, what it said and what it should have been.
+#[derive(Debug)]
+pub struct Frontmatter<'a> {
+    values: HashMap<&'a str, HeaderValue<'a>>,
+    path: &'a Path,
+    event: Option<usize>,
+    today: Option<NaiveDate>,
+    /// keys which are there more than once, to be warned about
+    duplicates: Vec<&'a str>,
+}
+
+/// Whether a value means there's nothing there
+fn is_null(value: &str) -> bool {
+    let value = list_item(value);
+    value.is_empty() || value == "~" || value.eq_ignore_ascii_case("null")
+}
+
+impl<'a> Frontmatter<'a> {
+    /// Reads the first `---`-fenced block of frontmatter in `contents`. `path` and `event`, which
+    /// event in the note it is, are only used in errors. With `today`, dates like `tomorrow` are
+    /// read relative to it, as `--allow-relative-dates` does; otherwise they're errors.
+    pub fn parse(
+        contents: &'a str,
+        path: &'a Path,
+        event: Option<usize>,
+        today: Option<NaiveDate>,
+    ) -> Result<Self, Error> {
+        let mut frontmatter = Self {
+            values: HashMap::new(),
+            path,
+            event,
+            today,
+            duplicates: Vec::new(),
+        };
+        let mut in_header = false;
+        let mut lines = contents.lines().peekable();
+
+        while let Some(line) = lines.next() {
+            if line == "---" {
+                if in_header {
+                    // this means it's the end of the header, so we're done
+                    break;
+                }
+                // otherwise it must be the start of the header
+                in_header = true;
+                continue;
+            }
+
+            if in_header {
+                let (key, value) = line.split_once(':').ok_or_else(|| Error::Parse {
+                    path: path.to_path_buf(),
+                    event,
+                    field: None,
+                    kind: ParseErrorKind::Yaml(format!("expected 'key: value', found '{}'", line)),
+                })?;
+
+                // stupid special case for the lists so I don't have to use a full general yaml
+                // parser. a placeholder is left as it is, to be reported below. `aliases` can
+                // also be written as a single name.
+                let is_list = key == "daysOfWeek"
+                    || key == "aliases"
+                        && (strip_comment(value).trim().is_empty() || value.contains('['));
+                let header_value = if is_list && placeholder(value).is_none() {
+                    HeaderValue::Many(if strip_comment(value).trim().is_empty() {
+                        // the items have to be indented further than the key, otherwise a `---`
+                        // ending the frontmatter would be taken as an item
+                        let key_indent = indent(line);
+                        let mut items = Vec::new();
+                        while let Some(next_line) = lines.next_if(|next_line| {
+                            indent(next_line) > key_indent
+                                && next_line.trim_start().starts_with('-')
+                        }) {
+                            items.push(list_item(&next_line.trim_start()[1..]));
+                        }
+                        items
+                    } else {
+                        let yaml = |message: &str| {
+                            frontmatter.error(key, ParseErrorKind::Yaml(message.into()))
+                        };
+                        // splitting rather than slicing, so that no list can be cut in the wrong
+                        // place, and only looking for the ] after the [
+                        let (before, after_open) = value
+                            .split_once('[')
+                            .ok_or_else(|| yaml("cannot find opening [ on list"))?;
+                        if before.contains(']') {
+                            return Err(yaml("] comes before the opening [ of the list"));
+                        }
+                        let (without_brackets, _) = after_open
+                            .split_once(']')
+                            .ok_or_else(|| yaml("cannot find closing ] on list"))?;
+
+                        // naive method of parsing a yaml list (should work for now)
+                        without_brackets
+                            .split(',')
+                            .map(list_item)
+                            .filter(|x| !x.is_empty()) // `[]` is an empty list, not one empty item
+                            .collect()
+                    })
+                } else {
+                    HeaderValue::One(value.trim_start())
+                };
+                // a key can end up in there twice after a sloppy merge, in which case the first
+                // one wins so that appending to the frontmatter can't silently change the event
+                match frontmatter.values.entry(key) {
+                    Entry::Occupied(_) => frontmatter.duplicates.push(key),
+                    Entry::Vacant(entry) => {
+                        entry.insert(header_value);
+                    }
+                }
+            }
+        }
+        Ok(frontmatter)
+    }
+
+    /// The problem with `key`
+    pub fn error(&self, key: &str, kind: ParseErrorKind) -> Error {
+        Error::Parse {
+            path: self.path.to_path_buf(),
+            event: self.event,
+            field: Some(key.into()),
+            kind,
+        }
+    }
+
+    /// A problem with what `key` says, e.g. "'allDay': is 'yes', not true or false"
+    fn bad_value(&self, key: &str, value: &str, expected: &str) -> Error {
+        self.error(
+            key,
+            ParseErrorKind::Yaml(format!("is '{}', not {}", value, expected)),
+        )
+    }
+
+    /// Whether the key is there with a value
+    pub fn has(&self, key: &str) -> bool {
+        match self.values.get(key) {
+            Some(HeaderValue::One(value)) => !is_null(value),
+            Some(HeaderValue::Many(_)) => true,
+            None => false,
+        }
+    }
+
+    /// The value of the key as it's written, or None if it isn't there. A list is an error.
+    pub fn get_str(&self, key: &str) -> Result<Option<&'a str>, Error> {
+        match self.values.get(key) {
+            Some(HeaderValue::One(value)) if !is_null(value) => Ok(Some(value.trim_end())),
+            Some(HeaderValue::Many(_)) => Err(self.error(
+                key,
+                ParseErrorKind::Yaml("is a list, not a single value".into()),
+            )),
+            _ => Ok(None),
+        }
+    }
+
+    /// The items of a list, e.g. `daysOfWeek`, or None if it isn't there
+    pub fn get_list(&self, key: &str) -> Result<Option<&[&'a str]>, Error> {
+        match self.values.get(key) {
+            Some(HeaderValue::Many(items)) => Ok(Some(items)),
+            Some(HeaderValue::One(value)) if !is_null(value) => {
+                Err(self.bad_value(key, value.trim(), "a list"))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// `true` or `false`, in any case
+    pub fn get_bool(&self, key: &str) -> Result<Option<bool>, Error> {
+        let Some(value) = self.get_str(key)? else {
+            return Ok(None);
+        };
+        match list_item(value) {
+            value if value.eq_ignore_ascii_case("true") => Ok(Some(true)),
+            value if value.eq_ignore_ascii_case("false") => Ok(Some(false)),
+            _ => Err(self.bad_value(key, value, "true or false")),
+        }
+    }
+
+    /// A number, or anything else written the way `FromStr` reads it, which is `expected` in the
+    /// error if it isn't
+    pub fn get_parsed<T: FromStr>(&self, key: &str, expected: &str) -> Result<Option<T>, Error> {
+        let Some(value) = self.get_str(key)? else {
+            return Ok(None);
+        };
+        list_item(value)
+            .parse()
+            .map(Some)
+            .map_err(|_| self.bad_value(key, value, expected))
+    }
+
+    /// A date like `2024-05-03`, or with `today` one like `tomorrow`
+    pub fn get_date_opt(&self, key: &str) -> Result<Option<NaiveDate>, Error> {
+        let Some(value) = self.get_str(key)? else {
+            return Ok(None);
+        };
+        let value = list_item(value);
+        let bad_date = |error| {
+            self.error(
+                key,
+                ParseErrorKind::BadDate {
+                    value: value.into(),
+                    error,
+                },
+            )
+        };
+        value.parse().map(Some).or_else(|e| {
+            // any day will do to tell whether it's relative at all
+            match (self.today, relative_date(value, NaiveDate::default())) {
+                (_, None) => Err(bad_date(e)),
+                (Some(today), Some(_)) => relative_date(value, today)
+                    .map(Some)
+                    .ok_or_else(|| bad_date(e)),
+                (None, Some(_)) => Err(self.error(
+                    key,
+                    ParseErrorKind::RelativeDate {
+                        value: value.into(),
+                        resolved: None,
+                    },
+                )),
+            }
+        })
+    }
+
+    pub fn get_date(&self, key: &str) -> Result<NaiveDate, Error> {
+        self.get_date_opt(key)?
+            .ok_or_else(|| self.error(key, ParseErrorKind::MissingField))
+    }
+
+    /// A time of day like `09:00`
+    pub fn get_time_opt(&self, key: &str) -> Result<Option<NaiveTime>, Error> {
+        let Some(value) = self.get_str(key)? else {
+            return Ok(None);
+        };
+        let value = list_item(value);
+        parse_time(value).map(Some).map_err(|error| {
+            self.error(
+                key,
+                ParseErrorKind::BadTime {
+                    value: value.into(),
+                    error,
+                },
+            )
+        })
+    }
+
+    pub fn get_time(&self, key: &str) -> Result<NaiveTime, Error> {
+        self.get_time_opt(key)?
+            .ok_or_else(|| self.error(key, ParseErrorKind::MissingField))
+    }
+
+    /// `2024-05-03T14:00` as written by the Day Planner plugin, with a `T` or a space and
+    /// optionally seconds
+    pub fn get_datetime(&self, key: &str) -> Result<NaiveDateTime, Error> {
+        let value = list_item(
+            self.get_str(key)?
+                .ok_or_else(|| self.error(key, ParseErrorKind::MissingField))?,
+        );
+        let mut result = NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M");
+        for format in ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%d %H:%M:%S"] {
+            result = result.or_else(|_| NaiveDateTime::parse_from_str(value, format));
+        }
+        result.map_err(|error| {
+            self.error(
+                key,
+                ParseErrorKind::BadDateTime {
+                    value: value.into(),
+                    error,
+                },
+            )
+        })
+    }
+
+    /// A date which may have a time of day after it, like `2024-05-03T14:00` in imported notes,
+    /// read as `get_datetime` does, or else just a date as `get_date_opt` reads it
+    pub fn get_date_and_time_opt(
+        &self,
+        key: &str,
+    ) -> Result<Option<(NaiveDate, Option<NaiveTime>)>, Error> {
+        let Some(value) = self.get_str(key)? else {
+            return Ok(None);
+        };
+        let value = list_item(value);
+        // a relative date like `+3 days` has a space in it too, so only a date followed by
+        // something counts
+        let has_time = value.get(..10).is_some_and(|date| {
+            date.parse::<NaiveDate>().is_ok() && value[10..].starts_with(['T', ' '])
+        });
+        if has_time {
+            let at = self.get_datetime(key)?;
+            Ok(Some((at.date(), Some(at.time()))))
+        } else {
+            Ok(self.get_date_opt(key)?.map(|date| (date, None)))
+        }
+    }
+
+    /// What the event is called: its `title`, or else the first of the note's `aliases`, like
+    /// Obsidian shows it, or else `fallback`
+    fn title(&self, fallback: Option<&str>) -> Result<Arc<str>, Error> {
+        let alias = match self.values.get("aliases") {
+            Some(HeaderValue::One(alias)) => Some(list_item(alias)),
+            Some(HeaderValue::Many(aliases)) => aliases.first().copied(),
+            None => None,
+        };
+        self.get_str("title")?
+            .or(alias.filter(|alias| !is_null(alias)))
+            .or(fallback)
+            .map(Into::into)
+            .ok_or_else(|| self.error("title", ParseErrorKind::MissingField))
+    }
+}
+
+/// Parses the first `---`-fenced block of frontmatter in `contents` into an event and how it's
+/// shown, along with warnings about keys which don't apply to it. `event` is which event in the note it is, for
+/// errors. An event without a title is named after the note.
+#[allow(clippy::too_many_arguments)]
+fn parse_event(
+    contents: &str,
+    path: &Path,
+    event: Option<usize>,
+    locale: WeekdayLocale,
+    allday_end: AllDayEnd,
+    today: Option<NaiveDate>,
+    strip_date_prefix: bool,
+    filename_date: bool,
+) -> Result<(Event, Shown, Vec<Error>), Error> {
+    let frontmatter = Frontmatter::parse(contents, path, event, today)?;
+    // a note made from a template which wasn't filled in gets its own error, rather than e.g.
+    // `{{date}}` being reported as a bad date
+    for key in KEYS {
+        let found = match frontmatter.values.get(key) {
+            Some(HeaderValue::One(value)) => placeholder(value),
+            Some(HeaderValue::Many(values)) => values.iter().find_map(|value| placeholder(value)),
+            None => None,
+        };
+        if let Some(found) = found {
+            return Err(frontmatter.error(key, ParseErrorKind::Placeholder(found.into())));
+        }
+    }
+    let stem = path.file_stem().and_then(|stem| stem.to_str());
+    let fallback_title = stem.map(|stem| {
+        if strip_date_prefix {
+            without_date_prefix(stem)
+        } else {
+            stem
+        }
+    });
+    let filename_date = stem
+        .filter(|_| filename_date)
+        .and_then(split_date_prefix)
+        .map(|(date, _)| date);
+    let parsed = header_event(
+        &frontmatter,
+        locale,
+        allday_end,
+        fallback_title,
+        filename_date,
+    )?;
+    // leftovers from a template, or from changing the type of an event, are ignored, but might
+    // mean that the event isn't what was meant
+    let (ignored, used_by): (&[&str], _) = match parsed {
+        Event::Once { .. } => (&RECURRING_KEYS, "recurring"),
+        Event::Recurring { .. } => (&SINGLE_KEYS, "single"),
+        Event::AllDay { .. } | Event::Deadline { .. } => (&[], ""),
+    };
+    let mut warnings: Vec<Error> = frontmatter
+        .duplicates
+        .iter()
+        .map(|key| frontmatter.error(key, ParseErrorKind::DuplicateKey))
+        .collect();
+    warnings.extend(
+        ignored
+            .iter()
+            .filter(|key| frontmatter.has(key))
+            .map(|key| {
+                frontmatter.error(
+                    key,
+                    ParseErrorKind::IgnoredKey(format!(
+                        "only used by {} events, so it's ignored",
+                        used_by
+                    )),
+                )
+            }),
+    );
+    // other tools write Sunday as `S` or `Su`, so a lone `S` might not be the Saturday it's read as
+    if let Event::Recurring { recur_days, .. } = &parsed {
+        let days = frontmatter.get_list("daysOfWeek")?.unwrap_or_default();
+        if days.contains(&"S") && !recur_days.contains(&Weekday::Sun) {
+            warnings.push(frontmatter.error(
+                "daysOfWeek",
+                ParseErrorKind::AmbiguousWeekday(
+                    "'S' is read as Saturday, and Sunday is 'U' (write 'Sa' or 'Su' to be sure)"
+                        .into(),
+                ),
+            ));
+        }
+    }
+    // so that `lint` can say which dates will move
+    if let Some(today) = today {
+        for key in DATE_KEYS {
+            let Ok(Some(value)) = frontmatter.get_str(key) else {
+                continue;
+            };
+            let value = list_item(value);
+            if let Some(resolved) = relative_date(value, today) {
+                warnings.push(frontmatter.error(
+                    key,
+                    ParseErrorKind::RelativeDate {
+                        value: value.into(),
+                        resolved: Some(resolved),
+                    },
+                ));
+            }
+        }
+    }
+    Ok((parsed, Shown::read(&frontmatter)?, warnings))
+}
+
+/// The keys which only recurring events use
+const RECURRING_KEYS: [&str; 5] = [
+    "daysOfWeek",
+    "startRecur",
+    "endRecur",
+    "weekOfMonth",
+    "skipHolidays",
+];
+
+/// The keys which can be written as relative dates
+const DATE_KEYS: [&str; 4] = ["date", "endDate", "startRecur", "endRecur"];
+
+/// A date written relative to `today` for `--allow-relative-dates`: `today`, `tomorrow`,
+/// `next-monday` (the first Monday after today, also `next-mon`) or `+3 days`
+pub fn relative_date(value: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let value = value.to_lowercase();
+    if let Some(weekday) = value.strip_prefix("next-") {
+        let weekday: Weekday = weekday.parse().ok()?;
+        let days =
+            (weekday.num_days_from_monday() + 6 - today.weekday().num_days_from_monday()) % 7 + 1;
+        return today.checked_add_days(chrono::Days::new(days.into()));
+    }
+    let days = match value.as_str() {
+        "today" => 0,
+        "tomorrow" => 1,
+        _ => {
+            let (days, unit) = value.strip_prefix('+')?.trim().split_once(' ')?;
+            if !matches!(unit.trim(), "day" | "days") {
+                return None;
+            }
+            days.parse().ok()?
+        }
+    };
+    today.checked_add_days(chrono::Days::new(days))
+}
+
+/// The date a note's name starts with, like `2024-05-03 Dentist`, and the rest of the name
+fn split_date_prefix(stem: &str) -> Option<(NaiveDate, &str)> {
+    let (date, rest) = stem.split_at_checked(10)?;
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    Some((date, rest.trim_start_matches([' ', '-', '_'])))
+}
+
+/// The rest of a note's name after a date like `2024-05-03 `, or all of it if it doesn't start
+/// with one or there's nothing after it
+fn without_date_prefix(stem: &str) -> &str {
+    match split_date_prefix(stem) {
+        Some((_, rest)) if !rest.is_empty() => rest,
+        _ => stem,
+    }
+}
+
+/// The keys which only one-off timed events use
+const SINGLE_KEYS: [&str; 3] = ["date", "startDate", "actualEnd"];
+
+/// Makes the event out of the frontmatter's keys, calling it `fallback_title` if it has neither a
+/// `title` nor `aliases`
+fn header_event(
+    frontmatter: &Frontmatter,
+    locale: WeekdayLocale,
+    allday_end: AllDayEnd,
+    fallback_title: Option<&str>,
+    filename_date: Option<NaiveDate>,
+) -> Result<Event, Error> {
+    let error = |key: &str, kind| frontmatter.error(key, kind);
+    let travel_minutes = frontmatter.get_parsed("travelMinutes", "a number of minutes")?;
+    // Full Calendar can leave `date` out of notes named like `2024-05-03 Dentist.md`, since it's
+    // in the name, and then the date isn't part of the title either
+    let filename_date =
+        filename_date.filter(|_| !frontmatter.has("date") && !frontmatter.has("startDate"));
+    let title = || match filename_date {
+        Some(_) => frontmatter.title(fallback_title.map(without_date_prefix)),
+        None => frontmatter.title(fallback_title),
+    };
+    let date = || match filename_date {
+        Some(date) => Ok(date),
+        None => frontmatter.get_date("date"),
+    };
+
+    if frontmatter.get_bool("deadline")?.unwrap_or(false) {
+        Ok(Event::Deadline {
+            title: title()?,
+            due: date()?,
+            // Full Calendar writes when it was completed rather than `true`
+            completed: frontmatter.get_bool("completed").unwrap_or(Some(true)) == Some(true),
+        })
+    } else if frontmatter.get_bool("allDay")?.unwrap_or(false) {
+        let begin_date = date()?;
+        let end_date = match (frontmatter.get_date_opt("endDate")?, allday_end) {
+            (Some(end_date), AllDayEnd::Exclusive) => end_date,
+            (Some(end_date), AllDayEnd::Inclusive) => next_day(end_date),
+            // with no end it's just the one day
+            (None, _) => next_day(begin_date),
+        };
+        Ok(Event::AllDay {
+            title: title()?,
+            begin_date,
+            end_date,
+            suppress_recurring: frontmatter.get_bool("suppressRecurring")?.unwrap_or(false),
+        })
+    } else if frontmatter.get_str("type")?.unwrap_or("single") == "single"
+        && !frontmatter.has("repeat")
+    {
+        let conflict = |key, other_key, value: &dyn Display, other: &dyn Display| {
+            error(
+                key,
+                ParseErrorKind::Conflict(format!(
+                    "{} doesn't match '{}' ({})",
+                    value, other_key, other
+                )),
+            )
+        };
+        let (day, begin, end) = if frontmatter.has("startDate") {
+            let begin_at = frontmatter.get_datetime("startDate")?;
+            let end_at = frontmatter.get_datetime("endDate")?;
+            if end_at < begin_at {
+                return Err(error(
+                    "endDate",
+                    ParseErrorKind::NeverHappens(format!(
+                        "{} is before 'startDate' ({}), so the event never happens",
+                        end_at, begin_at
+                    )),
+                ));
+            }
+            // an event can run past midnight, but not into the day after that
+            if end_at >= begin_at + chrono::Days::new(1) {
+                return Err(error(
+                    "endDate",
+                    ParseErrorKind::Yaml(
+                        "is a day or more after 'startDate', which only all-day events can be"
+                            .into(),
+                    ),
+                ));
+            }
+            // the split keys can be there too, as long as they say the same thing
+            if let Some((date, time)) = frontmatter.get_date_and_time_opt("date")? {
+                if date != begin_at.date() {
+                    return Err(conflict("startDate", "date", &begin_at.date(), &date));
+                }
+                if let Some(time) = time.filter(|time| *time != begin_at.time()) {
+                    return Err(conflict("startDate", "date", &begin_at.time(), &time));
+                }
+            }
+            for (key, split_key, at) in [
+                ("startDate", "startTime", begin_at),
+                ("endDate", "endTime", end_at),
+            ] {
+                if let Some(time) = frontmatter.get_time_opt(split_key)? {
+                    if time != at.time() {
+                        return Err(conflict(key, split_key, &at.time(), &time));
+                    }
+                }
+            }
+            (begin_at.date(), begin_at.time(), end_at.time())
+        } else {
+            // imported notes can have the time in `date` instead of `startTime`
+            let (date, date_time) = match filename_date {
+                Some(date) => (date, None),
+                None => frontmatter
+                    .get_date_and_time_opt("date")?
+                    .ok_or_else(|| error("date", ParseErrorKind::MissingField))?,
+            };
+            let begin = match (date_time, frontmatter.get_time_opt("startTime")?) {
+                (Some(date_time), Some(begin)) if date_time != begin => {
+                    return Err(conflict("date", "startTime", &date_time, &begin));
+                }
+                (Some(begin), _) | (None, Some(begin)) => begin,
+                (None, None) => return Err(error("startTime", ParseErrorKind::MissingField)),
+            };
+            (date, begin, frontmatter.get_time("endTime")?)
+        };
+        Ok(Event::Once {
+            title: title()?,
+            begin,
+            end,
+            day,
+            travel_minutes,
+            actual_end: frontmatter.get_time_opt("actualEnd")?,
+        })
+    } else {
+        // the bounds of a recurrence can be left out to leave that end open
+        let begin_recur = frontmatter.get_date_opt("startRecur")?;
+        let end_recur = frontmatter.get_date_opt("endRecur")?;
+        if let (Some(begin_recur), Some(end_recur)) = (begin_recur, end_recur) {
+            if end_recur < begin_recur {
+                return Err(error(
+                    "endRecur",
+                    ParseErrorKind::NeverHappens(format!(
+                        "{} is before 'startRecur' ({}), so the event never happens",
+                        end_recur, begin_recur
+                    )),
+                ));
+            }
+        }
+        let week_of_month = match (
+            frontmatter.get_str("repeat")?.unwrap_or("weekly"),
+            frontmatter.has("weekOfMonth"),
+        ) {
+            ("weekly", false) => None,
+            ("weekly", true) => {
+                return Err(error(
+                    "weekOfMonth",
+                    ParseErrorKind::Yaml("needs 'repeat: monthly'".into()),
+                ))
+            }
+            ("monthly", _) => {
+                let expected = "1 to 5, or -1 to -5 to count from the end of the month";
+                let n: i8 = frontmatter
+                    .get_parsed("weekOfMonth", expected)?
+                    .ok_or_else(|| error("weekOfMonth", ParseErrorKind::MissingField))?;
+                if !(1..=5).contains(&n.unsigned_abs()) {
+                    return Err(frontmatter.bad_value("weekOfMonth", &n.to_string(), expected));
+                }
+                Some(n)
+            }
+            (other, _) => {
+                return Err(frontmatter.bad_value("repeat", other, "weekly or monthly"));
+            }
+        };
+        let recur_days = frontmatter
+            .get_list("daysOfWeek")?
+            .ok_or_else(|| error("daysOfWeek", ParseErrorKind::MissingField))?;
+        if recur_days.is_empty() {
+            return Err(error(
+                "daysOfWeek",
+                ParseErrorKind::NeverHappens("empty, so the event never happens".into()),
+            ));
+        }
+        Ok(Event::Recurring {
+            title: frontmatter.title(fallback_title)?,
+            begin: frontmatter.get_time("startTime")?,
+            end: frontmatter.get_time("endTime")?,
+            begin_recur,
+            end_recur,
+            recur_days: recur_days
+                .iter()
+                .map(|day| {
+                    locale
+                        .weekday(day)
+                        .map_err(|kind| error("daysOfWeek", kind))
+                })
+                .collect::<Result<Vec<Weekday>, Error>>()?,
+            skip_holidays: frontmatter.get_bool("skipHolidays")?.unwrap_or(false),
+            travel_minutes,
+            week_of_month,
+        })
+    }
+}
+
+fn next_day(date: NaiveDate) -> NaiveDate {
+    date.succ_opt().unwrap_or(date)
+}
+
+/// The colour of a category inferred from a folder: the 32-bit FNV-1a hash of the folder name's
+/// UTF-8 bytes, modulo 360, as the hue of a colour with 60% saturation and 45% lightness. It
+/// doesn't depend on the machine or the run, so a folder always gets the same colour.
+pub fn category_color(name: &str) -> String {
+    let hash = name.bytes().fold(0x811c9dc5u32, |hash, byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x01000193)
+    });
+    let hue = f64::from(hash % 360) / 60.0;
+    let (saturation, lightness) = (0.6, 0.45);
+    let chroma = (1.0 - (2.0 * lightness - 1.0f64).abs()) * saturation;
+    let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+    let (r, g, b) = match hue as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let channel = |value: f64| ((value + lightness - chroma / 2.0) * 255.0).round() as u8;
+    format!("#{:02x}{:02x}{:02x}", channel(r), channel(g), channel(b))
+}
+
+/// How long to wait before trying to read a note again with `--retry-unreadable`
+const RETRY_DELAY: std::time::Duration = std::time::Duration::from_millis(50);
+
+/// Reads the events in one note along with its warnings, or the problem with it. `folder` is the
+/// name of the folder it's in below the calendar directory, when the category is inferred from it.
+/// With `retry`, a note which can't be read is tried once more after `RETRY_DELAY`, in case a sync
+/// tool was writing it.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn parse_note(
+    path: PathBuf,
+    folder: Option<&str>,
+    locale: WeekdayLocale,
+    allday_end: AllDayEnd,
+    retry: bool,
+    today: Option<NaiveDate>,
+    strip_date_prefix: bool,
+    filename_date: bool,
+) -> Result<(Vec<SourcedEvent>, Vec<Error>), Error> {
+    let contents = std::fs::read_to_string(&path)
+        .or_else(|e| {
+            if !retry {
+                return Err(e);
+            }
+            std::thread::sleep(RETRY_DELAY);
+            std::fs::read_to_string(&path)
+        })
+        .map_err(|source| Error::Io {
+            path: path.clone(),
+            source,
+        })?;
+    let (events, shown, warnings) = parse_blocks(
+        &contents,
+        &path,
+        locale,
+        allday_end,
+        today,
+        strip_date_prefix,
+        filename_date,
+    )?;
+    let modified = std::fs::metadata(&path)
+        .and_then(|metadata| metadata.modified())
+        .ok();
+    let events = events
+        .into_iter()
+        .zip(shown)
+        .map(|(event, shown)| {
+            // the frontmatter wins over the folder, and only an inferred category gets an
+            // inferred colour
+            let inferred = folder.filter(|_| shown.category.is_none());
+            SourcedEvent {
+                pinned: shown.pinned.unwrap_or(false),
+                hidden: shown.hidden.unwrap_or(false),
+                category: shown.category.or_else(|| folder.map(Into::into)),
+                color: shown
+                    .color
+                    .or_else(|| Some(category_color(inferred?).into())),
+                modified,
+                numbered: shown.numbered,
+                url: shown.url,
+                ..SourcedEvent::new(event, path.clone())
+            }
+        })
+        .collect();
+    Ok((events, warnings))
+}
+
+/// The files in the directory, and with `recurse` in the folders below it except hidden ones
+/// like `.obsidian`, each with the name of the folder it's directly in or `None` at the top
+pub(crate) fn note_paths(
+    dir: &Path,
+    folder: Option<String>,
+    recurse: bool,
+    notes: &mut Vec<(PathBuf, Option<String>)>,
+) -> Result<(), Error> {
+    let entries = std::fs::read_dir(dir).map_err(|source| Error::Io {
+        path: dir.to_path_buf(),
+        source,
+    })?;
+    for entry in entries.flatten() {
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if file_type.is_file() {
+            notes.push((entry.path(), folder.clone()));
+        } else if recurse && file_type.is_dir() && !name.starts_with('.') {
+            note_paths(&entry.path(), Some(name), recurse, notes)?;
+        }
+    }
+    Ok(())
+}
+
+/// The calendar directories and notes given on the command line, e.g. by a shell glob like
+/// `Calendar/2024-*` which matches folders and notes alike, leaving out any which resolve to one
+/// already given so that their events aren't read twice. Anything which is neither a directory
+/// nor a `.md` note, or doesn't exist, is returned as a problem to be skipped like an unreadable
+/// note.
+pub fn calendar_roots(paths: &[impl AsRef<Path>]) -> (Vec<PathBuf>, Vec<Error>) {
+    let mut roots = Vec::new();
+    let mut problems = Vec::new();
+    let mut seen = HashSet::new();
+    for path in paths {
+        let path = path.as_ref();
+        let metadata = match std::fs::metadata(path) {
+            Ok(metadata) => metadata,
+            Err(source) => {
+                problems.push(Error::Io {
+                    path: path.to_path_buf(),
+                    source,
+                });
+                continue;
+            }
+        };
+        let note = metadata.is_file() && path.extension().is_some_and(|ext| ext == "md");
+        if !metadata.is_dir() && !note {
+            problems.push(Error::Io {
+                path: path.to_path_buf(),
+                source: std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "not a directory or a .md note",
+                ),
+            });
+            continue;
+        }
+        if seen.insert(std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())) {
+            roots.push(path.to_path_buf());
+        }
+    }
+    (roots, problems)
+}
+
+/// The notes in the calendar directory, and with `recurse` the folders below it, which don't match
+/// any of the `ignore` patterns, in order of name so that events which sort the same are always
+/// listed the same way. A single note can be given instead of a directory, e.g. by a pre-commit
+/// hook linting the notes which changed.
+pub(crate) fn calendar_notes(
+    dir: &Path,
+    recurse: bool,
+    ignore: &[Glob],
+) -> Result<Vec<(PathBuf, Option<String>)>, Error> {
+    if dir.is_file() {
+        return Ok(vec![(dir.to_path_buf(), None)]);
+    }
+    let mut notes = Vec::new();
+    note_paths(dir, None, recurse, &mut notes)?;
+    notes.retain(|(note, _)| {
+        let relative = note.strip_prefix(dir).unwrap_or(note);
+        !ignore.iter().any(|glob| glob.matches(relative))
+    });
+    notes.sort();
+    Ok(notes)
+}
+
+/// Reads every note in the directory. With `infer_category` the notes in the folders below it are
+/// read too, and get the name of the folder they're in as their category unless they have one.
+/// The notes which couldn't be read are returned separately so that the rest can still be shown,
+/// after trying them twice with `retry`, along with warnings about the notes which could. Notes
+/// matching any of the `ignore` patterns aren't read at all. With `today`, relative dates like
+/// `tomorrow` are read relative to it, and with `strip_date_prefix` events named after notes like
+/// `2024-05-03 Dentist.md` are called `Dentist`. With `filename_date`, events in notes like that
+/// without a `date` are on the day in the name.
+#[allow(clippy::too_many_arguments)]
+pub fn parse_events(
+    path: impl AsRef<Path>,
+    locale: WeekdayLocale,
+    allday_end: AllDayEnd,
+    infer_category: bool,
+    retry: bool,
+    ignore: &[Glob],
+    today: Option<NaiveDate>,
+    strip_date_prefix: bool,
+    filename_date: bool,
+) -> Result<(Vec<SourcedEvent>, Vec<Error>), Error> {
+    let notes = calendar_notes(path.as_ref(), infer_category, ignore)?;
+    let mut events = Vec::new();
+    let mut problems = Vec::new();
+    for (note, folder) in notes {
+        match parse_note(
+            note,
+            folder.as_deref(),
+            locale,
+            allday_end,
+            retry,
+            today,
+            strip_date_prefix,
+            filename_date,
+        ) {
+            Ok((note_events, warnings)) => {
+                events.extend(note_events);
+                problems.extend(warnings);
+            }
+            Err(e) => problems.push(e),
+        }
+    }
+    Ok((events, problems))
+}
@@ -0,0 +1,410 @@
+use crate::{
+    calendar::{skipped_on_holiday, suppressed_by_vacation, Calendar},
+    cli::{AllDayFilter, AllDayPosition, Options, SortBy},
+    event::{Event, SourcedEvent},
+    snooze::Snoozed,
+    SortSpec,
+};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+
+type Predicate<'a> = Box<dyn Fn(&Event) -> bool + 'a>;
+
+/// Picks events out of a calendar, e.g. `EventQuery::today(now).matching_title("standup")
+/// .categories(&["work"]).within(Duration::hours(2)).apply(&calendar)`. This is what the command
+/// line uses to choose which events to show, so anything built on it behaves the same way.
+pub struct EventQuery<'a> {
+    date: Option<NaiveDate>,
+    now: Option<NaiveDateTime>,
+    unfinished: bool,
+    grace: Option<chrono::Duration>,
+    title: Option<String>,
+    categories: Option<Vec<String>>,
+    within: Option<chrono::Duration>,
+    from: Option<NaiveTime>,
+    until: Option<NaiveTime>,
+    min_duration: Option<chrono::Duration>,
+    allday_filter: AllDayFilter,
+    sort: SortSpec,
+    skip_holidays: bool,
+    dedup_recurring: bool,
+    show_hidden: bool,
+    deadline_lookahead: u64,
+    show_overdue: bool,
+    snoozed: Option<&'a Snoozed>,
+    predicates: Vec<Predicate<'a>>,
+}
+
+impl Default for EventQuery<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> EventQuery<'a> {
+    /// A query matching every event
+    pub fn new() -> Self {
+        Self {
+            date: None,
+            now: None,
+            unfinished: false,
+            grace: None,
+            title: None,
+            categories: None,
+            within: None,
+            from: None,
+            until: None,
+            min_duration: None,
+            allday_filter: AllDayFilter::Show,
+            sort: SortSpec::default(),
+            skip_holidays: false,
+            dedup_recurring: false,
+            show_hidden: false,
+            deadline_lookahead: 0,
+            show_overdue: false,
+            snoozed: None,
+            predicates: Vec::new(),
+        }
+    }
+
+    /// The events shown by default: those today which haven't finished yet
+    pub fn today(now: NaiveDateTime) -> Self {
+        Self::new().on_date(now.date()).at(now).unfinished()
+    }
+
+    /// Only events shown on `date`, taking holidays and vacations into account
+    pub fn on_date(mut self, date: NaiveDate) -> Self {
+        self.date = Some(date);
+        self
+    }
+
+    /// The current time used by `unfinished` and `within`, instead of the local time
+    pub fn at(mut self, now: NaiveDateTime) -> Self {
+        self.now = Some(now);
+        self
+    }
+
+    /// Leaves out timed events which have already ended
+    pub fn unfinished(mut self) -> Self {
+        self.unfinished = true;
+        self
+    }
+
+    /// With `unfinished`, keeps timed events for `grace` after they end, so that one which runs
+    /// over doesn't vanish while it's still going
+    pub fn grace(mut self, grace: chrono::Duration) -> Self {
+        self.grace = Some(grace);
+        self
+    }
+
+    /// Only events whose title contains `title`, ignoring case
+    pub fn matching_title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_lowercase());
+        self
+    }
+
+    /// Only events from notes whose `category` is one of `categories`, ignoring case. Events
+    /// without a category are left out.
+    pub fn categories(mut self, categories: &[&str]) -> Self {
+        self.categories = Some(categories.iter().map(|c| c.to_lowercase()).collect());
+        self
+    }
+
+    /// Only events which have started or start within `duration` from now. All-day events count
+    /// as having started.
+    pub fn within(mut self, duration: chrono::Duration) -> Self {
+        self.within = Some(duration);
+        self
+    }
+
+    /// Leaves out timed events on the day which end by `from`, unless they're ongoing. Used with
+    /// `until`, only the events in that part of the day are kept.
+    pub fn from(mut self, from: NaiveTime) -> Self {
+        self.from = Some(from);
+        self
+    }
+
+    /// Leaves out timed events on the day which start after `until`, unless they're ongoing
+    pub fn until(mut self, until: NaiveTime) -> Self {
+        self.until = Some(until);
+        self
+    }
+
+    /// Leaves out timed events shorter than `min_duration`, like the zero-length markers some
+    /// tools write. All-day events are kept.
+    pub fn min_duration(mut self, min_duration: chrono::Duration) -> Self {
+        self.min_duration = Some(min_duration);
+        self
+    }
+
+    pub fn allday_filter(mut self, allday_filter: AllDayFilter) -> Self {
+        self.allday_filter = allday_filter;
+        self
+    }
+
+    /// Where all-day events are sorted relative to timed events
+    pub fn allday_position(mut self, allday_position: AllDayPosition) -> Self {
+        self.sort.allday_position = allday_position;
+        self
+    }
+
+    /// Which time timed events are sorted by
+    pub fn sort_by(mut self, by: SortBy) -> Self {
+        self.sort.by = by;
+        self
+    }
+
+    /// Lists the latest events first
+    pub fn reverse(mut self, reverse: bool) -> Self {
+        self.sort.reverse = reverse;
+        self
+    }
+
+    /// Orders the events as `sort` says, instead of setting each part of it
+    pub fn sorted(mut self, sort: SortSpec) -> Self {
+        self.sort = sort;
+        self
+    }
+
+    /// Hides every recurring event on holidays, not just those with `skipHolidays: true`
+    pub fn skip_holidays(mut self, skip_holidays: bool) -> Self {
+        self.skip_holidays = skip_holidays;
+        self
+    }
+
+    /// Leaves out the events snoozed on the day
+    pub fn without_snoozed(mut self, snoozed: &'a Snoozed) -> Self {
+        self.snoozed = Some(snoozed);
+        self
+    }
+
+    /// Leaves out recurring events which are the same as one in an earlier note, keeping the first
+    pub fn dedup_recurring(mut self, dedup_recurring: bool) -> Self {
+        self.dedup_recurring = dedup_recurring;
+        self
+    }
+
+    /// Includes the events from notes with `hidden: true`, which are left out otherwise
+    pub fn show_hidden(mut self, show_hidden: bool) -> Self {
+        self.show_hidden = show_hidden;
+        self
+    }
+
+    /// Shows deadlines from `days` days before they're due, counting down to them, rather than
+    /// only on the day
+    pub fn deadline_lookahead(mut self, days: u64) -> Self {
+        self.deadline_lookahead = days;
+        self
+    }
+
+    /// Keeps showing deadlines after the day they were due, as overdue
+    pub fn show_overdue(mut self, show_overdue: bool) -> Self {
+        self.show_overdue = show_overdue;
+        self
+    }
+
+    /// Only events for which `predicate` returns true
+    pub fn filter(mut self, predicate: impl Fn(&Event) -> bool + 'a) -> Self {
+        self.predicates.push(Box::new(predicate));
+        self
+    }
+
+    /// Applies the command line options which affect which events are shown and their order,
+    /// and the time given with `--now`
+    pub fn with_options(self, options: &Options) -> Self {
+        let mut query = self
+            .allday_filter(options.allday_filter)
+            .sorted(options.sort)
+            .skip_holidays(options.skip_holidays)
+            .dedup_recurring(options.dedup_fuzzy)
+            .show_hidden(options.show_hidden)
+            .deadline_lookahead(options.deadline_lookahead)
+            .show_overdue(options.show_overdue);
+        query.from = options.from.or(query.from);
+        query.grace = options.grace.or(query.grace);
+        query.until = options.until.or(query.until);
+        query.min_duration = options.min_duration.or(query.min_duration);
+        match options.now {
+            Some(now) => query.at(now),
+            None => query,
+        }
+    }
+
+    /// Whether the event matches, given the current time, the day it's being looked for on and
+    /// whether that day is a holiday or in a vacation, which are worked out once in `apply`
+    /// rather than for every event
+    fn matches(
+        &self,
+        sourced: &SourcedEvent,
+        now: NaiveDateTime,
+        date: NaiveDate,
+        is_holiday: bool,
+        on_vacation: bool,
+    ) -> bool {
+        let event = &sourced.event;
+        if sourced.hidden && !self.show_hidden {
+            return false;
+        }
+        if self.date.is_some()
+            && (!self.shown_on(event, date)
+                || skipped_on_holiday(event, is_holiday, self.skip_holidays)
+                || suppressed_by_vacation(event, on_vacation))
+        {
+            return false;
+        }
+        if let Some(title) = &self.title {
+            if !event.title().to_lowercase().contains(title) {
+                return false;
+            }
+        }
+        if let Some(categories) = &self.categories {
+            let category = sourced.category.as_deref().map(str::to_lowercase);
+            if !category.is_some_and(|category| categories.contains(&category)) {
+                return false;
+            }
+        }
+        if !self.allday_filter.keep(event)
+            || !self.predicates.iter().all(|predicate| predicate(event))
+        {
+            return false;
+        }
+        if self.min_duration.is_some_and(|min_duration| {
+            event.times_on(date).is_some() && event.duration() < min_duration
+        }) {
+            return false;
+        }
+        // checked last so that pinning only overrides the filters on time
+        self.in_time(event, now, date) || sourced.pinned
+    }
+
+    /// Whether the event is on `date`, or for a deadline whether it's due within
+    /// `deadline_lookahead` days of it, or was due before it with `show_overdue`, as long as it
+    /// hasn't been completed
+    fn shown_on(&self, event: &Event, date: NaiveDate) -> bool {
+        match event {
+            Event::Deadline {
+                completed: true, ..
+            } => false,
+            Event::Deadline { due, .. } if *due < date => self.show_overdue,
+            Event::Deadline { due, .. } => {
+                (*due - date).num_days() as u64 <= self.deadline_lookahead
+            }
+            _ => event.occurs_on(date),
+        }
+    }
+
+    /// Whether the event passes the filters on time: `unfinished`, then the `from`/`until`
+    /// window, then `within`, so that `within` only counts the events left in the window. They
+    /// all compare the date-times the event begins and ends at on `date`, so an event running
+    /// past midnight ends on the next day, and one running over ends at its `actualEnd`. All-day
+    /// events have no times, so they always pass.
+    fn in_time(&self, event: &Event, now: NaiveDateTime, date: NaiveDate) -> bool {
+        let (Some((begin, _)), Some(end)) = (event.times_on(date), event.running_until(date))
+        else {
+            return true;
+        };
+        if self.unfinished && end < now - self.grace.unwrap_or_default() {
+            return false;
+        }
+        let ongoing = begin <= now && now <= end;
+        if self.date.is_some() && !ongoing && !self.in_window(begin, end, date) {
+            return false;
+        }
+        self.within.is_none_or(|within| begin <= now + within)
+    }
+
+    /// Whether an event from `begin` to `end` is inside the `from`/`until` window on `date`. A
+    /// window whose `until` is before its `from`, like 22:00 to 02:00, goes round midnight: it's
+    /// the start of the day up to `until` and the end of it from `from`.
+    fn in_window(&self, begin: NaiveDateTime, end: NaiveDateTime, date: NaiveDate) -> bool {
+        let after_from = |from: NaiveTime| end > date.and_time(from);
+        let before_until = |until: NaiveTime| begin <= date.and_time(until);
+        match (self.from, self.until) {
+            (Some(from), Some(until)) if until < from => after_from(from) || before_until(until),
+            (from, until) => from.is_none_or(after_from) && until.is_none_or(before_until),
+        }
+    }
+
+    /// The matching events in the order they're listed
+    pub fn apply<'c>(&self, calendar: &'c Calendar) -> Vec<&'c SourcedEvent> {
+        let now = self
+            .now
+            .unwrap_or_else(|| chrono::Local::now().naive_local());
+        let date = self.date.unwrap_or(now.date());
+        let is_holiday = self.date.is_some() && calendar.is_holiday(date);
+        let on_vacation = self.date.is_some() && calendar.vacation_on(date).is_some();
+        let mut events: Vec<&SourcedEvent> = calendar
+            .all()
+            .filter(|sourced| self.matches(sourced, now, date, is_holiday, on_vacation))
+            // after everything else, since matching a note's path has to look at the filesystem
+            .filter(|sourced| {
+                !self
+                    .snoozed
+                    .is_some_and(|snoozed| snoozed.hides(sourced, date))
+            })
+            .collect();
+        if self.dedup_recurring {
+            let mut kept: Vec<&SourcedEvent> = Vec::new();
+            for sourced in events {
+                if !kept.iter().any(|earlier| {
+                    earlier.path != sourced.path && earlier.event.same_recurrence(&sourced.event)
+                }) {
+                    kept.push(sourced);
+                }
+            }
+            events = kept;
+        }
+        events.sort_by(|a, b| self.sort.compare_sourced(a, b));
+        if self.unfinished {
+            pinned_ended_last(&mut events, date, now);
+        }
+        overdue_first(&mut events, date);
+        events
+    }
+}
+
+/// Moves the pinned events which are over to the bottom of the list, keeping their order. They're
+/// only there because they're pinned, so they come after everything which is still to come.
+pub fn pinned_ended_last(events: &mut [&SourcedEvent], date: NaiveDate, now: NaiveDateTime) {
+    events.sort_by_key(|sourced| sourced.pinned && sourced.event.ended(date, now));
+}
+
+/// Moves the deadlines which are overdue on `date` to the top of the list, above even all-day
+/// events, keeping their order
+pub fn overdue_first(events: &mut [&SourcedEvent], date: NaiveDate) {
+    events.sort_by_key(|sourced| !is_overdue(&sourced.event, date));
+}
+
+fn is_overdue(event: &Event, date: NaiveDate) -> bool {
+    matches!(event, Event::Deadline { due, .. } if *due < date)
+}
+
+/// A line for each adjacent pair of `events`, as ordered by `sort`, `pinned_ended_last` and
+/// `overdue_first`, saying what put them in that order, for `--explain-sort`
+pub fn explain_order(
+    events: &[&SourcedEvent],
+    sort: &SortSpec,
+    date: NaiveDate,
+    now: NaiveDateTime,
+) -> Vec<String> {
+    let pinned_ended = |sourced: &SourcedEvent| sourced.pinned && sourced.event.ended(date, now);
+    events
+        .windows(2)
+        .map(|pair| {
+            if is_overdue(&pair[0].event, date) && !is_overdue(&pair[1].event, date) {
+                format!(
+                    "{} < {}: overdue deadlines go first",
+                    pair[0].event.title(),
+                    pair[1].event.title()
+                )
+            } else if !pinned_ended(pair[0]) && pinned_ended(pair[1]) {
+                format!(
+                    "{} < {}: pinned events which are over go last",
+                    pair[0].event.title(),
+                    pair[1].event.title()
+                )
+            } else {
+                sort.explain(pair[0], pair[1])
+            }
+        })
+        .collect()
+}
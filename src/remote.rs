@@ -0,0 +1,178 @@
+use crate::{config::Config, error::Error, event::SourcedEvent, ics::parse_ics};
+use std::{
+    io::Write,
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+/// A remote calendar (e.g. a CalDAV server's ICS export), configured in a `[source.<name>]`
+/// section of the config file
+#[derive(Debug)]
+pub struct Source {
+    pub name: String,
+    pub url: String,
+    pub username: Option<String>,
+    /// the environment variable holding the basic auth password
+    pub password_env: Option<String>,
+    /// the environment variable holding a bearer token
+    pub token_env: Option<String>,
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-' || c == '_'
+}
+
+/// Puts `value` in double quotes for a curl config file, escaping `\\` and `"`. A control
+/// character such as a newline would end the line and let the rest be read as another option,
+/// so it's refused.
+fn quote(value: &str) -> Result<String, Error> {
+    if value.chars().any(char::is_control) {
+        return Err(Error::Fetch(
+            "Source URLs and credentials cannot contain control characters".into(),
+        ));
+    }
+    Ok(format!(
+        "\"{}\"",
+        value.replace('\\', "\\\\").replace('"', "\\\"")
+    ))
+}
+
+impl Source {
+    pub fn from_config(config: &Config) -> Result<Vec<Source>, Error> {
+        config
+            .sections("source")
+            .into_iter()
+            .map(|name| {
+                // it's the name of the cache file, so it mustn't lead out of the cache directory
+                if name.is_empty() || !name.chars().all(is_name_char) {
+                    return Err(Error::Config(format!(
+                        "Source name '{}' can only contain letters, digits, '-' and '_'",
+                        name
+                    )));
+                }
+                let get = |key: &str| {
+                    config
+                        .get_str(&format!("source.{}.{}", name, key))
+                        .map(|value| value.map(String::from))
+                };
+                Ok(Source {
+                    name: name.into(),
+                    url: get("url")?
+                        .ok_or(Error::Config(format!("Source '{}' has no 'url'", name)))?,
+                    username: get("username")?,
+                    password_env: get("password_env")?,
+                    token_env: get("token_env")?,
+                })
+            })
+            .collect()
+    }
+
+    /// Where the last download is kept, named after the source. Anything in the name which
+    /// isn't allowed by the config file is replaced by `_`.
+    pub fn cache_path(&self) -> Option<PathBuf> {
+        let dir = std::env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+        Some(dir.join("obsidian-calendar-summary").join(format!(
+            "{}.ics",
+            self.name.replace(|c| !is_name_char(c), "_")
+        )))
+    }
+
+    /// The URL and credentials as a curl config file, which is passed on stdin so they don't show
+    /// up in the process list. The URL is in there too so that one starting with `-` can't be
+    /// taken as an option.
+    pub fn curl_config(&self) -> Result<String, Error> {
+        let env = |var: &Option<String>| -> Result<Option<String>, Error> {
+            var.as_ref()
+                .map(|var| {
+                    std::env::var(var).map_err(|_| {
+                        Error::Fetch(format!("Environment variable '{}' is not set", var))
+                    })
+                })
+                .transpose()
+        };
+        let mut curl_config = format!("url = {}\n", quote(&self.url)?);
+        if let Some(username) = &self.username {
+            let password = env(&self.password_env)?.unwrap_or_default();
+            curl_config += &format!("user = {}\n", quote(&format!("{}:{}", username, password))?);
+        }
+        if let Some(token) = env(&self.token_env)? {
+            curl_config += &format!(
+                "header = {}\n",
+                quote(&format!("Authorization: Bearer {}", token))?
+            );
+        }
+        Ok(curl_config)
+    }
+
+    /// Downloads the calendar with curl, which keeps this crate free of an HTTP client
+    fn fetch(&self) -> Result<String, Error> {
+        let curl_config = self.curl_config()?;
+
+        let mut child = Command::new("curl")
+            .args(["--silent", "--show-error", "--fail", "--location"])
+            // never hold up the summary for long
+            .args(["--max-time", "5", "--config", "-"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| Error::Fetch(format!("Cannot run curl: {}", e)))?;
+        let output = child
+            .stdin
+            .take()
+            .unwrap() // stdin was set to piped above
+            .write_all(curl_config.as_bytes())
+            .and_then(|_| child.wait_with_output())
+            .map_err(|e| Error::Fetch(format!("Cannot run curl: {}", e)))?;
+        if !output.status.success() {
+            return Err(Error::Fetch(
+                String::from_utf8_lossy(&output.stderr).trim().into(),
+            ));
+        }
+        String::from_utf8(output.stdout).map_err(|e| Error::Fetch(e.to_string()))
+    }
+
+    /// Loads the events from this source, downloading a fresh copy unless `offline` is set. If the
+    /// download fails the cached copy from the last successful download is used instead, so a
+    /// network problem never stops the summary from being shown.
+    pub fn load(&self, offline: bool) -> Vec<SourcedEvent> {
+        let cache_path = self.cache_path();
+        let contents = if offline {
+            None
+        } else {
+            match self.fetch() {
+                Ok(contents) => {
+                    if let Some(cache_path) = &cache_path {
+                        let saved = cache_path
+                            .parent()
+                            .map_or(Ok(()), std::fs::create_dir_all)
+                            .and_then(|_| std::fs::write(cache_path, &contents));
+                        if let Err(e) = saved {
+                            eprintln!("Warning: cannot cache source '{}': {}", self.name, e);
+                        }
+                    }
+                    Some(contents)
+                }
+                Err(e) => {
+                    eprintln!(
+                        "Warning: cannot fetch source '{}', using the cached copy: {}",
+                        self.name, e
+                    );
+                    None
+                }
+            }
+        };
+        let contents = match (contents, &cache_path) {
+            (Some(contents), _) => contents,
+            (None, Some(cache_path)) => std::fs::read_to_string(cache_path).unwrap_or_default(),
+            (None, None) => String::new(),
+        };
+        let path = cache_path.unwrap_or_else(|| PathBuf::from(&self.url));
+        parse_ics(&contents)
+            .into_iter()
+            .map(|event| SourcedEvent::new(event, path.clone()))
+            .collect()
+    }
+}
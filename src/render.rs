@@ -0,0 +1,927 @@
+use crate::{
+    cli::SortBy,
+    event::{timed_span, Event, SourcedEvent},
+    lang::Lang,
+    theme::{Colour, Palette},
+    week::day_heading,
+};
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use std::fmt::Write;
+use std::path::Path;
+
+/// The displayed pieces of an event, worked out once so that every renderer shows the same thing
+#[derive(Debug)]
+pub struct Fields<'a> {
+    /// "09:00 - 10:30" for timed events, "Today" or "May 03 - May 10" for all-day events
+    pub when: String,
+    /// "09:00", only for timed events
+    pub start: Option<String>,
+    /// "10:30", only for timed events
+    pub end: Option<String>,
+    /// "(Now)", "(5 mins)" etc for timed events, "(6 days left)" etc for all-day events which
+    /// last more than a day
+    pub countdown: Option<String>,
+    /// how long the event lasts, e.g. "(1h 30m)" or "(3 days)" for all-day events, and empty for
+    /// deadlines so that they still line up, only with `--show-duration`
+    pub duration: Option<String>,
+    /// how long a timed event lasts in minutes
+    pub duration_minutes: Option<i64>,
+    /// minutes until the event starts, only for timed events
+    pub minutes_until: Option<i64>,
+    /// minutes until it's time to leave for the event, only for timed events with a travel time
+    pub minutes_until_leave: Option<i64>,
+    /// minutes until the event ends, only for timed events
+    pub minutes_left: Option<i64>,
+    /// how far through the event we are as a percentage, only for ongoing timed events
+    pub progress: Option<i64>,
+    /// which occurrence this is out of how many, only for recurring events with a `startRecur`
+    /// and an `endRecur`
+    pub occurrence: Option<(usize, usize)>,
+    /// the label from the note's `numberOccurrences` and which occurrence this is, e.g.
+    /// ("Lecture", 7), which is worked out from the calendar since cancelled ones don't count
+    pub numbered: Option<(&'a str, usize)>,
+    /// days from today until the last day of the event, only for all-day events and deadlines,
+    /// which are overdue when it's negative
+    pub days_remaining: Option<i64>,
+    /// "Tomorrow" or e.g. "Mon 21 Oct", only for events shown from a later day by
+    /// `--show-next-day`
+    pub day: Option<String>,
+    pub date: NaiveDate,
+    pub ongoing: bool,
+    /// over already, which only pinned events are shown once they are
+    pub ended: bool,
+    /// from a note with `pinned: true`
+    pub pinned: bool,
+    /// from a note with `hidden: true`, only shown with `--show-hidden`
+    pub hidden: bool,
+    /// made up by the program rather than read from a note, like a suggested focus block
+    pub synthetic: bool,
+    /// from a note with `deadline: true`, shown by when it's due
+    pub deadline: bool,
+    /// how many days ago a deadline was due, only for deadlines shown after their day by
+    /// `--show-overdue`
+    pub overdue_days: Option<i64>,
+    pub category: Option<&'a str>,
+    /// e.g. "#2e8fb8"
+    pub color: Option<&'a str>,
+    /// the meeting's link, from the note's `url`
+    pub url: Option<&'a str>,
+    pub title: &'a str,
+    pub path: &'a Path,
+    /// what the suffix is written in
+    pub lang: &'a Lang,
+}
+
+/// The whole minutes in `delta`, rounded up so that something 59 seconds away is a minute away
+/// rather than none
+pub fn minutes_rounded_up(delta: chrono::Duration) -> i64 {
+    let minutes = delta.num_minutes();
+    // num_minutes rounds towards zero, which is already up for times in the past
+    minutes + i64::from(delta > chrono::Duration::try_minutes(minutes).unwrap_or_default())
+}
+
+/// A time until something as e.g. "5 mins" or "2 hours", or with `seconds` e.g. "4 mins 30 secs"
+/// when it's less than an hour
+fn delta_text(delta: chrono::Duration, lang: &Lang, seconds: bool) -> String {
+    let minutes = minutes_rounded_up(delta);
+    if seconds && delta.num_hours() == 0 {
+        let (minutes, seconds) = (delta.num_minutes(), delta.num_seconds() % 60);
+        match (minutes, seconds) {
+            (0, seconds) => lang.seconds.format(seconds),
+            (minutes, 0) => lang.minutes.format(minutes),
+            (minutes, seconds) => format!(
+                "{} {}",
+                lang.minutes.format(minutes),
+                lang.seconds.format(seconds)
+            ),
+        }
+    } else if minutes < 60 {
+        lang.minutes.format(minutes)
+    } else {
+        lang.hours.format(minutes / 60)
+    }
+}
+
+/// The countdown shown next to timed events, e.g. "(5 mins)", "(2 hours)" or "(Now)" once the
+/// event has started
+pub fn format_delta(delta: chrono::Duration, lang: &Lang, seconds: bool) -> String {
+    if delta <= chrono::Duration::zero() {
+        format!("({})", lang.now)
+    } else {
+        format!("({})", delta_text(delta, lang, seconds))
+    }
+}
+
+/// The countdown shown instead of `format_delta` for events which have ended but are still shown
+/// because of `--grace`, given how long ago they ended, e.g. "(over by 4 mins)"
+pub fn format_over(delta: chrono::Duration, lang: &Lang, seconds: bool) -> String {
+    format!(
+        "({})",
+        lang.over_by
+            .replace("{time}", &delta_text(delta, lang, seconds))
+    )
+}
+
+/// The countdown shown instead of `format_delta` for events with a travel time, given the time
+/// until it's time to leave, e.g. "(leave in 5 mins)" or "(leave now)"
+pub fn format_leave(delta: chrono::Duration, lang: &Lang, seconds: bool) -> String {
+    if delta <= chrono::Duration::zero() {
+        format!("({})", lang.leave_now)
+    } else {
+        format!(
+            "({})",
+            lang.leave_in
+                .replace("{time}", &delta_text(delta, lang, seconds))
+        )
+    }
+}
+
+/// The countdown shown with `--sort end`, given the time until the event ends, e.g.
+/// "(ends in 20 mins)"
+pub fn format_end(delta: chrono::Duration, lang: &Lang, seconds: bool) -> String {
+    format!(
+        "({})",
+        lang.ends_in
+            .replace("{time}", &delta_text(delta, lang, seconds))
+    )
+}
+
+/// How long is left of an all-day event, given the number of days from today until its last day
+pub fn humanize_days(days: i64, lang: &Lang) -> String {
+    match days {
+        // this shouldn't happen because finished events are filtered out
+        ..=-1 => lang.ended.to_lowercase(),
+        0 => lang.last_day.clone(),
+        1 => lang.ends_tomorrow.clone(),
+        days => lang.days_left.format(days),
+    }
+}
+
+/// How far off `date` is from `today` in words: "Today", "Tomorrow", the weekday for the rest of
+/// the next week, e.g. "Thu", and beyond that (or before today) the date, e.g. "Thu 16 May"
+pub fn humanize_date(date: NaiveDate, today: NaiveDate, lang: &Lang) -> String {
+    match (date - today).num_days() {
+        0 => lang.today.clone(),
+        1 => lang.tomorrow.clone(),
+        // a week on is the same weekday as today, so it needs the date
+        2..=6 => lang.weekday(date.weekday()).to_string(),
+        _ => lang.weekday_date(date),
+    }
+}
+
+/// For `--relative-dates`, names the day of events on other days as `humanize_date` does, e.g.
+/// "Thu" instead of "Thu 16 May". The date itself is still in `date`.
+pub fn relative_days(fields: &mut [Fields], today: NaiveDate) {
+    for fields in fields.iter_mut().filter(|fields| fields.day.is_some()) {
+        let day = humanize_date(fields.date, today, fields.lang);
+        // all-day events on another day are described by just the day
+        if fields.start.is_none() {
+            fields.when = day.clone();
+        }
+        fields.day = Some(day);
+    }
+}
+
+/// `begin` rounded down and `end` rounded up to a multiple of `step` after midnight, so that the
+/// times shown always cover the real ones: 09:02 - 09:58 is 09:00 - 10:00 at 5 minutes. An end
+/// rounded up to midnight is 00:00, as an event ending at midnight is written. A `step` under a
+/// second leaves the times as they are.
+pub fn round_out(
+    begin: NaiveTime,
+    end: NaiveTime,
+    step: chrono::Duration,
+) -> (NaiveTime, NaiveTime) {
+    let step = step.num_seconds();
+    if step <= 0 {
+        return (begin, end);
+    }
+    let begin = i64::from(begin.num_seconds_from_midnight());
+    let end = i64::from(end.num_seconds_from_midnight());
+    let time = |seconds: i64| {
+        NaiveTime::from_num_seconds_from_midnight_opt((seconds % 86_400) as u32, 0)
+            .unwrap_or(NaiveTime::MIN)
+    };
+    (
+        time(begin - begin % step),
+        time(end + (step - end % step) % step),
+    )
+}
+
+/// For `--round`, shows the times of timed events as `round_out` does. Only what's shown
+/// changes: the countdown, duration and filters still go by the real times.
+pub fn round_times(fields: &mut [Fields], events: &[&SourcedEvent], step: chrono::Duration) {
+    for (fields, sourced) in fields.iter_mut().zip(events) {
+        let (Event::Once { begin, end, .. } | Event::Recurring { begin, end, .. }) = sourced.event
+        else {
+            continue;
+        };
+        let (begin, end) = round_out(begin, end, step);
+        let start = format!("{:02}:{:02}", begin.hour(), begin.minute());
+        let end = format!("{:02}:{:02}", end.hour(), end.minute());
+        fields.when = format!("{} - {}", start, end);
+        fields.start = Some(start);
+        fields.end = Some(end);
+    }
+}
+
+/// A number of minutes written as e.g. "1h 20m" or "45m"
+pub fn humanize_minutes(minutes: i64, lang: &Lang) -> String {
+    let hours = || lang.short_hours.replace("{n}", &(minutes / 60).to_string());
+    let minutes_text = || {
+        lang.short_minutes
+            .replace("{n}", &(minutes % 60).to_string())
+    };
+    match (minutes / 60, minutes % 60) {
+        (0, _) => minutes_text(),
+        (_, 0) => hours(),
+        _ => format!("{} {}", hours(), minutes_text()),
+    }
+}
+
+/// How far `now` is through the time from `begin` to `end`, as a percentage rounded to the
+/// nearest whole number
+pub fn progress(begin: NaiveDateTime, end: NaiveDateTime, now: NaiveDateTime) -> i64 {
+    let total = (end - begin).num_seconds();
+    if total <= 0 {
+        return 100;
+    }
+    let elapsed = (now - begin).num_seconds().clamp(0, total);
+    (elapsed * 100 + total / 2) / total
+}
+
+/// A percentage drawn as e.g. `[####----] 52%`
+pub fn progress_bar(percent: i64) -> String {
+    const WIDTH: i64 = 8;
+    let filled = ((percent * WIDTH + 50) / 100).clamp(0, WIDTH) as usize;
+    format!(
+        "[{}{}] {}%",
+        "#".repeat(filled),
+        "-".repeat(WIDTH as usize - filled),
+        percent
+    )
+}
+
+/// Only keeps the progress of the ongoing event which ends soonest, so that just one progress bar
+/// is shown
+pub fn keep_soonest_progress(fields: &mut [Fields]) {
+    let soonest = fields
+        .iter()
+        .enumerate()
+        .filter(|(_, fields)| fields.progress.is_some())
+        .min_by_key(|(_, fields)| fields.minutes_left)
+        .map(|(i, _)| i);
+    for (i, fields) in fields.iter_mut().enumerate() {
+        if Some(i) != soonest {
+            fields.progress = None;
+        }
+    }
+}
+
+impl<'a> Fields<'a> {
+    /// The fields of the event as it occurs on `date`, which is normally today. With
+    /// `SortBy::End` the countdown of timed events is to when they end instead. With `seconds`
+    /// the countdown goes down to the second. Events which ended less than `grace` ago say how
+    /// long they've overrun by.
+    pub fn new(
+        sourced: &'a SourcedEvent,
+        date: NaiveDate,
+        now: NaiveDateTime,
+        sort: SortBy,
+        lang: &'a Lang,
+        seconds: bool,
+        grace: chrono::Duration,
+    ) -> Self {
+        let event = &sourced.event;
+        let path = &sourced.path;
+        let day = match (date - now.date()).num_days() {
+            0 => None,
+            1 => Some(lang.tomorrow.clone()),
+            _ => Some(lang.weekday_date(date)),
+        };
+        match event {
+            Event::Once { begin, end, .. } | Event::Recurring { begin, end, .. } => {
+                // compare whole date-times so that nothing goes wrong around midnight, e.g. for
+                // tomorrow's 00:05 event at 23:58 or an event which runs past midnight
+                let (begin_at, end_at) = timed_span(date, *begin, *end);
+                // an event running over goes on until its `actualEnd`
+                let running_until = event.running_until(date).unwrap_or(end_at);
+                let running_over = end_at < now && now <= running_until;
+                let delta = begin_at - now;
+                // up to and including the minute it ends, like the `--from`/`--until` window
+                let ongoing = begin_at <= now && now <= running_until;
+                let ended = running_until < now;
+                let leave_delta = event.travel_minutes().map(|minutes| {
+                    delta - chrono::Duration::try_minutes(minutes.into()).unwrap_or_default()
+                });
+                let countdown = match (sort, leave_delta) {
+                    _ if running_over => format!(
+                        "({})",
+                        lang.running_over
+                            .replace("{time}", &running_until.format("%H:%M").to_string())
+                    ),
+                    _ if ended && now - running_until <= grace => {
+                        format_over(now - running_until, lang, seconds)
+                    }
+                    _ if ended => format!("({})", lang.ended),
+                    (SortBy::End, _) => format_end(end_at - now, lang, seconds),
+                    (SortBy::Start, Some(leave_delta)) if now < begin_at => {
+                        format_leave(leave_delta, lang, seconds)
+                    }
+                    (SortBy::Start, _) => format_delta(delta, lang, seconds),
+                };
+                let start = format!("{:02}:{:02}", begin.hour(), begin.minute());
+                let end_text = format!("{:02}:{:02}", end.hour(), end.minute());
+                let duration = event.duration().num_minutes();
+                Self {
+                    when: format!("{} - {}", start, end_text),
+                    duration: Some(format!("({})", humanize_minutes(duration, lang))),
+                    duration_minutes: Some(duration),
+                    start: Some(start),
+                    end: Some(end_text),
+                    countdown: Some(countdown),
+                    minutes_until: Some(minutes_rounded_up(delta)),
+                    minutes_until_leave: leave_delta.map(minutes_rounded_up),
+                    minutes_left: Some(minutes_rounded_up(running_until - now)),
+                    progress: ongoing.then(|| progress(begin_at, running_until, now)),
+                    occurrence: event.occurrence_index(date).zip(event.total_occurrences()),
+                    numbered: None,
+                    url: sourced.url.as_deref(),
+                    days_remaining: None,
+                    day,
+                    date,
+                    ongoing,
+                    ended,
+                    pinned: sourced.pinned,
+                    hidden: sourced.hidden,
+                    synthetic: sourced.synthetic,
+                    deadline: false,
+                    overdue_days: None,
+                    category: sourced.category.as_deref(),
+                    color: sourced.color.as_deref(),
+                    title: event.title(),
+                    path,
+                    lang,
+                }
+            }
+            Event::AllDay {
+                title,
+                begin_date,
+                end_date,
+                ..
+            } => {
+                let last_day = end_date.pred_opt().unwrap_or(*end_date);
+                let days_remaining = (last_day - now.date()).num_days();
+                let single_day = (*end_date - *begin_date).num_days() == 1;
+                Self {
+                    when: if let Some(day) = &day {
+                        day.clone()
+                    } else if single_day {
+                        lang.today.clone()
+                    } else {
+                        format!(
+                            "{} - {}",
+                            lang.month_day(*begin_date),
+                            lang.month_day(last_day),
+                        )
+                    },
+                    duration: Some(format!(
+                        "({})",
+                        lang.days.format(event.duration().num_days())
+                    )),
+                    duration_minutes: None,
+                    start: None,
+                    end: None,
+                    countdown: (!single_day && day.is_none())
+                        .then(|| format!("({})", humanize_days(days_remaining, lang))),
+                    minutes_until: None,
+                    minutes_until_leave: None,
+                    minutes_left: None,
+                    progress: None,
+                    occurrence: None,
+                    numbered: None,
+                    url: sourced.url.as_deref(),
+                    days_remaining: Some(days_remaining),
+                    ongoing: day.is_none(),
+                    ended: false,
+                    pinned: sourced.pinned,
+                    hidden: sourced.hidden,
+                    synthetic: sourced.synthetic,
+                    deadline: false,
+                    overdue_days: None,
+                    category: sourced.category.as_deref(),
+                    color: sourced.color.as_deref(),
+                    day,
+                    date,
+                    title,
+                    path,
+                    lang,
+                }
+            }
+            Event::Deadline { title, due, .. } => {
+                let days_until = (*due - date).num_days();
+                Self {
+                    when: match &day {
+                        Some(day) => day.clone(),
+                        None if days_until == 0 => lang.due_today.clone(),
+                        None if days_until > 0 => lang.due_in.format(days_until),
+                        None => lang.overdue.format(-days_until),
+                    },
+                    duration: Some(String::new()),
+                    duration_minutes: None,
+                    start: None,
+                    end: None,
+                    countdown: None,
+                    minutes_until: None,
+                    minutes_until_leave: None,
+                    minutes_left: None,
+                    progress: None,
+                    occurrence: None,
+                    numbered: None,
+                    url: sourced.url.as_deref(),
+                    days_remaining: Some((*due - now.date()).num_days()),
+                    ongoing: false,
+                    ended: false,
+                    pinned: sourced.pinned,
+                    hidden: sourced.hidden,
+                    synthetic: sourced.synthetic,
+                    deadline: true,
+                    overdue_days: (days_until < 0).then_some(-days_until),
+                    category: sourced.category.as_deref(),
+                    color: sourced.color.as_deref(),
+                    day,
+                    date,
+                    title,
+                    path,
+                    lang,
+                }
+            }
+        }
+    }
+}
+
+/// How an event is highlighted by the coloured formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    /// a pinned event which is over, or a hidden one shown by `--show-hidden`, which is dimmed
+    Ended,
+    /// a deadline which has passed, shown by `--show-overdue`
+    Overdue,
+    AllDay,
+    Ongoing,
+    /// starts within the `--soon` threshold
+    Imminent,
+    Later,
+}
+
+impl Fields<'_> {
+    /// How the event is highlighted. Events with a travel time are imminent when it's nearly
+    /// time to leave rather than when they're about to begin.
+    pub fn role(&self, soon: i64) -> Role {
+        match self.minutes_until_leave.or(self.minutes_until) {
+            _ if self.ended || self.hidden => Role::Ended,
+            _ if self.overdue_days.is_some() => Role::Overdue,
+            None => Role::AllDay,
+            Some(_) if self.ongoing => Role::Ongoing,
+            Some(minutes) if minutes <= soon => Role::Imminent,
+            Some(_) => Role::Later,
+        }
+    }
+
+    /// The short form of the event used by the single-line formats, e.g. `*09:00 Standup`
+    fn segment(&self) -> String {
+        if let Some(day) = &self.day {
+            return match &self.start {
+                Some(start) => format!("{} {} {}", day, start, self.title),
+                None => format!("{} {}", day, self.title),
+            };
+        }
+        match &self.start {
+            Some(start) => format!(
+                "{}{} {}{}{}",
+                if self.ongoing { "*" } else { "" },
+                start,
+                self.marker(),
+                self.title,
+                self.suffix()
+            ),
+            None => self.title.into(),
+        }
+    }
+
+    /// Put before the title of synthetic events to tell them apart from real ones, and of pinned
+    /// events
+    fn marker(&self) -> &'static str {
+        if self.synthetic {
+            "· "
+        } else if self.pinned {
+            "📌 "
+        } else {
+            ""
+        }
+    }
+
+    /// Put after the title: ` 🔗` for events with a link, e.g. ` (Lecture 7)`, ` (3 of 10)` and
+    /// the progress bar
+    fn suffix(&self) -> String {
+        let link = if self.url.is_some() { " 🔗" } else { "" };
+        let numbered = self
+            .numbered
+            .map(|(label, n)| format!(" ({} {})", label, n));
+        let occurrence = self.occurrence.map(|(index, total)| {
+            let occurrence = self.lang.occurrence.replace("{n}", &index.to_string());
+            format!(" ({})", occurrence.replace("{total}", &total.to_string()))
+        });
+        let progress = self
+            .progress
+            .map(|percent| format!(" {}", progress_bar(percent)));
+        link.to_string()
+            + &numbered.unwrap_or_default()
+            + &occurrence.unwrap_or_default()
+            + &progress.unwrap_or_default()
+    }
+}
+
+/// The line `--header` puts above the list, e.g. "Fri 03 May 2024 · W18 · 3 events", counting the
+/// events after they've been filtered
+pub fn header(date: NaiveDate, count: usize, lang: &Lang) -> String {
+    format!(
+        "{} · {}",
+        day_heading(date, lang),
+        lang.events.format(count as i64)
+    )
+}
+
+/// The default output: one aligned line per event
+pub fn line(fields: &Fields) -> String {
+    if let Some(day) = &fields.day {
+        return match &fields.start {
+            Some(start) => format!("{} {} | {}", day, start, fields.title),
+            None => format!("{} | {}", day, fields.title),
+        };
+    }
+    // the duration is padded so that the countdowns after it line up
+    let (when, width) = match &fields.duration {
+        Some(duration) => (format!("{} {:<9}", fields.when, duration), 34),
+        None => (fields.when.clone(), 24),
+    };
+    match (&fields.start, &fields.countdown) {
+        (Some(_), countdown) => format!(
+            "{} {:<10} | {}{}{}",
+            when,
+            countdown.as_deref().unwrap_or_default(),
+            fields.marker(),
+            fields.title,
+            fields.suffix()
+        ),
+        (None, Some(countdown)) => format!(
+            "{:<width$} | {}",
+            format!("{} {}", when.trim_end(), countdown),
+            fields.title
+        ),
+        (None, None) => format!("{:<width$} | {}", when.trim_end(), fields.title),
+    }
+}
+
+/// The event that matters most right now: the first ongoing or upcoming timed event, or the
+/// first all-day event if there are no timed events left
+pub fn next<'a, 'b>(fields: &'b [Fields<'a>]) -> Option<&'b Fields<'a>> {
+    next_timed(fields).or(fields.first())
+}
+
+/// The first ongoing or upcoming timed event, skipping pinned events which are over and hidden
+/// ones
+pub fn next_timed<'a, 'b>(fields: &'b [Fields<'a>]) -> Option<&'b Fields<'a>> {
+    next_timed_where(fields, |_| true)
+}
+
+/// Like `next_timed`, but only out of the events `keep` is true for. An ongoing event comes
+/// before any upcoming one, then the one which started or starts first, whichever way the events
+/// are sorted.
+pub fn next_timed_where<'a, 'b>(
+    fields: &'b [Fields<'a>],
+    keep: impl Fn(&Fields) -> bool,
+) -> Option<&'b Fields<'a>> {
+    fields
+        .iter()
+        .filter(|fields| fields.start.is_some() && !fields.ended && !fields.hidden)
+        .filter(|fields| keep(fields))
+        .min_by_key(|fields| (!fields.ongoing, fields.minutes_until))
+}
+
+/// Just the time until the next timed event starts: the number of minutes (or e.g. "1h 20m" with
+/// `humanize`), or "now" if one is ongoing. None if there are no timed events left today.
+pub fn next_in(fields: &[Fields], humanize: bool, lang: &Lang) -> Option<String> {
+    let next = next_timed(fields)?;
+    let minutes = next.minutes_until?;
+    Some(if next.ongoing {
+        lang.next_in_now.clone()
+    } else if humanize {
+        humanize_minutes(minutes, lang)
+    } else {
+        minutes.to_string()
+    })
+}
+
+/// Whether there's an event on right now, for `--format status`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    /// a timed event is ongoing
+    Busy,
+    /// the next timed event starts (or it's time to leave for it) within the `--soon` threshold
+    Soon,
+    Free,
+}
+
+impl Status {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Busy => "busy",
+            Self::Soon => "soon",
+            Self::Free => "free",
+        }
+    }
+}
+
+/// Whether there's an event on, using the same roles as the coloured formats, along with the
+/// ongoing or imminent event which decided it. All-day events, made-up ones like focus blocks,
+/// pinned events which are over and hidden events don't make anyone busy.
+pub fn classify<'a, 'b>(fields: &'b [Fields<'a>], soon: i64) -> (Status, Option<&'b Fields<'a>>) {
+    let timed = || {
+        fields
+            .iter()
+            .filter(|fields| !fields.synthetic && fields.role(soon) != Role::AllDay)
+    };
+    if let Some(ongoing) = timed().find(|fields| fields.role(soon) == Role::Ongoing) {
+        return (Status::Busy, Some(ongoing));
+    }
+    let imminent = timed()
+        .filter(|fields| fields.role(soon) == Role::Imminent)
+        .min_by_key(|fields| fields.minutes_until_leave.or(fields.minutes_until));
+    match imminent {
+        Some(imminent) => (Status::Soon, Some(imminent)),
+        None => (Status::Free, None),
+    }
+}
+
+/// Exactly one of `busy`, `soon` or `free`, with `with_title` followed by the title of the event
+/// that's on or coming up
+pub fn status(fields: &[Fields], soon: i64, with_title: bool) -> String {
+    match classify(fields, soon) {
+        (status, Some(fields)) if with_title => format!("{} {}", status.as_str(), fields.title),
+        (status, _) => status.as_str().into(),
+    }
+}
+
+/// Pushes as much of `text` onto `out` as fits in `remaining` characters, ending with '…' if it
+/// had to be cut short. Returns false once there is no more room.
+fn push_truncated(out: &mut String, text: &str, remaining: &mut usize) -> bool {
+    let len = text.chars().count();
+    if len >= *remaining && len > 0 {
+        if len == *remaining {
+            *out += text;
+        } else if *remaining > 0 {
+            out.extend(text.chars().take(*remaining - 1));
+            out.push('…');
+        }
+        *remaining = 0;
+        return false;
+    }
+    *out += text;
+    *remaining -= len;
+    true
+}
+
+/// Joins the events onto a single line, wrapping each one in the (opening, closing) markup
+/// returned by `markup`. The visible text (not counting the markup) is cut off at `max_width`
+/// characters.
+fn single_line(
+    fields: &[Fields],
+    separator: &str,
+    max_width: Option<usize>,
+    markup: impl Fn(&Fields) -> (String, String),
+) -> String {
+    let mut out = String::new();
+    let mut remaining = max_width.unwrap_or(usize::MAX);
+    for (i, fields) in fields.iter().enumerate() {
+        if i > 0 && !push_truncated(&mut out, separator, &mut remaining) {
+            break;
+        }
+        let (open, close) = markup(fields);
+        out += &open;
+        let fits = push_truncated(&mut out, &fields.segment(), &mut remaining);
+        out += &close;
+        if !fits {
+            break;
+        }
+    }
+    out
+}
+
+/// Compact output with every event on a single line, e.g. `*09:00 Standup | 11:30 1:1 Sam`.
+/// Ongoing timed events are marked with an asterisk.
+pub fn oneline(fields: &[Fields], separator: &str, max_width: Option<usize>) -> String {
+    single_line(fields, separator, max_width, |_| {
+        (String::new(), String::new())
+    })
+}
+
+/// Like `oneline`, but with each event coloured using tmux style codes (`#[fg=yellow]`), since
+/// tmux doesn't understand ANSI escapes in the status line
+pub fn tmux(
+    fields: &[Fields],
+    separator: &str,
+    soon: i64,
+    max_width: Option<usize>,
+    palette: &Palette,
+) -> String {
+    single_line(fields, separator, max_width, |fields| {
+        let style = palette
+            .colour(fields.role(soon))
+            .map_or("default".into(), Colour::tmux);
+        (format!("#[{}]", style), "#[default]".into())
+    })
+}
+
+/// The `obsidian://` URI which opens the note in Obsidian
+fn obsidian_uri(path: &Path) -> String {
+    let path = std::fs::canonicalize(path).unwrap_or(path.to_path_buf());
+    let mut uri = String::from("obsidian://open?path=");
+    for byte in path.to_string_lossy().bytes() {
+        if byte.is_ascii_alphanumeric() || b"-_.~".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri += &format!("%{:02X}", byte);
+        }
+    }
+    uri
+}
+
+/// Like `oneline`, but coloured with Polybar formatting tags (`%{F#rrggbb}`). With `click`, each
+/// event is wrapped in a click action which opens its note in Obsidian.
+pub fn polybar(
+    fields: &[Fields],
+    separator: &str,
+    soon: i64,
+    max_width: Option<usize>,
+    click: bool,
+    palette: &Palette,
+) -> String {
+    single_line(fields, separator, max_width, |fields| {
+        let (mut open, mut close) = match palette.colour(fields.role(soon)) {
+            Some(colour) => (format!("%{{F{}}}", colour.hex()), "%{F-}".into()),
+            None => (String::new(), String::new()),
+        };
+        // synthetic events have no note to open
+        if click && !fields.synthetic {
+            // colons inside the command have to be escaped so polybar doesn't end the tag early
+            let command = format!("xdg-open {}", obsidian_uri(fields.path)).replace(':', "\\:");
+            open = format!("%{{A1:{}:}}{}", command, open);
+            close += "%{A}";
+        }
+        (open, close)
+    })
+}
+
+/// The i3blocks protocol: the full text on the first line, the short text (just the next event)
+/// on the second and the colour of the next event on the third
+pub fn i3blocks(fields: &[Fields], separator: &str, soon: i64, palette: &Palette) -> String {
+    let next = next(fields);
+    format!(
+        "{}\n{}\n{}\n",
+        oneline(fields, separator, None),
+        next.map(Fields::segment).unwrap_or_default(),
+        next.and_then(|next| palette.colour(next.role(soon)))
+            .map_or("", Colour::hex)
+    )
+}
+
+/// The characters kept in `--format compact`, besides ASCII letters and digits. `;` separates
+/// the events, so it isn't one of them.
+const COMPACT_SAFE: &str = " -_.,:!?'()/&+#@";
+
+/// A title for `--format compact`: every character which isn't an ASCII letter or digit or in
+/// `COMPACT_SAFE` replaced with `_`, one for one, then cut down to `max_title` characters, with
+/// any spaces left at either end removed
+pub fn compact_title(title: &str, max_title: Option<usize>) -> String {
+    let safe: String = title
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || COMPACT_SAFE.contains(c) {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(max_title.unwrap_or(usize::MAX))
+        .collect();
+    safe.trim_end().into()
+}
+
+/// Every event on one short line of plain ASCII, to be put in a QR code, e.g.
+/// `0900-0915 Standup;1130-1200 1:1;AD Conference`. Timed events start with their times without
+/// the colons, all-day events with `AD` and deadlines with `DUE`. Made-up events like focus
+/// blocks are left out.
+pub fn compact(fields: &[Fields], max_title: Option<usize>) -> String {
+    fields
+        .iter()
+        .filter(|fields| !fields.synthetic)
+        .map(|fields| {
+            let when = match (&fields.start, &fields.end) {
+                (Some(start), Some(end)) => {
+                    format!("{}-{}", start.replace(':', ""), end.replace(':', ""))
+                }
+                _ if fields.deadline => "DUE".into(),
+                _ => "AD".into(),
+            };
+            format!("{} {}", when, compact_title(fields.title, max_title))
+        })
+        .collect::<Vec<String>>()
+        .join(";")
+}
+
+/// Quotes and escapes a string for JSON
+pub fn json_string(s: &str) -> String {
+    let mut out = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => out += "\\\"",
+            '\\' => out += "\\\\",
+            '\n' => out += "\\n",
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32).unwrap(), // writing to a String can't fail
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn json_option<T: std::fmt::Display>(value: Option<T>) -> String {
+    value.map_or("null".into(), |value| value.to_string())
+}
+
+/// A JSON array with an object for each event
+pub fn json(fields: &[Fields]) -> String {
+    let objects: Vec<String> = fields.iter().map(json_object).collect();
+    format!("[{}]", objects.join(","))
+}
+
+/// The same objects as `json`, each on its own line (NDJSON). With `summary`, they're followed
+/// by a line like `{"summary":true,"count":3,"generated_at":"2024-05-03T09:00:00"}`, which is
+/// there even when there are no events, so that a reader knows it has them all.
+pub fn json_lines(fields: &[Fields], summary: Option<NaiveDateTime>) -> String {
+    let mut out = String::new();
+    for fields in fields {
+        writeln!(out, "{}", json_object(fields)).unwrap(); // writing to a String can't fail
+    }
+    if let Some(generated_at) = summary {
+        writeln!(
+            out,
+            "{{\"summary\":true,\"count\":{},\"generated_at\":{}}}",
+            fields.len(),
+            json_string(&generated_at.format("%Y-%m-%dT%H:%M:%S").to_string())
+        )
+        .unwrap();
+    }
+    out
+}
+
+fn json_object(fields: &Fields) -> String {
+    format!(
+                "{{\"title\":{},\"all_day\":{},\"deadline\":{},\"overdue_days\":{},\"when\":{},\"start\":{},\"end\":{},\"ongoing\":{},\"ended\":{},\"duration_minutes\":{},\"minutes_until\":{},\"minutes_until_leave\":{},\"percentage\":{},\"occurrence\":{},\"occurrences\":{},\"numbered\":{},\"days_remaining\":{},\"date\":{},\"day\":{},\"synthetic\":{},\"pinned\":{},\"hidden\":{},\"category\":{},\"color\":{},\"url\":{},\"path\":{}}}",
+                json_string(fields.title),
+                fields.start.is_none() && !fields.deadline,
+                fields.deadline,
+                json_option(fields.overdue_days),
+                json_string(&fields.when),
+                json_option(fields.start.as_deref().map(json_string)),
+                json_option(fields.end.as_deref().map(json_string)),
+                fields.ongoing,
+                fields.ended,
+                json_option(fields.duration_minutes),
+                json_option(fields.minutes_until),
+                json_option(fields.minutes_until_leave),
+                json_option(fields.progress),
+                json_option(fields.occurrence.map(|(index, _)| index)),
+                json_option(fields.occurrence.map(|(_, total)| total)),
+                json_option(
+                    fields
+                        .numbered
+                        .map(|(label, n)| json_string(&format!("{} {}", label, n)))
+                ),
+                json_option(fields.days_remaining),
+                json_string(&fields.date.to_string()),
+                json_option(fields.day.as_deref().map(json_string)),
+                fields.synthetic,
+                fields.pinned,
+                fields.hidden,
+                json_option(fields.category.map(json_string)),
+                json_option(fields.color.map(json_string)),
+                json_option(fields.url.map(json_string)),
+                if fields.synthetic {
+                    "null".into()
+                } else {
+                    json_string(&fields.path.to_string_lossy())
+                },
+            )
+}
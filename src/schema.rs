@@ -0,0 +1,479 @@
+use crate::{error::Error, event::Event, parse::parse_time, render::json_string};
+use chrono::{NaiveDate, NaiveTime, Weekday};
+use std::sync::Arc;
+
+/// The version of the event format written by `to_json`, bumped whenever it changes in a way
+/// which older readers wouldn't understand
+pub const VERSION: i64 = 3;
+
+/// The oldest version `from_json` still reads. Version 1 had no monthly recurrences.
+pub const OLDEST_VERSION: i64 = 1;
+
+/// The JSON Schema of the format written by `to_json`, printed by the `schema` subcommand
+pub const SCHEMA: &str = r##"{
+  "$schema": "https://json-schema.org/draft/2020-12/schema",
+  "title": "Event",
+  "description": "An event read by obsidian-calendar-summary (format version 3)",
+  "type": "object",
+  "required": ["version", "type", "title"],
+  "properties": {
+    "version": { "const": 3 },
+    "type": { "enum": ["once", "recurring", "all_day", "deadline"] },
+    "title": { "type": "string" }
+  },
+  "oneOf": [
+    {
+      "properties": {
+        "type": { "const": "once" },
+        "date": { "type": "string", "format": "date" },
+        "start": { "$ref": "#/$defs/time" },
+        "end": { "$ref": "#/$defs/time" },
+        "travel_minutes": { "$ref": "#/$defs/travel_minutes" },
+        "actual_end": {
+          "oneOf": [{ "$ref": "#/$defs/time" }, { "type": "null" }],
+          "description": "when the event is really going to end, if it's running over"
+        }
+      },
+      "required": ["date", "start", "end"]
+    },
+    {
+      "properties": {
+        "type": { "const": "recurring" },
+        "start": { "$ref": "#/$defs/time" },
+        "end": { "$ref": "#/$defs/time" },
+        "start_recur": { "type": ["string", "null"], "format": "date" },
+        "end_recur": { "type": ["string", "null"], "format": "date" },
+        "days_of_week": {
+          "type": "array",
+          "items": { "enum": ["mon", "tue", "wed", "thu", "fri", "sat", "sun"] },
+          "minItems": 1
+        },
+        "skip_holidays": { "type": "boolean" },
+        "travel_minutes": { "$ref": "#/$defs/travel_minutes" },
+        "week_of_month": {
+          "type": ["integer", "null"],
+          "minimum": -5,
+          "maximum": 5,
+          "not": { "const": 0 },
+          "description": "which of the month's days_of_week it's on, e.g. 1 for the first or -1 for the last, or null for every week"
+        }
+      },
+      "required": ["start", "end", "start_recur", "end_recur", "days_of_week", "skip_holidays", "week_of_month"]
+    },
+    {
+      "properties": {
+        "type": { "const": "all_day" },
+        "date": { "type": "string", "format": "date" },
+        "end_date": {
+          "type": "string",
+          "format": "date",
+          "description": "the day after the last day of the event"
+        },
+        "suppress_recurring": {
+          "type": "boolean",
+          "description": "a vacation, during which recurring events are hidden"
+        }
+      },
+      "required": ["date", "end_date"]
+    },
+    {
+      "properties": {
+        "type": { "const": "deadline" },
+        "date": { "type": "string", "format": "date", "description": "the day it's due" },
+        "completed": { "type": "boolean", "description": "done, so it's no longer shown" }
+      },
+      "required": ["date"]
+    }
+  ],
+  "$defs": {
+    "time": { "type": "string", "pattern": "^[0-9]{2}:[0-9]{2}:[0-9]{2}$" },
+    "travel_minutes": {
+      "type": ["integer", "null"],
+      "minimum": 0,
+      "description": "how many minutes before the event begins to leave for it"
+    }
+  }
+}
+"##;
+
+const WEEKDAYS: [(&str, Weekday); 7] = [
+    ("mon", Weekday::Mon),
+    ("tue", Weekday::Tue),
+    ("wed", Weekday::Wed),
+    ("thu", Weekday::Thu),
+    ("fri", Weekday::Fri),
+    ("sat", Weekday::Sat),
+    ("sun", Weekday::Sun),
+];
+
+/// Writes the event as a JSON object in the format described by `SCHEMA`
+pub fn to_json(event: &Event) -> String {
+    let date = |date: &NaiveDate| json_string(&date.to_string());
+    let time = |time: &NaiveTime| json_string(&time.format("%H:%M:%S").to_string());
+    let travel =
+        |minutes: &Option<u32>| minutes.map_or("null".into(), |minutes| minutes.to_string());
+    let fields = match event {
+        Event::Once {
+            title,
+            begin,
+            end,
+            day,
+            travel_minutes,
+            actual_end,
+        } => format!(
+            "\"type\":\"once\",\"title\":{},\"date\":{},\"start\":{},\"end\":{},\"travel_minutes\":{},\"actual_end\":{}",
+            json_string(title),
+            date(day),
+            time(begin),
+            time(end),
+            travel(travel_minutes),
+            actual_end.as_ref().map_or("null".into(), time)
+        ),
+        Event::Recurring {
+            title,
+            begin,
+            end,
+            begin_recur,
+            end_recur,
+            recur_days,
+            skip_holidays,
+            travel_minutes,
+            week_of_month,
+        } => format!(
+            "\"type\":\"recurring\",\"title\":{},\"start\":{},\"end\":{},\"start_recur\":{},\"end_recur\":{},\"days_of_week\":[{}],\"skip_holidays\":{},\"travel_minutes\":{},\"week_of_month\":{}",
+            json_string(title),
+            time(begin),
+            time(end),
+            begin_recur.as_ref().map_or("null".into(), date),
+            end_recur.as_ref().map_or("null".into(), date),
+            recur_days
+                .iter()
+                .filter_map(|day| WEEKDAYS.iter().find(|(_, weekday)| weekday == day))
+                .map(|(name, _)| format!("\"{}\"", name))
+                .collect::<Vec<String>>()
+                .join(","),
+            skip_holidays,
+            travel(travel_minutes),
+            week_of_month.map_or("null".into(), |n| n.to_string())
+        ),
+        Event::AllDay {
+            title,
+            begin_date,
+            end_date,
+            suppress_recurring,
+        } => format!(
+            "\"type\":\"all_day\",\"title\":{},\"date\":{},\"end_date\":{},\"suppress_recurring\":{}",
+            json_string(title),
+            date(begin_date),
+            date(end_date),
+            suppress_recurring
+        ),
+        Event::Deadline {
+            title,
+            due,
+            completed,
+        } => format!(
+            "\"type\":\"deadline\",\"title\":{},\"date\":{},\"completed\":{}",
+            json_string(title),
+            date(due),
+            completed
+        ),
+    };
+    format!("{{\"version\":{},{}}}", VERSION, fields)
+}
+
+/// Reads an event written by `to_json`
+pub fn from_json(s: &str) -> Result<Event, Error> {
+    let Json::Object(fields) = parse_json(s)? else {
+        return Err(Error::Json("An event must be a JSON object".into()));
+    };
+    let get = |key: &str| {
+        fields
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value)
+            .ok_or(Error::Json(format!("Has no '{}'", key)))
+    };
+    let string = |key: &str| match get(key)? {
+        Json::String(s) => Ok(s.as_str()),
+        _ => Err(Error::Json(format!("'{}' is not a string", key))),
+    };
+    let optional_date = |key: &str| match get(key)? {
+        Json::Null => Ok(None),
+        Json::String(s) => s
+            .parse()
+            .map(Some)
+            .map_err(|e| Error::Json(format!("'{}': {}", key, e))),
+        _ => Err(Error::Json(format!("'{}' is not a string or null", key))),
+    };
+    let date = |key: &str| -> Result<NaiveDate, Error> {
+        string(key)?
+            .parse()
+            .map_err(|e| Error::Json(format!("'{}': {}", key, e)))
+    };
+    let time = |key: &str| -> Result<NaiveTime, Error> {
+        parse_time(string(key)?).map_err(|e| Error::Json(format!("'{}': {}", key, e)))
+    };
+    // added without a version bump, so it can be missing
+    let travel_minutes = match get("travel_minutes") {
+        Err(_) | Ok(Json::Null) => None,
+        Ok(Json::Number(minutes))
+            if minutes.fract() == 0.0 && (0.0..=u32::MAX as f64).contains(minutes) =>
+        {
+            Some(*minutes as u32)
+        }
+        Ok(_) => {
+            return Err(Error::Json(
+                "'travel_minutes' is not a number of minutes or null".into(),
+            ))
+        }
+    };
+
+    let version = match get("version")? {
+        Json::Number(version)
+            if version.fract() == 0.0
+                && (OLDEST_VERSION as f64..=VERSION as f64).contains(version) =>
+        {
+            *version as i64
+        }
+        _ => {
+            return Err(Error::Json(format!(
+                "Unsupported version (expected {} to {})",
+                OLDEST_VERSION, VERSION
+            )))
+        }
+    };
+    let title: Arc<str> = string("title")?.into();
+    match string("type")? {
+        "once" => Ok(Event::Once {
+            title,
+            begin: time("start")?,
+            end: time("end")?,
+            day: date("date")?,
+            travel_minutes,
+            // added without a version bump too
+            actual_end: match get("actual_end") {
+                Err(_) | Ok(Json::Null) => None,
+                Ok(_) => Some(time("actual_end")?),
+            },
+        }),
+        "recurring" => Ok(Event::Recurring {
+            title,
+            begin: time("start")?,
+            end: time("end")?,
+            begin_recur: optional_date("start_recur")?,
+            end_recur: optional_date("end_recur")?,
+            recur_days: match get("days_of_week")? {
+                Json::Array(days) => days
+                    .iter()
+                    .map(|day| {
+                        WEEKDAYS
+                            .iter()
+                            .find(|(name, _)| matches!(day, Json::String(s) if s == name))
+                            .map(|(_, weekday)| *weekday)
+                            .ok_or(Error::Json(format!("Unknown weekday {:?}", day)))
+                    })
+                    .collect::<Result<_, _>>()?,
+                _ => return Err(Error::Json("'days_of_week' is not a list".into())),
+            },
+            skip_holidays: match get("skip_holidays")? {
+                Json::Bool(skip_holidays) => *skip_holidays,
+                _ => return Err(Error::Json("'skip_holidays' is not a boolean".into())),
+            },
+            travel_minutes,
+            week_of_month: match get("week_of_month") {
+                Err(_) if version < 2 => None,
+                Ok(Json::Null) => None,
+                Ok(Json::Number(n))
+                    if n.fract() == 0.0 && *n != 0.0 && (-5.0..=5.0).contains(n) =>
+                {
+                    Some(*n as i8)
+                }
+                Err(e) => return Err(e),
+                Ok(_) => {
+                    return Err(Error::Json(
+                        "'week_of_month' is not -5 to 5 (other than 0) or null".into(),
+                    ))
+                }
+            },
+        }),
+        "all_day" => Ok(Event::AllDay {
+            title,
+            begin_date: date("date")?,
+            end_date: date("end_date")?,
+            // added without a version bump, so it can be missing
+            suppress_recurring: match get("suppress_recurring") {
+                Err(_) => false,
+                Ok(Json::Bool(suppress_recurring)) => *suppress_recurring,
+                Ok(_) => return Err(Error::Json("'suppress_recurring' is not a boolean".into())),
+            },
+        }),
+        "deadline" if version >= 3 => Ok(Event::Deadline {
+            title,
+            due: date("date")?,
+            completed: match get("completed") {
+                Err(_) => false,
+                Ok(Json::Bool(completed)) => *completed,
+                Ok(_) => return Err(Error::Json("'completed' is not a boolean".into())),
+            },
+        }),
+        other => Err(Error::Json(format!("Unknown event type '{}'", other))),
+    }
+}
+
+/// Parses a whole JSON document, e.g. the snooze file
+pub(crate) fn parse_json(s: &str) -> Result<Json, Error> {
+    let mut parser = Parser { s, pos: 0 };
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    if parser.pos != s.len() {
+        return Err(parser.error("Trailing characters"));
+    }
+    Ok(value)
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+/// Just enough of a JSON parser to read events and the snooze file back in
+struct Parser<'a> {
+    s: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> Error {
+        Error::Json(format!("{} at byte {}", message, self.pos))
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.s[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.s[self.pos..].chars().next()
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), Error> {
+        self.skip_whitespace();
+        if self.peek() != Some(expected) {
+            return Err(self.error(&format!("Expected '{}'", expected)));
+        }
+        self.pos += expected.len_utf8();
+        Ok(())
+    }
+
+    fn value(&mut self) -> Result<Json, Error> {
+        self.skip_whitespace();
+        let rest = &self.s[self.pos..];
+        for (word, value) in [
+            ("null", Json::Null),
+            ("true", Json::Bool(true)),
+            ("false", Json::Bool(false)),
+        ] {
+            if rest.starts_with(word) {
+                self.pos += word.len();
+                return Ok(value);
+            }
+        }
+        match self.peek() {
+            Some('"') => Ok(Json::String(self.string()?)),
+            Some('[') => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                self.skip_whitespace();
+                if self.peek() == Some(']') {
+                    self.pos += 1;
+                    return Ok(Json::Array(items));
+                }
+                loop {
+                    items.push(self.value()?);
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some(',') => self.pos += 1,
+                        Some(']') => {
+                            self.pos += 1;
+                            return Ok(Json::Array(items));
+                        }
+                        _ => return Err(self.error("Expected ',' or ']'")),
+                    }
+                }
+            }
+            Some('{') => {
+                self.pos += 1;
+                let mut fields = Vec::new();
+                self.skip_whitespace();
+                if self.peek() == Some('}') {
+                    self.pos += 1;
+                    return Ok(Json::Object(fields));
+                }
+                loop {
+                    self.skip_whitespace();
+                    let key = self.string()?;
+                    self.expect(':')?;
+                    fields.push((key, self.value()?));
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some(',') => self.pos += 1,
+                        Some('}') => {
+                            self.pos += 1;
+                            return Ok(Json::Object(fields));
+                        }
+                        _ => return Err(self.error("Expected ',' or '}'")),
+                    }
+                }
+            }
+            _ => {
+                let len = rest
+                    .find(|c: char| !(c.is_ascii_digit() || "+-.eE".contains(c)))
+                    .unwrap_or(rest.len());
+                let number = rest[..len]
+                    .parse()
+                    .map_err(|_| self.error("Expected a value"))?;
+                self.pos += len;
+                Ok(Json::Number(number))
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, Error> {
+        self.expect('"')?;
+        let mut out = String::new();
+        let mut chars = self.s[self.pos..].char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.pos += i + 1;
+                    return Ok(out);
+                }
+                '\\' => match chars.next().map(|(_, c)| c) {
+                    Some('n') => out.push('\n'),
+                    Some('t') => out.push('\t'),
+                    Some('r') => out.push('\r'),
+                    Some('b') => out.push('\u{8}'),
+                    Some('f') => out.push('\u{c}'),
+                    Some('u') => {
+                        let hex: String = chars.by_ref().take(4).map(|(_, c)| c).collect();
+                        let c = u32::from_str_radix(&hex, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .unwrap_or(char::REPLACEMENT_CHARACTER);
+                        out.push(c);
+                    }
+                    Some(c) => out.push(c),
+                    None => break,
+                },
+                c => out.push(c),
+            }
+        }
+        Err(self.error("Unterminated string"))
+    }
+}
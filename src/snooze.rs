@@ -0,0 +1,181 @@
+use crate::{
+    error::Error,
+    event::SourcedEvent,
+    render::json_string,
+    schema::{parse_json, Json},
+};
+use chrono::NaiveDate;
+use std::path::{Path, PathBuf};
+
+/// Which events a snooze hides
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnoozeTarget {
+    /// every event with exactly this title
+    Title(String),
+    /// every event in the note at this absolute path
+    Path(PathBuf),
+}
+
+impl SnoozeTarget {
+    /// What was given to `snooze`: the note at that path if there is one, otherwise a title
+    pub fn from_arg(arg: &str) -> Self {
+        match std::fs::canonicalize(arg) {
+            Ok(path) if path.is_file() => Self::Path(path),
+            _ => Self::Title(arg.into()),
+        }
+    }
+}
+
+impl std::fmt::Display for SnoozeTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Title(title) => write!(f, "{}", title),
+            Self::Path(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// An event hidden for the rest of `date`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snooze {
+    pub target: SnoozeTarget,
+    pub date: NaiveDate,
+}
+
+impl Snooze {
+    fn hides(&self, sourced: &SourcedEvent, date: NaiveDate) -> bool {
+        self.date == date
+            && match &self.target {
+                SnoozeTarget::Title(title) => sourced.event.title() == title,
+                // the note may have been given relative to somewhere else
+                SnoozeTarget::Path(path) => {
+                    std::fs::canonicalize(&sourced.path).is_ok_and(|note| note == *path)
+                }
+            }
+    }
+}
+
+/// The snoozed events, kept as a JSON array like
+/// `[{"date":"2024-05-08","title":"Standup"},{"date":"2024-05-08","path":"/notes/Gym.md"}]`
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Snoozed {
+    pub snoozes: Vec<Snooze>,
+}
+
+impl Snoozed {
+    pub fn parse(s: &str) -> Result<Self, Error> {
+        let Json::Array(items) = parse_json(s)? else {
+            return Err(Error::Json("The snooze file must be a JSON array".into()));
+        };
+        let snoozes = items
+            .iter()
+            .map(|item| {
+                let Json::Object(fields) = item else {
+                    return Err(Error::Json("A snooze must be a JSON object".into()));
+                };
+                let string = |key: &str| {
+                    fields.iter().find_map(|(name, value)| match value {
+                        Json::String(s) if name == key => Some(s.as_str()),
+                        _ => None,
+                    })
+                };
+                let date = string("date")
+                    .ok_or(Error::Json("A snooze has no 'date'".into()))?
+                    .parse()
+                    .map_err(|e| Error::Json(format!("'date': {}", e)))?;
+                let target = match (string("title"), string("path")) {
+                    (Some(title), None) => SnoozeTarget::Title(title.into()),
+                    (None, Some(path)) => SnoozeTarget::Path(path.into()),
+                    _ => {
+                        return Err(Error::Json(
+                            "A snooze must have either a 'title' or a 'path'".into(),
+                        ))
+                    }
+                };
+                Ok(Snooze { target, date })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { snoozes })
+    }
+
+    /// The snoozes as JSON, one to a line so that the file is easy to read and edit
+    pub fn to_json(&self) -> String {
+        let items: Vec<String> = self
+            .snoozes
+            .iter()
+            .map(|snooze| {
+                let (key, value) = match &snooze.target {
+                    SnoozeTarget::Title(title) => ("title", json_string(title)),
+                    SnoozeTarget::Path(path) => ("path", json_string(&path.to_string_lossy())),
+                };
+                format!("  {{\"date\":\"{}\",\"{}\":{}}}", snooze.date, key, value)
+            })
+            .collect();
+        if items.is_empty() {
+            return "[]\n".into();
+        }
+        format!("[\n{}\n]\n", items.join(",\n"))
+    }
+
+    /// Reads the snooze file, which is empty if it doesn't exist yet
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => Self::parse(&contents).map_err(|e| {
+                Error::Json(format!(
+                    "Cannot read snooze file '{}': {}",
+                    path.display(),
+                    e
+                ))
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(source) => Err(Error::Io {
+                path: path.to_path_buf(),
+                source,
+            }),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        path.parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(path, self.to_json()))
+            .map_err(|source| Error::Io {
+                path: path.to_path_buf(),
+                source,
+            })
+    }
+
+    /// Hides the target's events for the rest of `today`, forgetting the snoozes from before then
+    pub fn snooze(&mut self, target: SnoozeTarget, today: NaiveDate) {
+        self.expire(today);
+        let snooze = Snooze {
+            target,
+            date: today,
+        };
+        if !self.snoozes.contains(&snooze) {
+            self.snoozes.push(snooze);
+        }
+    }
+
+    /// Forgets the snoozes from before `today`, which don't hide anything any more
+    pub fn expire(&mut self, today: NaiveDate) {
+        self.snoozes.retain(|snooze| snooze.date >= today);
+    }
+
+    /// Whether the event is snoozed on `date`
+    pub fn hides(&self, sourced: &SourcedEvent, date: NaiveDate) -> bool {
+        self.snoozes
+            .iter()
+            .any(|snooze| snooze.hides(sourced, date))
+    }
+}
+
+/// Where the snoozed events are kept: `$XDG_STATE_HOME/obsidian-calendar-summary/snoozed.json`
+pub fn state_path() -> Option<PathBuf> {
+    let dir = std::env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("state"))
+        })?;
+    Some(dir.join("obsidian-calendar-summary").join("snoozed.json"))
+}
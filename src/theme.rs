@@ -0,0 +1,209 @@
+use crate::{
+    config::{Config, ConfigValue},
+    error::Error,
+    render::Role,
+};
+use std::str::FromStr;
+
+/// Which background the coloured formats pick their colours for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Theme {
+    Dark,
+    Light,
+    /// light or dark depending on the terminal's background, from `COLORFGBG`
+    Auto,
+}
+
+impl FromStr for Theme {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dark" => Ok(Self::Dark),
+            "light" => Ok(Self::Light),
+            "auto" => Ok(Self::Auto),
+            _ => Err(Error::Usage(format!(
+                "Unknown theme '{}' (expected dark, light or auto)",
+                s
+            ))),
+        }
+    }
+}
+
+/// Whether a `COLORFGBG` value like `15;0` (white on black) describes a light background. The
+/// background is the last number, and like vim, 7 and 9 to 15 count as light. Values which can't
+/// be read are `None`.
+pub fn light_background(colorfgbg: &str) -> Option<bool> {
+    let background: u8 = colorfgbg.rsplit(';').next()?.trim().parse().ok()?;
+    Some(background == 7 || (9..=15).contains(&background))
+}
+
+impl Theme {
+    /// `Auto` made into `Dark` or `Light` using `colorfgbg`, the value of `COLORFGBG`, assuming
+    /// a dark background when there's nothing to go on
+    pub fn resolve(self, colorfgbg: Option<&str>) -> Theme {
+        match self {
+            Self::Auto => match colorfgbg.and_then(light_background) {
+                Some(true) => Self::Light,
+                _ => Self::Dark,
+            },
+            theme => theme,
+        }
+    }
+}
+
+/// The basic terminal colours, with the hex colours used for them by the formats which need one
+const NAMED: [(&str, &str); 8] = [
+    ("black", "#000000"),
+    ("red", "#ff0000"),
+    ("green", "#00ff00"),
+    ("yellow", "#ffff00"),
+    ("blue", "#0000ff"),
+    ("magenta", "#ff00ff"),
+    ("cyan", "#00ffff"),
+    ("white", "#ffffff"),
+];
+
+/// A colour an event can be highlighted with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Colour {
+    /// the normal text colour, dimmed where that's possible and grey where it isn't
+    Dim,
+    /// one of the basic terminal colours, e.g. `green`, which follows the terminal's own palette
+    /// where it can
+    Named(&'static str),
+    /// e.g. `#2e8fb8`
+    Hex(String),
+}
+
+impl FromStr for Colour {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "dim" {
+            return Ok(Self::Dim);
+        }
+        if let Some((name, _)) = NAMED.iter().find(|(name, _)| *name == s) {
+            return Ok(Self::Named(name));
+        }
+        match s.strip_prefix('#') {
+            Some(digits) if digits.len() == 6 && digits.chars().all(|c| c.is_ascii_hexdigit()) => {
+                Ok(Self::Hex(s.to_ascii_lowercase()))
+            }
+            _ => Err(()),
+        }
+    }
+}
+
+impl Colour {
+    /// A tmux style, e.g. `fg=green`
+    pub fn tmux(&self) -> String {
+        match self {
+            Self::Dim => "dim".into(),
+            Self::Named(name) => format!("fg={}", name),
+            Self::Hex(hex) => format!("fg={}", hex),
+        }
+    }
+
+    /// A hex colour, e.g. `#00ff00`, for polybar and i3blocks
+    pub fn hex(&self) -> &str {
+        match self {
+            Self::Dim => "#808080",
+            Self::Named(name) => NAMED
+                .iter()
+                .find(|(named, _)| named == name)
+                .map_or("", |(_, hex)| hex),
+            Self::Hex(hex) => hex,
+        }
+    }
+}
+
+/// The colour for each role, with `None` for roles which aren't highlighted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Palette {
+    pub ended: Option<Colour>,
+    pub overdue: Option<Colour>,
+    pub all_day: Option<Colour>,
+    pub ongoing: Option<Colour>,
+    pub imminent: Option<Colour>,
+    pub later: Option<Colour>,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Palette {
+    pub fn dark() -> Self {
+        Self {
+            ended: Some(Colour::Dim),
+            overdue: Some(Colour::Named("red")),
+            all_day: Some(Colour::Named("cyan")),
+            ongoing: Some(Colour::Named("green")),
+            imminent: Some(Colour::Named("yellow")),
+            later: None,
+        }
+    }
+
+    /// Darker colours which can still be read on a white background, where e.g. yellow can't
+    pub fn light() -> Self {
+        Self {
+            ended: Some(Colour::Hex("#6c6c6c".into())),
+            overdue: Some(Colour::Hex("#af0000".into())),
+            all_day: Some(Colour::Hex("#005f87".into())),
+            ongoing: Some(Colour::Hex("#008700".into())),
+            imminent: Some(Colour::Hex("#af5f00".into())),
+            later: None,
+        }
+    }
+
+    /// The palette for `theme`, once it's been resolved
+    pub fn for_theme(theme: Theme) -> Self {
+        match theme {
+            Theme::Light => Self::light(),
+            Theme::Dark | Theme::Auto => Self::dark(),
+        }
+    }
+
+    /// The colour an event with `role` is highlighted with, if any. Every coloured format goes
+    /// through this, so that they all agree.
+    pub fn colour(&self, role: Role) -> Option<&Colour> {
+        match role {
+            Role::Ended => self.ended.as_ref(),
+            Role::Overdue => self.overdue.as_ref(),
+            Role::AllDay => self.all_day.as_ref(),
+            Role::Ongoing => self.ongoing.as_ref(),
+            Role::Imminent => self.imminent.as_ref(),
+            Role::Later => self.later.as_ref(),
+        }
+    }
+
+    /// Changes the roles set in the `[color]` section of the config file, e.g.
+    /// `color.ongoing = "green"`, where `none` leaves the role uncoloured
+    pub fn with_overrides(mut self, config: &Config) -> Result<Self, Error> {
+        for (key, value) in config.section("color") {
+            let error = || {
+                Error::Config(format!(
+                    "Config key 'color.{}' should be a colour like \"green\" or \"#2e8fb8\", \"dim\" or \"none\"",
+                    key
+                ))
+            };
+            let colour = match value {
+                ConfigValue::String(s) if s == "none" => None,
+                ConfigValue::String(s) => Some(s.parse().map_err(|_| error())?),
+                _ => return Err(error()),
+            };
+            let role = match key {
+                "ended" => &mut self.ended,
+                "overdue" => &mut self.overdue,
+                "all_day" => &mut self.all_day,
+                "ongoing" => &mut self.ongoing,
+                "imminent" => &mut self.imminent,
+                "later" => &mut self.later,
+                _ => return Err(Error::Config(format!("Unknown config key 'color.{}'", key))),
+            };
+            *role = colour;
+        }
+        Ok(self)
+    }
+}
@@ -0,0 +1,117 @@
+use crate::{
+    event::{Event, SourcedEvent},
+    parse::parse_time,
+};
+use chrono::{NaiveTime, Timelike};
+
+const LETTERS: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// Maps times of day onto the columns of the timeline. Times are in seconds since midnight so
+/// that the end of the day can be 24:00.
+#[derive(Debug, Clone, Copy)]
+pub struct Scale {
+    pub day_start: i64,
+    pub day_end: i64,
+    pub width: usize,
+}
+
+impl Scale {
+    /// The column a time falls in, clamped to the timeline
+    pub fn column(&self, seconds: i64) -> usize {
+        let span = (self.day_end - self.day_start).max(1);
+        let offset = (seconds - self.day_start).clamp(0, span);
+        ((offset * self.width as i64) / span) as usize
+    }
+
+    /// The columns covered by an event, or None if it is entirely outside the timeline. Events
+    /// partly outside are clipped, and every event covers at least one column so that it's
+    /// visible.
+    pub fn columns(&self, begin: i64, end: i64) -> Option<std::ops::Range<usize>> {
+        if end <= self.day_start || begin >= self.day_end || self.width == 0 {
+            return None;
+        }
+        let first = self.column(begin).min(self.width - 1);
+        let last = self.column(end).max(first + 1);
+        Some(first..last)
+    }
+}
+
+/// Parses a time of day like "09:30" into seconds since midnight, also accepting "24:00"
+pub fn parse_clock(s: &str) -> Result<i64, chrono::ParseError> {
+    if s == "24:00" {
+        return Ok(24 * 60 * 60);
+    }
+    let time = parse_time(s)?;
+    Ok(time.num_seconds_from_midnight() as i64)
+}
+
+/// Draws the day as a bar with a block of letters for each timed event, stacking overlapping
+/// events onto extra rows, with a caret under the current time and a legend of which letter is
+/// which event underneath
+pub fn timeline(events: &[&SourcedEvent], scale: Scale, now: NaiveTime) -> String {
+    let mut rows: Vec<Vec<char>> = Vec::new();
+    let mut legend = Vec::new();
+    let timed = events.iter().filter_map(|sourced| match &sourced.event {
+        Event::Once { begin, end, .. } | Event::Recurring { begin, end, .. } => {
+            Some((begin, end, sourced.event.title()))
+        }
+        Event::AllDay { .. } | Event::Deadline { .. } => None,
+    });
+    for ((begin, end, title), letter) in timed.zip(LETTERS.chars()) {
+        // an event which runs past midnight is drawn up to the end of the day
+        let end_seconds = if end < begin {
+            24 * 60 * 60
+        } else {
+            end.num_seconds_from_midnight() as i64
+        };
+        let Some(columns) = scale.columns(begin.num_seconds_from_midnight() as i64, end_seconds)
+        else {
+            continue;
+        };
+        let row = match rows
+            .iter_mut()
+            .find(|row| row[columns.clone()].iter().all(|c| *c == '.'))
+        {
+            Some(row) => row,
+            None => {
+                rows.push(vec!['.'; scale.width]);
+                rows.last_mut().unwrap() // just pushed
+            }
+        };
+        row[columns].fill(letter);
+        legend.push(format!(
+            "{}  {:02}:{:02} - {:02}:{:02}  {}",
+            letter,
+            begin.hour(),
+            begin.minute(),
+            end.hour(),
+            end.minute(),
+            title
+        ));
+    }
+    if rows.is_empty() {
+        rows.push(vec!['.'; scale.width]);
+    }
+
+    let clock = |seconds: i64| format!("{:02}:{:02}", seconds / 3600, seconds / 60 % 60);
+    let mut out = format!(
+        "{}{:>width$}\n",
+        clock(scale.day_start),
+        clock(scale.day_end),
+        width = scale.width.saturating_sub(5)
+    );
+    for row in rows {
+        out.extend(row);
+        out.push('\n');
+    }
+    let now = now.num_seconds_from_midnight() as i64;
+    if now >= scale.day_start && now <= scale.day_end {
+        let column = scale.column(now).min(scale.width.saturating_sub(1));
+        out += &format!("{}^\n", " ".repeat(column));
+    }
+    for line in legend {
+        out += &line;
+        out.push('\n');
+    }
+    out
+}
@@ -0,0 +1,144 @@
+use crate::{
+    calendar::Calendar,
+    cli::{Options, Weekends},
+    error::Error,
+    event::{Event, SourcedEvent},
+    lang::Lang,
+};
+use chrono::{Datelike, NaiveDate, Timelike, Weekday};
+
+/// Parses an ISO week like "2024-W23" into the Monday it starts on
+pub fn parse_iso_week(s: &str) -> Result<NaiveDate, Error> {
+    let invalid = || Error::Usage(format!("Invalid week '{}' (expected e.g. 2024-W23)", s));
+    let (year, week) = s.split_once("-W").ok_or_else(invalid)?;
+    let year: i32 = year.parse().map_err(|_| invalid())?;
+    let week: u32 = week.parse().map_err(|_| invalid())?;
+    // only some years have a week 53, which from_isoywd_opt checks for us
+    NaiveDate::from_isoywd_opt(year, week, Weekday::Mon)
+        .ok_or(Error::Usage(format!("{} has no week {}", year, week)))
+}
+
+/// The first day of the week to show for `--week-of`. ISO weeks start on Monday, so when weeks
+/// start on Sunday this is the Sunday just before the ISO week.
+pub fn first_day_of_iso_week(monday: NaiveDate, week_start: Weekday) -> NaiveDate {
+    week_containing(monday, week_start)
+}
+
+/// The first day of the week containing `date`, for weeks starting on `week_start`
+pub fn week_containing(date: NaiveDate, week_start: Weekday) -> NaiveDate {
+    let days_since_start =
+        (date.weekday().num_days_from_monday() + 7 - week_start.num_days_from_monday()) % 7;
+    date - chrono::Days::new(days_since_start as u64)
+}
+
+/// The date with its ISO week, e.g. "Fri 03 May 2024 · W18"
+pub fn day_heading(date: NaiveDate, lang: &Lang) -> String {
+    // near new year the ISO week can belong to the next or previous year, so say which
+    let iso_week = date.iso_week();
+    let week = if iso_week.year() == date.year() {
+        format!("W{:02}", iso_week.week())
+    } else {
+        format!("{}-W{:02}", iso_week.year(), iso_week.week())
+    };
+    format!("{} {} · {}", lang.weekday_date(date), date.year(), week)
+}
+
+fn event_line(event: &Event, lang: &Lang) -> String {
+    match event {
+        Event::Once { begin, end, .. } | Event::Recurring { begin, end, .. } => format!(
+            "{:02}:{:02} - {:02}:{:02} | {}",
+            begin.hour(),
+            begin.minute(),
+            end.hour(),
+            end.minute(),
+            event.title()
+        ),
+        Event::AllDay { title, .. } => format!("{:<13} | {}", lang.all_day, title),
+        Event::Deadline { title, .. } => format!("{:<13} | {}", lang.due, title),
+    }
+}
+
+/// The days, in the order they're shown, with runs of consecutive days joined, e.g.
+/// "Mon–Tue, Thu" for Monday, Tuesday and Thursday
+pub fn compress_days(days: &[Weekday], lang: &Lang) -> String {
+    let mut runs: Vec<(Weekday, Weekday)> = Vec::new();
+    for day in days {
+        match runs.last_mut() {
+            Some((_, last)) if last.succ() == *day => *last = *day,
+            _ => runs.push((*day, *day)),
+        }
+    }
+    runs.iter()
+        .map(|(first, last)| {
+            if first == last {
+                lang.weekday(*first).to_string()
+            } else {
+                format!("{}–{}", lang.weekday(*first), lang.weekday(*last))
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Lists the events on each of the 7 days starting at `first_day`, under a heading for each day
+/// with its ISO week number. Weekends are left out as `--skip-weekends`/`--hide-weekends` say.
+/// With `--collapse-recurring`, recurring events on more than one of the days shown are listed
+/// once above the days instead, with the days they're on.
+pub fn week(calendar: &Calendar, options: &Options, first_day: NaiveDate) -> String {
+    let days: Vec<(NaiveDate, Vec<&SourcedEvent>)> = first_day
+        .iter_days()
+        .take(7)
+        .map(|date| (date, calendar.events_on(date, options)))
+        .filter(|(date, events)| {
+            let weekend = matches!(date.weekday(), Weekday::Sat | Weekday::Sun);
+            !matches!(
+                (weekend, options.weekends, events.is_empty()),
+                (true, Weekends::Hide, _) | (true, Weekends::SkipEmpty, true)
+            )
+        })
+        .collect();
+    let mut recurring: Vec<(&SourcedEvent, Vec<Weekday>)> = Vec::new();
+    if options.collapse_recurring {
+        for (date, events) in &days {
+            for sourced in events {
+                if !matches!(sourced.event, Event::Recurring { .. }) {
+                    continue;
+                }
+                match recurring
+                    .iter_mut()
+                    .find(|(other, _)| std::ptr::eq(*other, *sourced))
+                {
+                    Some((_, weekdays)) => weekdays.push(date.weekday()),
+                    None => recurring.push((sourced, vec![date.weekday()])),
+                }
+            }
+        }
+        recurring.retain(|(_, weekdays)| weekdays.len() > 1);
+    }
+    let mut out = String::new();
+    for (sourced, weekdays) in &recurring {
+        out += &format!(
+            "{} {}\n",
+            compress_days(weekdays, &options.lang),
+            event_line(&sourced.event, &options.lang)
+        );
+    }
+    for (date, events) in &days {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out += &day_heading(*date, &options.lang);
+        out.push('\n');
+        for sourced in events {
+            if recurring
+                .iter()
+                .any(|(collapsed, _)| std::ptr::eq(*collapsed, *sourced))
+            {
+                continue;
+            }
+            out += &event_line(&sourced.event, &options.lang);
+            out.push('\n');
+        }
+    }
+    out
+}
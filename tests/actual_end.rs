@@ -0,0 +1,120 @@
+//! Meetings running over, with an `actualEnd` later than their `endTime`
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use obsidian_calendar_summary::{
+    calendar::Calendar,
+    cli::SortBy,
+    event::{Event, SourcedEvent},
+    lang::Lang,
+    parse::{parse_cal_file_with_warnings, AllDayEnd, WeekdayLocale},
+    query::EventQuery,
+    render::Fields,
+};
+use std::path::Path;
+
+fn date() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 5, 8).unwrap()
+}
+
+fn at(time: &str) -> NaiveDateTime {
+    date().and_time(NaiveTime::parse_from_str(time, "%H:%M").unwrap())
+}
+
+/// The events in the note and its warnings
+fn parse(frontmatter: &str) -> (Vec<Event>, Vec<String>) {
+    let (events, warnings) = parse_cal_file_with_warnings(
+        &format!("---\n{}---\n", frontmatter),
+        Path::new("note.md"),
+        WeekdayLocale::English,
+        AllDayEnd::Exclusive,
+        None,
+        false,
+        true,
+    )
+    .unwrap_or_else(|e| panic!("{}", e));
+    (events, warnings.iter().map(ToString::to_string).collect())
+}
+
+fn calendar(actual_end: &str) -> Calendar {
+    let (events, _) = parse(&format!(
+        "title: Review\ndate: 2024-05-08\nstartTime: 14:00\nendTime: 15:00\nactualEnd: {}\n",
+        actual_end
+    ));
+    Calendar {
+        events: events
+            .into_iter()
+            .map(|event| SourcedEvent::new(event, "Review.md"))
+            .collect(),
+        holidays: Vec::new(),
+        problems: Vec::new(),
+    }
+}
+
+/// The countdown of each event shown at `now`, and whether it's ongoing
+fn shown(calendar: &Calendar, now: &str) -> Vec<(String, bool)> {
+    let lang = Lang::english();
+    EventQuery::today(at(now))
+        .apply(calendar)
+        .into_iter()
+        .map(|sourced| {
+            let fields = Fields::new(
+                sourced,
+                date(),
+                at(now),
+                SortBy::Start,
+                &lang,
+                false,
+                chrono::Duration::zero(),
+            );
+            (fields.countdown.unwrap(), fields.ongoing)
+        })
+        .collect()
+}
+
+#[test]
+fn running_over() {
+    let calendar = calendar("15:20");
+    assert_eq!(shown(&calendar, "14:50"), [("(Now)".to_string(), true)]);
+    assert_eq!(shown(&calendar, "15:00"), [("(Now)".to_string(), true)]);
+    assert_eq!(
+        shown(&calendar, "15:01"),
+        [("(running over, ends 15:20)".to_string(), true)]
+    );
+    assert_eq!(
+        shown(&calendar, "15:20"),
+        [("(running over, ends 15:20)".to_string(), true)]
+    );
+    assert_eq!(shown(&calendar, "15:21"), []);
+}
+
+#[test]
+fn finished_early() {
+    // an `actualEnd` before the end doesn't hide the event any sooner
+    let calendar = calendar("14:40");
+    assert_eq!(shown(&calendar, "14:50"), [("(Now)".to_string(), true)]);
+    assert_eq!(shown(&calendar, "15:01"), []);
+}
+
+#[test]
+fn past_midnight() {
+    // like `endTime`, an `actualEnd` before the start is on the next day
+    let (events, _) =
+        parse("title: Gig\ndate: 2024-05-08\nstartTime: 22:00\nendTime: 23:30\nactualEnd: 00:15\n");
+    assert_eq!(
+        events[0].running_until(date()),
+        Some(at("00:15") + chrono::Days::new(1))
+    );
+    assert!(!events[0].ended(date(), at("23:59")));
+}
+
+#[test]
+fn only_single_events() {
+    let (events, warnings) = parse(
+        "title: Standup\ntype: recurring\ndaysOfWeek: [W]\nstartTime: 09:45\nendTime: 10:15\nactualEnd: 10:30\n",
+    );
+    assert_eq!(events[0].running_until(date()), Some(at("10:15")));
+    assert_eq!(
+        warnings,
+        ["note.md: 'actualEnd': only used by single events, so it's ignored"]
+    );
+}
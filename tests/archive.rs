@@ -0,0 +1,187 @@
+//! Moving the notes of events which are over into an archive with `archive`
+
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// A vault of its own for each test, since `archive` moves the notes in it
+fn vault(name: &str) -> PathBuf {
+    let vault = std::env::temp_dir().join(format!(
+        "obsidian-calendar-summary-archive-{}-{}",
+        name,
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&vault);
+    let notes = [
+        (
+            "Calendar/Dentist.md",
+            "---\ntitle: Dentist\nallDay: false\ndate: 2023-11-02\nstartTime: 14:00\nendTime: 15:00\n---\n",
+        ),
+        (
+            "Calendar/Trips/Skiing.md",
+            "---\ntitle: Skiing\nallDay: true\ndate: 2023-12-27\nendDate: 2024-01-01\n---\n",
+        ),
+        (
+            "Calendar/Trips/New year.md",
+            "---\ntitle: New year\nallDay: true\ndate: 2023-12-31\nendDate: 2024-01-02\n---\n",
+        ),
+        (
+            "Calendar/Book club.md",
+            "---\ntitle: Book club\nallDay: false\ntype: recurring\ndaysOfWeek: [M]\nstartRecur: 2023-01-01\nendRecur: 2023-06-30\nstartTime: 19:30\nendTime: 21:30\n---\n",
+        ),
+        (
+            "Calendar/Gym.md",
+            "---\ntitle: Gym\nallDay: false\ntype: recurring\ndaysOfWeek: [T]\nstartRecur: 2023-01-01\nstartTime: 07:00\nendTime: 08:00\n---\n",
+        ),
+        (
+            "Calendar/Review.md",
+            "---\ntitle: Review\nallDay: false\ndate: 2024-01-01\nstartTime: 10:00\nendTime: 11:00\n---\n",
+        ),
+        (
+            "Calendar/Broken.md",
+            "---\ntitle: Broken\nallDay: false\ndate: 2023-11-02\nstartTime: 2pm\n---\n",
+        ),
+    ];
+    for (path, contents) in notes {
+        let path = vault.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+    vault
+}
+
+fn archive(vault: &Path, dest: &str, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_obsidian-calendar-summary"))
+        .args(["archive", "--before", "2024-01-01", "--dest", dest])
+        .args(args)
+        .args(["--infer-category-from-dir", "Calendar"])
+        .current_dir(vault)
+        .env("XDG_CONFIG_HOME", vault)
+        .output()
+        .expect("failed to run obsidian-calendar-summary");
+    assert!(output.status.success(), "exited with {}", output.status);
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn notes(dir: &Path) -> Vec<String> {
+    let mut notes = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(next) = dirs.pop() {
+        for entry in std::fs::read_dir(next).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else {
+                let relative = path.strip_prefix(dir).unwrap();
+                notes.push(relative.to_string_lossy().into_owned());
+            }
+        }
+    }
+    notes.sort();
+    notes
+}
+
+#[test]
+fn moves_past_events() {
+    let vault = vault("move");
+    let output = archive(&vault, "Archive", &[]);
+    assert!(
+        output.ends_with("Moved 3 notes, 1 refused, 3 kept\n"),
+        "{}",
+        output
+    );
+    // in the same folders as they were in
+    assert_eq!(
+        notes(&vault.join("Archive")),
+        ["Book club.md", "Dentist.md", "Trips/Skiing.md"]
+    );
+    // the open-ended recurring event, the one on the day and the one going on over it stay, and
+    // so does the one which can't be read
+    assert_eq!(
+        notes(&vault.join("Calendar")),
+        ["Broken.md", "Gym.md", "Review.md", "Trips/New year.md"]
+    );
+    // and there's nothing left to move
+    let output = archive(&vault, "Archive", &[]);
+    assert!(
+        output.ends_with("Moved 0 notes, 1 refused, 3 kept\n"),
+        "{}",
+        output
+    );
+    let _ = std::fs::remove_dir_all(&vault);
+}
+
+#[test]
+fn dry_run() {
+    let vault = vault("dry-run");
+    let output = archive(&vault, "Archive", &["--dry-run"]);
+    assert!(
+        output.contains(&format!(
+            "Would move {} -> {}\n",
+            Path::new("Calendar/Trips/Skiing.md").display(),
+            Path::new("Archive/Trips/Skiing.md").display()
+        )),
+        "{}",
+        output
+    );
+    assert!(
+        output.ends_with("Would move 3 notes, 1 refused, 3 kept\n"),
+        "{}",
+        output
+    );
+    assert!(!vault.join("Archive").exists());
+    assert_eq!(notes(&vault.join("Calendar")).len(), 7);
+    let _ = std::fs::remove_dir_all(&vault);
+}
+
+#[test]
+fn never_overwrites() {
+    let vault = vault("overwrite");
+    std::fs::create_dir_all(vault.join("Archive")).unwrap();
+    std::fs::write(vault.join("Archive/Dentist.md"), "already archived").unwrap();
+    let output = archive(&vault, "Archive", &[]);
+    assert!(
+        output.ends_with("Moved 2 notes, 2 refused, 3 kept\n"),
+        "{}",
+        output
+    );
+    assert_eq!(
+        std::fs::read_to_string(vault.join("Archive/Dentist.md")).unwrap(),
+        "already archived"
+    );
+    assert!(vault.join("Calendar/Dentist.md").exists());
+    let _ = std::fs::remove_dir_all(&vault);
+}
+
+#[test]
+fn archive_in_the_calendar() {
+    let vault = vault("inside");
+    let output = archive(&vault, "Calendar/Archive", &[]);
+    assert!(
+        output.ends_with("Moved 3 notes, 1 refused, 3 kept\n"),
+        "{}",
+        output
+    );
+    // the notes already in the archive aren't archived again
+    let output = archive(&vault, "Calendar/Archive", &[]);
+    assert!(
+        output.ends_with("Moved 0 notes, 1 refused, 3 kept\n"),
+        "{}",
+        output
+    );
+    assert_eq!(
+        notes(&vault.join("Calendar/Archive")),
+        ["Book club.md", "Dentist.md", "Trips/Skiing.md"]
+    );
+    let _ = std::fs::remove_dir_all(&vault);
+}
+
+#[test]
+fn needs_a_cutoff_and_destination() {
+    let output = Command::new(env!("CARGO_BIN_EXE_obsidian-calendar-summary"))
+        .args(["archive", "--dest", "Archive", "Calendar"])
+        .output()
+        .expect("failed to run obsidian-calendar-summary");
+    assert!(!output.status.success());
+}
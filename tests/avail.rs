@@ -0,0 +1,56 @@
+use chrono::NaiveDateTime;
+use obsidian_calendar_summary::focus::gaps_between;
+
+fn at(time: &str) -> NaiveDateTime {
+    NaiveDateTime::parse_from_str(&format!("2024-05-06 {}", time), "%Y-%m-%d %H:%M").unwrap()
+}
+
+fn gaps(busy: &[(&str, &str)]) -> Vec<(NaiveDateTime, NaiveDateTime)> {
+    let busy = busy
+        .iter()
+        .map(|(begin, end)| (at(begin), at(end)))
+        .collect();
+    gaps_between(busy, at("09:00"), at("17:00"))
+}
+
+#[test]
+fn overlapping() {
+    // in any order, and one inside another
+    assert_eq!(
+        gaps(&[
+            ("11:00", "12:00"),
+            ("10:30", "11:30"),
+            ("13:00", "15:00"),
+            ("13:30", "14:00")
+        ]),
+        [
+            (at("09:00"), at("10:30")),
+            (at("12:00"), at("13:00")),
+            (at("15:00"), at("17:00"))
+        ]
+    );
+}
+
+#[test]
+fn adjacent() {
+    // events which touch leave no empty gap between them
+    assert_eq!(
+        gaps(&[("10:00", "11:00"), ("11:00", "12:00"), ("12:00", "12:30")]),
+        [(at("09:00"), at("10:00")), (at("12:30"), at("17:00"))]
+    );
+}
+
+#[test]
+fn outside_the_day() {
+    // only the part of the day between the start and the end is free
+    assert_eq!(
+        gaps(&[("08:00", "09:30"), ("16:30", "18:00")]),
+        [(at("09:30"), at("16:30"))]
+    );
+    assert_eq!(
+        gaps(&[("07:00", "08:00"), ("18:00", "19:00")]),
+        [(at("09:00"), at("17:00"))]
+    );
+    assert_eq!(gaps(&[("08:00", "18:00")]), []);
+    assert_eq!(gaps(&[]), [(at("09:00"), at("17:00"))]);
+}
@@ -0,0 +1,26 @@
+//! The titles in `--format compact`, which has to stay plain ASCII for QR codes
+
+use obsidian_calendar_summary::render::compact_title;
+
+#[test]
+fn safe_characters() {
+    assert_eq!(
+        compact_title("1:1 with Sam (Q&A)", None),
+        "1:1 with Sam (Q&A)"
+    );
+    // `;` separates the events
+    assert_eq!(compact_title("Lunch; then gym", None), "Lunch_ then gym");
+    // one for one, however many bytes they are
+    assert_eq!(compact_title("Café ☕", None), "Caf_ _");
+    assert_eq!(compact_title("Tab\there", None), "Tab_here");
+}
+
+#[test]
+fn max_title() {
+    assert_eq!(compact_title("Conference", Some(4)), "Conf");
+    assert_eq!(compact_title("Café", Some(4)), "Caf_");
+    assert_eq!(compact_title("Standup", Some(12)), "Standup");
+    // no spaces left hanging
+    assert_eq!(compact_title("  Team lunch", Some(5)), "Team");
+    assert_eq!(compact_title("Standup", Some(0)), "");
+}
@@ -0,0 +1,88 @@
+//! Rounding the countdowns, which are rounded up to the minute unless `--seconds` is given
+
+use obsidian_calendar_summary::{
+    lang::Lang,
+    parse::parse_time,
+    render::{format_delta, format_end, format_leave, format_over, minutes_rounded_up},
+};
+
+fn seconds(seconds: i64) -> chrono::Duration {
+    chrono::Duration::try_seconds(seconds).unwrap()
+}
+
+#[test]
+fn rounded_up() {
+    let lang = Lang::english();
+    for (delta, minutes, countdown) in [
+        (0, 0, "(Now)"),
+        (1, 1, "(1 min)"),
+        (59, 1, "(1 min)"),
+        (60, 1, "(1 min)"),
+        (61, 2, "(2 mins)"),
+        (59 * 60 + 1, 60, "(1 hour)"),
+        (2 * 60 * 60 + 59, 121, "(2 hours)"),
+        // already started, which rounds towards now
+        (-59, 0, "(Now)"),
+        (-61, -1, "(Now)"),
+    ] {
+        assert_eq!(minutes_rounded_up(seconds(delta)), minutes, "{}s", delta);
+        assert_eq!(
+            format_delta(seconds(delta), &lang, false),
+            countdown,
+            "{}s",
+            delta
+        );
+    }
+    assert_eq!(format_leave(seconds(0), &lang, false), "(leave now)");
+    assert_eq!(format_leave(seconds(59), &lang, false), "(leave in 1 min)");
+    assert_eq!(format_end(seconds(61), &lang, false), "(ends in 2 mins)");
+    assert_eq!(format_over(seconds(61), &lang, false), "(over by 2 mins)");
+}
+
+#[test]
+fn with_seconds() {
+    let lang = Lang::english();
+    for (delta, countdown) in [
+        (0, "(Now)"),
+        (1, "(1 sec)"),
+        (59, "(59 secs)"),
+        (60, "(1 min)"),
+        (61, "(1 min 1 sec)"),
+        (4 * 60 + 30, "(4 mins 30 secs)"),
+        (59 * 60 + 59, "(59 mins 59 secs)"),
+        // too far off for seconds to matter
+        (60 * 60 + 30, "(1 hour)"),
+    ] {
+        assert_eq!(
+            format_delta(seconds(delta), &lang, true),
+            countdown,
+            "{}s",
+            delta
+        );
+    }
+    assert_eq!(
+        format_end(seconds(61), &lang, true),
+        "(ends in 1 min 1 sec)"
+    );
+}
+
+#[test]
+fn times() {
+    for (time, expected) in [
+        ("09:00", (9, 0, 0)),
+        ("9:05", (9, 5, 0)),
+        ("09:00:30", (9, 0, 30)),
+        ("23:59:59", (23, 59, 59)),
+    ] {
+        let (hour, minute, second) = expected;
+        assert_eq!(
+            parse_time(time).unwrap(),
+            chrono::NaiveTime::from_hms_opt(hour, minute, second).unwrap(),
+            "{}",
+            time
+        );
+    }
+    for time in ["", "9am", "24:00", "09:60", "09:00:00 pm"] {
+        assert!(parse_time(time).is_err(), "{}", time);
+    }
+}
@@ -0,0 +1,164 @@
+//! Notes with `startDate`/`endDate` as combined dates and times, like the Day Planner plugin
+//! writes, in `tests/fixtures/day_planner`, and with the start time in `date`, like some imported
+//! notes
+
+use chrono::{NaiveDate, NaiveTime};
+use obsidian_calendar_summary::{
+    error::Error,
+    event::Event,
+    parse::{parse_cal_file, AllDayEnd, WeekdayLocale},
+};
+use std::path::Path;
+
+fn parse(contents: &str) -> Result<Vec<Event>, Error> {
+    parse_cal_file(
+        contents,
+        Path::new("note.md"),
+        WeekdayLocale::English,
+        AllDayEnd::Exclusive,
+    )
+}
+
+fn fixture(name: &str) -> Event {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/day_planner")
+        .join(name);
+    let contents = std::fs::read_to_string(&path).unwrap();
+    let mut events = parse(&contents).unwrap_or_else(|e| panic!("{}", e));
+    assert_eq!(events.len(), 1);
+    events.remove(0)
+}
+
+fn once(title: &str, day: &str, begin: &str, end: &str) -> Event {
+    let time = |time| NaiveTime::parse_from_str(time, "%H:%M").unwrap();
+    Event::Once {
+        title: title.into(),
+        begin: time(begin),
+        end: time(end),
+        day: NaiveDate::parse_from_str(day, "%Y-%m-%d").unwrap(),
+        travel_minutes: None,
+        actual_end: None,
+    }
+}
+
+#[test]
+fn fixtures() {
+    assert_eq!(
+        fixture("Design review.md"),
+        once("Design review", "2024-05-03", "14:00", "15:30")
+    );
+    // quoted, with a space and seconds, and running past midnight
+    assert_eq!(
+        fixture("Release party.md"),
+        once("Release party", "2024-05-03", "21:30", "00:30")
+    );
+    // the split keys are there too, and agree
+    assert_eq!(
+        fixture("Lunch.md"),
+        once("Lunch", "2024-05-03", "12:00", "13:00")
+    );
+}
+
+#[test]
+fn disagreeing_keys() {
+    for (extra, message) in [
+        (
+            "date: 2024-05-04",
+            "note.md: 'startDate': 2024-05-03 doesn't match 'date' (2024-05-04)",
+        ),
+        (
+            "startTime: 14:30",
+            "note.md: 'startDate': 14:00:00 doesn't match 'startTime' (14:30:00)",
+        ),
+        (
+            "endTime: 15:00",
+            "note.md: 'endDate': 15:30:00 doesn't match 'endTime' (15:00:00)",
+        ),
+    ] {
+        let note = format!(
+            "---\ntitle: Review\nstartDate: 2024-05-03T14:00\nendDate: 2024-05-03T15:30\n{}\n---\n",
+            extra
+        );
+        let error = parse(&note).unwrap_err();
+        assert_eq!(error.code(), "conflict");
+        assert_eq!(error.to_string(), message);
+    }
+}
+
+#[test]
+fn bad_ends() {
+    for (end, code) in [
+        // not a date and time
+        ("2024-05-03", "bad_datetime"),
+        ("2024-05-03T25:00", "bad_datetime"),
+        // before it starts
+        ("2024-05-03T13:00", "never_happens"),
+        // too long for a timed event
+        ("2024-05-04T14:00", "yaml"),
+    ] {
+        let note = format!(
+            "---\ntitle: Review\nstartDate: 2024-05-03T14:00\nendDate: {}\n---\n",
+            end
+        );
+        let error = parse(&note).unwrap_err();
+        assert_eq!(error.code(), code, "{}", error);
+    }
+}
+
+#[test]
+fn time_in_date() {
+    for (date, begin) in [
+        ("2024-05-03T14:00", "14:00"),
+        ("2024-05-03 14:00", "14:00"),
+        ("2024-05-03T14:00:00", "14:00"),
+        ("\"2024-05-03 09:30:00\"", "09:30"),
+    ] {
+        let note = format!("---\ntitle: Review\ndate: {}\nendTime: 15:30\n---\n", date);
+        let events = parse(&note).unwrap_or_else(|e| panic!("{}: {}", date, e));
+        assert_eq!(
+            events,
+            [once("Review", "2024-05-03", begin, "15:30")],
+            "{}",
+            date
+        );
+    }
+    // a `startTime` which agrees is fine
+    let note =
+        "---\ntitle: Review\ndate: 2024-05-03T14:00\nstartTime: 14:00\nendTime: 15:30\n---\n";
+    assert_eq!(
+        parse(note).unwrap(),
+        [once("Review", "2024-05-03", "14:00", "15:30")]
+    );
+}
+
+#[test]
+fn time_in_date_problems() {
+    for (keys, code, message) in [
+        (
+            "date: 2024-05-03T14:00\nstartTime: 14:30\nendTime: 15:30",
+            "conflict",
+            "note.md: 'date': 14:00:00 doesn't match 'startTime' (14:30:00)",
+        ),
+        // the end is still needed
+        (
+            "date: 2024-05-03T14:00",
+            "missing_field",
+            "note.md: has no 'endTime'",
+        ),
+        (
+            "date: 2024-05-03T25:00\nendTime: 15:30",
+            "bad_datetime",
+            "note.md: 'date' is '2024-05-03T25:00', not a date and time (input contains invalid characters)",
+        ),
+        (
+            "startDate: 2024-05-03T14:00\nendDate: 2024-05-03T15:30\ndate: 2024-05-03T13:00",
+            "conflict",
+            "note.md: 'startDate': 14:00:00 doesn't match 'date' (13:00:00)",
+        ),
+    ] {
+        let note = format!("---\ntitle: Review\n{}\n---\n", keys);
+        let error = parse(&note).unwrap_err();
+        assert_eq!(error.code(), code, "{}", error);
+        assert_eq!(error.to_string(), message);
+    }
+}
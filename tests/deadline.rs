@@ -0,0 +1,255 @@
+//! Deadlines, from notes with `deadline: true`, which have a day but no time
+
+use chrono::{NaiveDate, NaiveTime};
+use obsidian_calendar_summary::{
+    calendar::Calendar,
+    cli::{AllDayPosition, SortBy},
+    event::{Event, SourcedEvent},
+    lang::Lang,
+    parse::{parse_cal_file_with_warnings, AllDayEnd, WeekdayLocale},
+    query::EventQuery,
+    render::{self, Fields, Role},
+    schema, SortSpec,
+};
+use std::path::Path;
+
+fn date(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 5, day).unwrap()
+}
+
+fn parse(frontmatter: &str) -> Event {
+    let (mut events, warnings) = parse_cal_file_with_warnings(
+        &format!("---\n{}---\n", frontmatter),
+        Path::new("note.md"),
+        WeekdayLocale::English,
+        AllDayEnd::Exclusive,
+        None,
+        false,
+        true,
+    )
+    .unwrap_or_else(|e| panic!("{}", e));
+    assert!(warnings.is_empty(), "{:?}", warnings);
+    events.remove(0)
+}
+
+/// A deadline on the 15th with `extra` in its note, and on every day a timed event and an all-day
+/// one
+fn calendar(extra: &str) -> Calendar {
+    let events = [
+        parse("title: Standup\ntype: recurring\ndaysOfWeek: [M, T, W, R, F]\nstartTime: 09:00\nendTime: 09:15\n"),
+        parse(&format!(
+            "title: Tax return\ndeadline: true\ndate: 2024-05-15\n{}",
+            extra
+        )),
+        parse("title: Sprint\nallDay: true\ndate: 2024-05-01\nendDate: 2024-06-01\n"),
+    ];
+    Calendar {
+        events: events
+            .into_iter()
+            .map(|event| {
+                let path = format!("{}.md", event.title());
+                SourcedEvent::new(event, path)
+            })
+            .collect(),
+        holidays: Vec::new(),
+        problems: Vec::new(),
+    }
+}
+
+/// The lines shown at 08:00 on `day` of May, with `--deadline-lookahead` and `--show-overdue`
+fn shown(day: u32, lookahead: u64, show_overdue: bool) -> Vec<String> {
+    shown_in(&calendar(""), day, lookahead, show_overdue)
+}
+
+fn shown_in(calendar: &Calendar, day: u32, lookahead: u64, show_overdue: bool) -> Vec<String> {
+    let lang = Lang::english();
+    let now = date(day).and_time(NaiveTime::from_hms_opt(8, 0, 0).unwrap());
+    EventQuery::today(now)
+        .deadline_lookahead(lookahead)
+        .show_overdue(show_overdue)
+        .apply(calendar)
+        .into_iter()
+        .map(|sourced| {
+            let mut fields = Fields::new(
+                sourced,
+                now.date(),
+                now,
+                SortBy::Start,
+                &lang,
+                false,
+                chrono::Duration::zero(),
+            );
+            // as without `--show-duration`
+            fields.duration = None;
+            render::line(&fields)
+        })
+        .collect()
+}
+
+#[test]
+fn parsed() {
+    assert_eq!(
+        parse("title: Tax return\ndeadline: true\ndate: 2024-05-15\n"),
+        Event::Deadline {
+            title: "Tax return".into(),
+            due: date(15),
+            completed: false,
+        }
+    );
+    // Full Calendar writes when it was done
+    for completed in ["true", "2024-05-14T18:02:00"] {
+        assert_eq!(
+            parse(&format!(
+                "title: Tax return\ndeadline: true\ndate: 2024-05-15\ncompleted: {}\n",
+                completed
+            )),
+            Event::Deadline {
+                title: "Tax return".into(),
+                due: date(15),
+                completed: true,
+            }
+        );
+    }
+}
+
+#[test]
+fn counting_down() {
+    // a week out, between the all-day event and the timed one
+    assert_eq!(
+        shown(8, 7, false),
+        [
+            "May 01 - May 31 (23 days left) | Sprint",
+            "Due in 7 days            | Tax return",
+            "09:00 - 09:15 (1 hour)   | Standup",
+        ]
+    );
+    assert_eq!(
+        shown(14, 7, false)[1],
+        "Due in 1 day             | Tax return"
+    );
+    assert_eq!(
+        shown(15, 7, false)[1],
+        "Due today                | Tax return"
+    );
+    // too far ahead, or gone once it's past
+    assert_eq!(shown(7, 7, false).len(), 2);
+    assert_eq!(shown(16, 7, false).len(), 2);
+}
+
+#[test]
+fn only_on_the_day_by_default() {
+    assert_eq!(shown(14, 0, false).len(), 2);
+    assert_eq!(
+        shown(15, 0, false)[1],
+        "Due today                | Tax return"
+    );
+}
+
+#[test]
+fn overdue() {
+    // at the very top, above all-day events, for as long as it isn't done
+    assert_eq!(
+        shown(16, 0, true),
+        [
+            "Overdue 1 day            | Tax return",
+            "May 01 - May 31 (15 days left) | Sprint",
+            "09:00 - 09:15 (1 hour)   | Standup",
+        ]
+    );
+    assert_eq!(
+        shown(19, 0, true)[0],
+        "Overdue 4 days           | Tax return"
+    );
+    let next_year = NaiveDate::from_ymd_opt(2025, 5, 15)
+        .unwrap()
+        .and_time(NaiveTime::MIN);
+    let calendar = calendar("");
+    let lang = Lang::english();
+    let shown = EventQuery::today(next_year)
+        .show_overdue(true)
+        .apply(&calendar);
+    let fields = Fields::new(
+        shown[0],
+        next_year.date(),
+        next_year,
+        SortBy::Start,
+        &lang,
+        false,
+        chrono::Duration::zero(),
+    );
+    assert_eq!(fields.overdue_days, Some(365));
+    assert_eq!(fields.role(5), Role::Overdue);
+    assert!(render::json(&[fields]).contains(r#""deadline":true,"overdue_days":365,"#));
+}
+
+#[test]
+fn completed() {
+    let calendar = calendar("completed: true\n");
+    for (day, lookahead, overdue) in [(8, 7, false), (15, 0, false), (16, 0, true)] {
+        assert_eq!(
+            shown_in(&calendar, day, lookahead, overdue).len(),
+            2,
+            "{}",
+            day
+        );
+    }
+}
+
+#[test]
+fn not_overdue() {
+    let lang = Lang::english();
+    let calendar = calendar("");
+    let fields = |day| {
+        let now = date(day).and_time(NaiveTime::MIN);
+        Fields::new(
+            &calendar.events[1],
+            now.date(),
+            now,
+            SortBy::Start,
+            &lang,
+            false,
+            chrono::Duration::zero(),
+        )
+        .overdue_days
+    };
+    assert_eq!(fields(14), None);
+    assert_eq!(fields(15), None);
+    assert_eq!(fields(16), Some(1));
+}
+
+#[test]
+fn sorted() {
+    let calendar = calendar("");
+    let on = |position| {
+        let sort = SortSpec {
+            allday_position: position,
+            ..SortSpec::default()
+        };
+        let mut events: Vec<&SourcedEvent> = calendar.events.iter().collect();
+        events.sort_by(|a, b| sort.compare_sourced(a, b));
+        events
+            .iter()
+            .map(|sourced| sourced.event.title())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    assert_eq!(on(AllDayPosition::Top), "Sprint, Tax return, Standup");
+    assert_eq!(on(AllDayPosition::Bottom), "Standup, Tax return, Sprint");
+    assert_eq!(on(AllDayPosition::Inline), "Sprint, Tax return, Standup");
+}
+
+#[test]
+fn json() {
+    let event = parse("title: Tax return\ndeadline: true\ndate: 2024-05-15\n");
+    let json = schema::to_json(&event);
+    assert_eq!(
+        json,
+        r#"{"version":3,"type":"deadline","title":"Tax return","date":"2024-05-15","completed":false}"#
+    );
+    assert_eq!(schema::from_json(&json).unwrap(), event);
+    // older versions had no deadlines
+    assert!(schema::from_json(
+        r#"{"version":2,"type":"deadline","title":"Tax return","date":"2024-05-15"}"#
+    )
+    .is_err());
+}
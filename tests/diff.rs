@@ -0,0 +1,130 @@
+//! Changes to the list of events between runs, for `--diff-state`
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use obsidian_calendar_summary::diff::{diff, Change, Entry, Snapshot};
+
+fn date(date: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap()
+}
+
+fn now(now: &str) -> NaiveDateTime {
+    NaiveDateTime::parse_from_str(now, "%Y-%m-%dT%H:%M").unwrap()
+}
+
+/// An event today from `begin` to `end`, or all day without them
+fn entry(title: &str, times: Option<(&str, &str)>) -> Entry {
+    let time = |time| NaiveTime::parse_from_str(time, "%H:%M").unwrap();
+    Entry {
+        title: title.into(),
+        date: date("2024-05-08"),
+        begin: times.map(|(begin, _)| time(begin)),
+        end: times.map(|(_, end)| time(end)),
+    }
+}
+
+fn snapshot(events: &[Entry]) -> Snapshot {
+    Snapshot {
+        date: date("2024-05-08"),
+        events: events.to_vec(),
+    }
+}
+
+#[test]
+fn added_removed_and_retimed() {
+    let standup = entry("Standup", Some(("09:45", "10:15")));
+    let dentist = entry("Dentist", Some(("14:00", "15:00")));
+    let moved = entry("Dentist", Some(("16:00", "17:00")));
+    let gym = entry("Gym", Some(("18:00", "19:00")));
+    let conference = entry("Conference", None);
+    let before = snapshot(&[standup.clone(), dentist.clone(), gym.clone()]);
+    let after = snapshot(&[conference.clone(), standup.clone(), moved.clone()]);
+    assert_eq!(
+        diff(&before, &after, now("2024-05-08T10:00")),
+        [
+            Change::Added(conference),
+            Change::Retimed {
+                before: dentist,
+                after: moved
+            },
+            Change::Removed(gym),
+        ]
+    );
+    assert_eq!(diff(&after, &after, now("2024-05-08T10:00")), []);
+}
+
+#[test]
+fn same_titles() {
+    // only the one which moved changed
+    let before = snapshot(&[
+        entry("Review", Some(("09:00", "10:00"))),
+        entry("Review", Some(("14:00", "15:00"))),
+    ]);
+    let after = snapshot(&[
+        entry("Review", Some(("11:00", "12:00"))),
+        entry("Review", Some(("14:00", "15:00"))),
+    ]);
+    assert_eq!(
+        diff(&before, &after, now("2024-05-08T08:00")),
+        [Change::Retimed {
+            before: entry("Review", Some(("09:00", "10:00"))),
+            after: entry("Review", Some(("11:00", "12:00"))),
+        }]
+    );
+}
+
+#[test]
+fn ended_events_drop_off() {
+    let before = snapshot(&[entry("Standup", Some(("09:45", "10:15")))]);
+    let after = snapshot(&[]);
+    assert_eq!(diff(&before, &after, now("2024-05-08T10:30")), []);
+    // but one which vanished before it ended was removed
+    assert_eq!(
+        diff(&before, &after, now("2024-05-08T10:00")),
+        [Change::Removed(entry("Standup", Some(("09:45", "10:15"))))]
+    );
+}
+
+#[test]
+fn new_day() {
+    let before = snapshot(&[entry("Standup", Some(("09:45", "10:15")))]);
+    let after = Snapshot {
+        date: date("2024-05-09"),
+        events: vec![entry("Gym", Some(("18:00", "19:00")))],
+    };
+    assert_eq!(diff(&before, &after, now("2024-05-09T08:00")), []);
+}
+
+#[test]
+fn round_trip() {
+    for snapshot in [
+        snapshot(&[
+            entry("Conference", None),
+            entry("\"Quoted\" standup", Some(("09:45", "10:15"))),
+        ]),
+        snapshot(&[]),
+    ] {
+        assert_eq!(Snapshot::parse(&snapshot.to_json()).unwrap(), snapshot);
+    }
+    // a time with seconds is written with them, so it's the same event next time
+    let mut standup = entry("Standup", Some(("09:00", "09:15")));
+    standup.begin = NaiveTime::from_hms_opt(9, 0, 30);
+    let seconds = snapshot(&[standup]);
+    let json = seconds.to_json();
+    assert!(
+        json.contains("\"start\":\"09:00:30\",\"end\":\"09:15\""),
+        "{}",
+        json
+    );
+    let saved = Snapshot::parse(&json).unwrap();
+    assert_eq!(saved, seconds);
+    assert_eq!(diff(&saved, &seconds, now("2024-05-08T08:00")), []);
+    for broken in [
+        "[]",
+        "{\"date\":\"2024-05-08\"}",
+        "{\"date\":\"yesterday\",\"events\":[]}",
+        "{\"date\":\"2024-05-08\",\"events\":[{\"date\":\"2024-05-08\"}]}",
+        "{\"date\":\"2024-05-08\",\"events\":[{\"title\":\"Gym\",\"date\":\"2024-05-08\",\"start\":\"6pm\"}]}",
+    ] {
+        assert!(Snapshot::parse(broken).is_err(), "{}", broken);
+    }
+}
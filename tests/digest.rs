@@ -0,0 +1,65 @@
+//! The pieces of `digest`: its line wrapping and how long is scheduled on a day
+
+use chrono::{NaiveDate, NaiveTime};
+use obsidian_calendar_summary::{
+    digest::{scheduled_minutes, wrap, WIDTH},
+    event::{Event, SourcedEvent},
+};
+
+fn sourced(event: Event) -> SourcedEvent {
+    SourcedEvent::new(event, "note.md")
+}
+
+fn timed(begin: &str, end: &str) -> SourcedEvent {
+    sourced(Event::Once {
+        title: "Meeting".into(),
+        begin: NaiveTime::parse_from_str(begin, "%H:%M").unwrap(),
+        end: NaiveTime::parse_from_str(end, "%H:%M").unwrap(),
+        day: NaiveDate::from_ymd_opt(2024, 5, 8).unwrap(),
+        travel_minutes: None,
+        actual_end: None,
+    })
+}
+
+#[test]
+fn short_lines() {
+    assert_eq!(
+        wrap("  09:45 - 10:15  ", "Standup", WIDTH),
+        "  09:45 - 10:15  Standup\n"
+    );
+    assert_eq!(wrap("", "", WIDTH), "\n");
+}
+
+#[test]
+fn long_lines() {
+    let title =
+        "Quarterly planning with the platform, infrastructure and developer experience teams";
+    let wrapped = wrap("  09:00 - 12:00  ", title, WIDTH);
+    assert_eq!(
+        wrapped,
+        "  09:00 - 12:00  Quarterly planning with the platform, infrastructure\n                 and developer experience teams\n"
+    );
+    for line in wrapped.lines() {
+        assert!(line.chars().count() <= WIDTH);
+    }
+    // a word longer than a line is kept whole, on a line of its own
+    assert_eq!(
+        wrap("  ", &format!("see {}", "x".repeat(80)), WIDTH),
+        format!("  see\n  {}\n", "x".repeat(80))
+    );
+}
+
+#[test]
+fn scheduled() {
+    let conference = sourced(Event::AllDay {
+        title: "Conference".into(),
+        begin_date: NaiveDate::from_ymd_opt(2024, 5, 7).unwrap(),
+        end_date: NaiveDate::from_ymd_opt(2024, 5, 10).unwrap(),
+        suppress_recurring: false,
+    });
+    let standup = timed("09:45", "10:15");
+    // running past midnight counts in full on the day it begins
+    let gig = timed("22:00", "01:00");
+    assert_eq!(scheduled_minutes(&[&conference]), 0);
+    assert_eq!(scheduled_minutes(&[&conference, &standup, &gig]), 210);
+}
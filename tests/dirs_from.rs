@@ -0,0 +1,88 @@
+//! Reading the calendar directories from a list with `--dirs-from`
+
+use obsidian_calendar_summary::cli::parse_dir_list;
+use std::{
+    io::Write,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+#[test]
+fn list() {
+    let list = "# work\nWork/Meetings\n\n  Personal  \n\t# old\n/srv/Shared\n";
+    assert_eq!(
+        parse_dir_list(list, Path::new("/vault")),
+        ["/vault/Work/Meetings", "/vault/Personal", "/srv/Shared"]
+    );
+    assert_eq!(parse_dir_list("Calendar\n", Path::new("")), ["Calendar"]);
+    assert!(parse_dir_list("# nothing\n\n", Path::new("/vault")).is_empty());
+}
+
+/// Runs the binary from `dir` with the test config, feeding it `stdin`, and
+/// returns its stdout and stderr
+fn run(dir: &Path, args: &[&str], stdin: &str) -> (String, String) {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let mut child = Command::new(env!("CARGO_BIN_EXE_obsidian-calendar-summary"))
+        .args(args)
+        .args(["--now", "2024-05-08T10:00", "--errors", "json"])
+        .current_dir(dir)
+        .env("XDG_CONFIG_HOME", &fixtures)
+        .env("XDG_STATE_HOME", &fixtures)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run obsidian-calendar-summary");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success(), "exited with {}", output.status);
+    (
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+#[test]
+fn relative_to_the_list() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let fixtures = root.join("tests/fixtures");
+    let (expected, errors) = run(&fixtures, &["vault"], "");
+    assert!(expected.contains("Dentist"), "{}", expected);
+    assert!(errors.contains(r#""path":"vault/Broken.md""#), "{}", errors);
+    // from somewhere else, the list still means the vault next to it, and
+    // the same notes are reported with paths from there
+    assert_eq!(
+        run(root, &["--dirs-from", "tests/fixtures/dirs.txt"], ""),
+        (expected, errors.replace("vault/", "tests/fixtures/vault/"))
+    );
+    // and a command can come after it
+    let (week, week_errors) = run(&fixtures, &["week", "vault"], "");
+    assert_eq!(
+        run(
+            root,
+            &["--dirs-from", "tests/fixtures/dirs.txt", "week"],
+            ""
+        ),
+        (week, week_errors.replace("vault/", "tests/fixtures/vault/"))
+    );
+}
+
+#[test]
+fn from_stdin() {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    // relative to where it's run from, since there's no list file
+    let (expected, errors) = run(&fixtures, &["vault"], "");
+    assert!(errors.contains(r#""path":"vault/Broken.md""#), "{}", errors);
+    assert_eq!(
+        run(&fixtures, &["--dirs-from", "-"], "# vault\nvault\n"),
+        (expected, errors)
+    );
+    // an empty list reads nothing, so there's nothing to report
+    let (_, errors) = run(&fixtures, &["--dirs-from", "-"], "# nothing\n");
+    assert_eq!(errors, "{\"errors\":[]}\n");
+}
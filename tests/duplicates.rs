@@ -0,0 +1,66 @@
+//! Recurring events copied into another note, e.g. by a sync tool, which `lint` points out and
+//! `--dedup-fuzzy` shows once
+
+use std::{path::Path, process::Command};
+
+const STANDUP: &str = "---\ntitle: Standup\ntype: recurring\ndaysOfWeek: [M, T, W, R, F]\nstartTime: 09:45\nendTime: 10:15\n---\n";
+
+fn run(vault: &Path, args: &[&str]) -> (bool, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_obsidian-calendar-summary"))
+        .args(args)
+        .args(["--now", "2024-05-08T09:00", "--oneline"])
+        .arg(vault)
+        .env("XDG_CONFIG_HOME", vault)
+        .output()
+        .expect("failed to run obsidian-calendar-summary");
+    (
+        output.status.success(),
+        String::from_utf8(output.stdout).unwrap(),
+    )
+}
+
+#[test]
+fn duplicates() {
+    let vault = std::env::temp_dir().join(format!(
+        "obsidian-calendar-summary-duplicates-{}",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&vault).unwrap();
+    std::fs::write(vault.join("Standup.md"), STANDUP).unwrap();
+    // the same days written differently
+    std::fs::write(
+        vault.join("Standup 1.md"),
+        STANDUP.replace("[M, T, W, R, F]", "[F, R, W, T, M]"),
+    )
+    .unwrap();
+    // a different time isn't a copy
+    std::fs::write(
+        vault.join("Standup 2.md"),
+        STANDUP.replace("09:45", "09:30"),
+    )
+    .unwrap();
+
+    assert_eq!(
+        run(&vault, &[]),
+        (
+            true,
+            "09:30 Standup | 09:45 Standup | 09:45 Standup\n".into()
+        )
+    );
+    assert_eq!(
+        run(&vault, &["--dedup-fuzzy"]),
+        (true, "09:30 Standup | 09:45 Standup\n".into())
+    );
+    let (success, lint) = run(&vault, &["lint"]);
+    assert!(!success);
+    // the first by path is taken as the original, and "Standup 1.md" sorts first
+    assert_eq!(
+        lint,
+        format!(
+            "{}: probably a duplicate of {}, with the same title, times and days\n",
+            vault.join("Standup.md").display(),
+            vault.join("Standup 1.md").display()
+        )
+    );
+    let _ = std::fs::remove_dir_all(&vault);
+}
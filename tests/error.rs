@@ -0,0 +1,89 @@
+//! The kind of error each problem is reported as, which scripts match on with `--errors json`
+
+use obsidian_calendar_summary::{
+    error::{Error, ParseErrorKind},
+    parse::{parse_cal_file, parse_events, AllDayEnd, WeekdayLocale},
+};
+use std::path::Path;
+
+fn parse(path: &str, frontmatter: &str) -> Error {
+    parse_cal_file(
+        &format!("---\n{}---\n", frontmatter),
+        Path::new(path),
+        WeekdayLocale::English,
+        AllDayEnd::Exclusive,
+    )
+    .unwrap_err()
+}
+
+#[test]
+fn missing_title() {
+    // untitled events are named after their note, so only one without a name can have no title
+    let error = parse(
+        "/",
+        "allDay: false\ndate: 2024-05-08\nstartTime: 09:00\nendTime: 10:00\n",
+    );
+    assert_eq!(error.code(), "missing_field");
+    assert!(
+        matches!(
+            &error,
+            Error::Parse {
+                field: Some(field),
+                kind: ParseErrorKind::MissingField,
+                ..
+            } if field == "title"
+        ),
+        "{:?}",
+        error
+    );
+    assert_eq!(error.to_string(), "/: has no 'title'");
+}
+
+#[test]
+fn bad_date() {
+    let error = parse(
+        "note.md",
+        "title: Dentist\nallDay: false\ndate: 2024-05-32\nstartTime: 09:00\nendTime: 10:00\n",
+    );
+    assert_eq!(error.code(), "bad_date");
+    assert!(
+        matches!(
+            &error,
+            Error::Parse {
+                field: Some(field),
+                kind: ParseErrorKind::BadDate { value, .. },
+                ..
+            } if field == "date" && value == "2024-05-32"
+        ),
+        "{:?}",
+        error
+    );
+    assert_eq!(
+        error.to_string(),
+        "note.md: 'date' is '2024-05-32', not a date (input is out of range)"
+    );
+}
+
+#[test]
+fn io() {
+    let missing = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/missing");
+    let error = parse_events(
+        &missing,
+        WeekdayLocale::English,
+        AllDayEnd::Exclusive,
+        false,
+        false,
+        &[],
+        None,
+        false,
+        true,
+    )
+    .unwrap_err();
+    assert_eq!(error.code(), "io");
+    assert!(
+        matches!(&error, Error::Io { path, .. } if *path == missing),
+        "{:?}",
+        error
+    );
+    assert!(error.is_skippable());
+}
@@ -0,0 +1,256 @@
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use obsidian_calendar_summary::event::{is_nth_weekday, Event};
+
+fn date(date: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap()
+}
+
+/// Tuesdays and Thursdays from `begin_recur` to `end_recur`
+fn course(begin_recur: Option<&str>, end_recur: Option<&str>) -> Event {
+    Event::Recurring {
+        title: "Pottery course".into(),
+        begin: NaiveTime::from_hms_opt(19, 0, 0).unwrap(),
+        end: NaiveTime::from_hms_opt(21, 0, 0).unwrap(),
+        begin_recur: begin_recur.map(date),
+        end_recur: end_recur.map(date),
+        recur_days: vec![Weekday::Tue, Weekday::Thu],
+        skip_holidays: false,
+        travel_minutes: None,
+        week_of_month: None,
+    }
+}
+
+/// The `week_of_month`th `day` of every month from `begin_recur` to `end_recur`
+fn monthly_event(
+    week_of_month: i8,
+    day: Weekday,
+    begin_recur: Option<&str>,
+    end_recur: Option<&str>,
+) -> Event {
+    Event::Recurring {
+        title: "Club".into(),
+        begin: NaiveTime::from_hms_opt(19, 30, 0).unwrap(),
+        end: NaiveTime::from_hms_opt(21, 30, 0).unwrap(),
+        begin_recur: begin_recur.map(date),
+        end_recur: end_recur.map(date),
+        recur_days: vec![day],
+        skip_holidays: false,
+        travel_minutes: None,
+        week_of_month: Some(week_of_month),
+    }
+}
+
+fn datetime(datetime: &str) -> NaiveDateTime {
+    NaiveDateTime::parse_from_str(datetime, "%Y-%m-%d %H:%M").unwrap()
+}
+
+fn interval(begin: &str, end: &str) -> Option<(NaiveDateTime, NaiveDateTime)> {
+    Some((datetime(begin), datetime(end)))
+}
+
+#[test]
+fn occurrence_index() {
+    let course = course(Some("2024-09-03"), Some("2024-10-03"));
+    assert_eq!(course.total_occurrences(), Some(10));
+    // the first and last sessions are on the bounds themselves
+    assert_eq!(course.occurrence_index(date("2024-09-03")), Some(1));
+    assert_eq!(course.occurrence_index(date("2024-09-05")), Some(2));
+    assert_eq!(course.occurrence_index(date("2024-10-01")), Some(9));
+    assert_eq!(course.occurrence_index(date("2024-10-03")), Some(10));
+    // not a session
+    assert_eq!(course.occurrence_index(date("2024-09-04")), None);
+    assert_eq!(course.occurrence_index(date("2024-08-29")), None);
+    assert_eq!(course.occurrence_index(date("2024-10-08")), None);
+}
+
+#[test]
+fn bounds_between_sessions() {
+    // starting on a Monday and ending on a Wednesday doesn't add any sessions
+    let course = course(Some("2024-09-02"), Some("2024-10-02"));
+    assert_eq!(course.total_occurrences(), Some(9));
+    assert_eq!(course.occurrence_index(date("2024-09-03")), Some(1));
+    assert_eq!(course.occurrence_index(date("2024-10-01")), Some(9));
+    // a course with no sessions at all
+    let course = self::course(Some("2024-09-04"), Some("2024-09-04"));
+    assert_eq!(course.total_occurrences(), Some(0));
+}
+
+#[test]
+fn unbounded() {
+    for course in [
+        course(None, None),
+        course(Some("2024-09-03"), None),
+        course(None, Some("2024-10-03")),
+    ] {
+        assert_eq!(course.total_occurrences(), None);
+        assert_eq!(course.occurrence_index(date("2024-09-05")), None);
+    }
+}
+
+#[test]
+fn occurrence_windows() {
+    let days = |event: &Event, from: NaiveDate, to: NaiveDate| -> Vec<NaiveDate> {
+        event.occurrences(from, to).collect()
+    };
+    let weekly = course(None, None);
+    // a window of exactly one day, on a day it happens and on one it doesn't
+    assert_eq!(
+        days(&weekly, date("2024-09-03"), date("2024-09-03")),
+        [date("2024-09-03")]
+    );
+    assert!(days(&weekly, date("2024-09-04"), date("2024-09-04")).is_empty());
+    // a window of zero days ends the day before it begins, and one ending earlier still is empty
+    // too rather than going backwards
+    assert!(days(&weekly, date("2024-09-03"), date("2024-09-02")).is_empty());
+    assert!(days(&weekly, date("2024-09-03"), date("2023-09-03")).is_empty());
+    // these would take ages if every day in the window was looked at
+    let later = course(Some("2024-09-03"), None);
+    assert!(days(&later, NaiveDate::MIN, date("2024-09-02")).is_empty());
+    assert_eq!(
+        days(&later, NaiveDate::MIN, date("2024-09-06")),
+        ["2024-09-03", "2024-09-05"].map(date)
+    );
+    let earlier = course(None, Some("2024-10-03"));
+    assert!(days(&earlier, date("2024-10-04"), NaiveDate::MAX).is_empty());
+    assert_eq!(
+        days(&earlier, date("2024-10-01"), NaiveDate::MAX),
+        ["2024-10-01", "2024-10-03"].map(date)
+    );
+    // and a course which ends before it begins never happens
+    let backwards = course(Some("2024-10-03"), Some("2024-09-03"));
+    assert!(days(&backwards, NaiveDate::MIN, NaiveDate::MAX).is_empty());
+}
+
+#[test]
+fn nth_weekday() {
+    // March 2025 has five Mondays: the 3rd, 10th, 17th, 24th and 31st
+    assert!(is_nth_weekday(date("2025-03-03"), 1));
+    assert!(is_nth_weekday(date("2025-03-10"), 2));
+    assert!(is_nth_weekday(date("2025-03-31"), 5));
+    assert!(is_nth_weekday(date("2025-03-31"), -1));
+    assert!(is_nth_weekday(date("2025-03-24"), -2));
+    assert!(is_nth_weekday(date("2025-03-03"), -5));
+    assert!(!is_nth_weekday(date("2025-03-24"), -1));
+    assert!(!is_nth_weekday(date("2025-03-10"), 1));
+    // the last Friday is the 4th in February 2025 and the 5th in January
+    assert!(is_nth_weekday(date("2025-02-28"), -1));
+    assert!(is_nth_weekday(date("2025-02-28"), 4));
+    assert!(is_nth_weekday(date("2025-01-31"), -1));
+    assert!(is_nth_weekday(date("2025-01-31"), 5));
+    assert!(!is_nth_weekday(date("2025-01-24"), -1));
+}
+
+#[test]
+fn monthly_recurrence() {
+    let fifth_monday = monthly_event(5, Weekday::Mon, None, None);
+    let days: Vec<NaiveDate> = fifth_monday
+        .occurrences(date("2025-01-01"), date("2025-12-31"))
+        .collect();
+    // the months with only four Mondays are skipped
+    assert_eq!(
+        days,
+        ["2025-03-31", "2025-06-30", "2025-09-29", "2025-12-29"].map(date)
+    );
+    let last_friday = monthly_event(-1, Weekday::Fri, Some("2025-01-01"), Some("2025-03-31"));
+    assert_eq!(
+        last_friday
+            .occurrences(date("2025-01-01"), date("2025-12-31"))
+            .collect::<Vec<NaiveDate>>(),
+        ["2025-01-31", "2025-02-28", "2025-03-28"].map(date)
+    );
+    assert_eq!(last_friday.total_occurrences(), Some(3));
+    assert_eq!(last_friday.occurrence_index(date("2025-02-28")), Some(2));
+}
+
+#[test]
+fn interval_on() {
+    let course = course(Some("2024-09-03"), Some("2024-10-03"));
+    assert_eq!(
+        course.interval_on(date("2024-09-05")),
+        interval("2024-09-05 19:00", "2024-09-05 21:00")
+    );
+    // a Wednesday, and a Thursday after the course is over
+    assert_eq!(course.interval_on(date("2024-09-04")), None);
+    assert_eq!(course.interval_on(date("2024-10-10")), None);
+
+    let gig = Event::Once {
+        title: "Gig".into(),
+        begin: NaiveTime::from_hms_opt(22, 0, 0).unwrap(),
+        end: NaiveTime::from_hms_opt(1, 0, 0).unwrap(),
+        day: date("2024-12-30"),
+        travel_minutes: None,
+        actual_end: None,
+    };
+    // runs past midnight into the next day, which it doesn't happen on itself
+    assert_eq!(
+        gig.interval_on(date("2024-12-30")),
+        interval("2024-12-30 22:00", "2024-12-31 01:00")
+    );
+    assert_eq!(gig.interval_on(date("2024-12-31")), None);
+
+    let conference = Event::AllDay {
+        title: "Conference".into(),
+        begin_date: date("2024-05-07"),
+        end_date: date("2024-05-10"),
+        suppress_recurring: false,
+    };
+    // just the one day, in the middle of the conference
+    assert_eq!(
+        conference.interval_on(date("2024-05-08")),
+        interval("2024-05-08 00:00", "2024-05-09 00:00")
+    );
+    assert_eq!(
+        conference.interval_on(date("2024-05-09")),
+        interval("2024-05-09 00:00", "2024-05-10 00:00")
+    );
+    // the end date is the day after it's over
+    assert_eq!(conference.interval_on(date("2024-05-10")), None);
+    assert_eq!(conference.interval_on(date("2024-05-06")), None);
+}
+
+#[test]
+fn same_recurrence() {
+    let course = course(Some("2024-01-01"), None);
+    // when it recurs from and until doesn't matter, nor the order of the days
+    let mut copy = self::course(None, Some("2024-12-31"));
+    if let Event::Recurring { recur_days, .. } = &mut copy {
+        recur_days.reverse();
+    }
+    assert!(course.same_recurrence(&copy));
+    assert!(copy.same_recurrence(&course));
+    let changed = |change: fn(&mut Event)| {
+        let mut other = course.clone();
+        change(&mut other);
+        course.same_recurrence(&other)
+    };
+    assert!(!changed(|event| {
+        if let Event::Recurring { title, .. } = event {
+            *title = "Pottery".into()
+        }
+    }));
+    assert!(!changed(|event| {
+        if let Event::Recurring { end, .. } = event {
+            *end = NaiveTime::from_hms_opt(20, 0, 0).unwrap()
+        }
+    }));
+    assert!(!changed(|event| {
+        if let Event::Recurring { recur_days, .. } = event {
+            recur_days.pop();
+        }
+    }));
+    assert!(!changed(|event| {
+        if let Event::Recurring { week_of_month, .. } = event {
+            *week_of_month = Some(1)
+        }
+    }));
+    // only recurring events
+    let once = Event::Once {
+        title: "Pottery course".into(),
+        begin: NaiveTime::from_hms_opt(19, 0, 0).unwrap(),
+        end: NaiveTime::from_hms_opt(21, 0, 0).unwrap(),
+        day: date("2024-05-07"),
+        travel_minutes: None,
+        actual_end: None,
+    };
+    assert!(!once.same_recurrence(&once));
+}
@@ -0,0 +1,124 @@
+//! Events without a `date` in notes named like `2024-05-03 Dentist.md`, which Full Calendar can
+//! leave out since the date is in the name
+
+use chrono::{NaiveDate, NaiveTime};
+use obsidian_calendar_summary::{
+    error::Error,
+    event::Event,
+    parse::{parse_cal_file_with_warnings, AllDayEnd, WeekdayLocale},
+};
+use std::path::Path;
+
+fn parse(note: &str, frontmatter: &str, filename_date: bool) -> Result<Event, Error> {
+    let (mut events, _) = parse_cal_file_with_warnings(
+        &format!("---\n{}---\n", frontmatter),
+        Path::new(note),
+        WeekdayLocale::English,
+        AllDayEnd::Exclusive,
+        None,
+        false,
+        filename_date,
+    )?;
+    Ok(events.remove(0))
+}
+
+fn date(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 5, day).unwrap()
+}
+
+const TIMED: &str = "startTime: 14:00\nendTime: 15:00\n";
+
+#[test]
+fn from_the_name() {
+    assert_eq!(
+        parse("Calendar/2024-05-03 Dentist.md", TIMED, true).unwrap(),
+        Event::Once {
+            title: "Dentist".into(),
+            begin: NaiveTime::from_hms_opt(14, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(15, 0, 0).unwrap(),
+            day: date(3),
+            travel_minutes: None,
+            actual_end: None,
+        }
+    );
+    assert_eq!(
+        parse("2024-05-03 Conference.md", "allDay: true\n", true).unwrap(),
+        Event::AllDay {
+            title: "Conference".into(),
+            begin_date: date(3),
+            end_date: date(4),
+            suppress_recurring: false,
+        }
+    );
+    assert_eq!(
+        parse("2024-05-03 Tax return.md", "deadline: true\n", true).unwrap(),
+        Event::Deadline {
+            title: "Tax return".into(),
+            due: date(3),
+            completed: false,
+        }
+    );
+    // a title in the note is still used
+    assert_eq!(
+        parse(
+            "2024-05-03 Dentist.md",
+            &format!("title: Checkup\n{}", TIMED),
+            true
+        )
+        .unwrap()
+        .title(),
+        "Checkup"
+    );
+}
+
+#[test]
+fn the_note_wins() {
+    // the date in the note, and then the name is left as it is
+    let event = parse(
+        "2024-05-03 Dentist.md",
+        &format!("date: 2024-05-10\n{}", TIMED),
+        true,
+    )
+    .unwrap();
+    assert_eq!(event.title(), "2024-05-03 Dentist");
+    assert!(matches!(event, Event::Once { day, .. } if day == date(10)));
+    let event = parse(
+        "2024-05-03 Dentist.md",
+        "startDate: 2024-05-10T14:00\nendDate: 2024-05-10T15:00\n",
+        true,
+    )
+    .unwrap();
+    assert!(matches!(event, Event::Once { day, .. } if day == date(10)));
+}
+
+#[test]
+fn without_a_date_in_the_name() {
+    for note in ["Dentist.md", "2024-05-32 Dentist.md", "May 3 Dentist.md"] {
+        assert_eq!(
+            parse(note, TIMED, true).unwrap_err().to_string(),
+            format!("{}: has no 'date'", note)
+        );
+    }
+}
+
+#[test]
+fn turned_off() {
+    assert_eq!(
+        parse("2024-05-03 Dentist.md", TIMED, false)
+            .unwrap_err()
+            .to_string(),
+        "2024-05-03 Dentist.md: has no 'date'"
+    );
+}
+
+#[test]
+fn recurring() {
+    // they have no `date` anyway
+    let event = parse(
+        "2024-05-03 Gym.md",
+        &format!("type: recurring\ndaysOfWeek: [M]\n{}", TIMED),
+        true,
+    )
+    .unwrap();
+    assert_eq!(event.title(), "2024-05-03 Gym");
+}
@@ -0,0 +1,77 @@
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use obsidian_calendar_summary::{
+    event::{Event, SourcedEvent},
+    focus::{free_gaps, suggest},
+};
+
+fn at(time: &str) -> NaiveDateTime {
+    NaiveDateTime::parse_from_str(&format!("2024-05-08 {}", time), "%Y-%m-%d %H:%M").unwrap()
+}
+
+fn minutes(minutes: i64) -> Duration {
+    Duration::try_minutes(minutes).unwrap()
+}
+
+fn event(begin: &str, end: &str) -> SourcedEvent {
+    SourcedEvent::new(
+        Event::Once {
+            title: "Meeting".into(),
+            begin: at(begin).time(),
+            end: at(end).time(),
+            day: at(begin).date(),
+            travel_minutes: None,
+            actual_end: None,
+        },
+        "Meeting.md",
+    )
+}
+
+#[test]
+fn gaps_between_overlapping_events() {
+    let events = [
+        event("09:00", "10:00"),
+        event("09:30", "11:00"),
+        event("13:00", "14:00"),
+    ];
+    let events: Vec<&SourcedEvent> = events.iter().collect();
+    let date = NaiveDate::from_ymd_opt(2024, 5, 8).unwrap();
+    assert_eq!(
+        free_gaps(&events, date, at("08:00"), at("18:00")),
+        [
+            (at("08:00"), at("09:00")),
+            (at("11:00"), at("13:00")),
+            (at("14:00"), at("18:00"))
+        ]
+    );
+    // nothing before `from` counts, including the rest of an ongoing event
+    assert_eq!(
+        free_gaps(&events, date, at("10:30"), at("13:30")),
+        [(at("11:00"), at("13:00"))]
+    );
+}
+
+#[test]
+fn longest_gaps_first() {
+    let gaps = [
+        (at("08:00"), at("09:00")),
+        (at("10:00"), at("12:00")),
+        (at("13:00"), at("13:30")),
+        (at("14:00"), at("16:00")),
+        (at("17:00"), at("20:00")),
+    ];
+    assert_eq!(
+        suggest(&gaps, minutes(50), 2),
+        [(at("10:00"), at("12:00")), (at("17:00"), at("20:00"))]
+    );
+    // too short to suggest anything in
+    assert_eq!(suggest(&gaps, minutes(4 * 60), 2), []);
+    assert_eq!(
+        suggest(&gaps, minutes(50), 10),
+        [
+            (at("08:00"), at("09:00")),
+            (at("10:00"), at("12:00")),
+            (at("14:00"), at("16:00")),
+            (at("17:00"), at("20:00"))
+        ]
+    );
+}
@@ -0,0 +1,168 @@
+//! The typed getters every key of an event is read with
+
+use chrono::{NaiveDate, NaiveTime};
+use obsidian_calendar_summary::parse::Frontmatter;
+use std::{path::Path, process::Command};
+
+fn with<T>(frontmatter: &str, read: impl FnOnce(&Frontmatter) -> T) -> T {
+    let contents = format!("---\n{}---\n", frontmatter);
+    let frontmatter = Frontmatter::parse(&contents, Path::new("note.md"), None, None)
+        .unwrap_or_else(|e| panic!("{}", e));
+    read(&frontmatter)
+}
+
+fn message<T: std::fmt::Debug>(
+    result: Result<T, obsidian_calendar_summary::error::Error>,
+) -> String {
+    result.unwrap_err().to_string()
+}
+
+#[test]
+fn nothing_there() {
+    // however it's written, an empty value is the same as no key at all
+    for value in ["", "\"\"", "''", "null", "~", "  # no end yet"] {
+        let frontmatter = format!("endRecur: {}\n", value);
+        with(&frontmatter, |frontmatter| {
+            assert!(!frontmatter.has("endRecur"), "{}", value);
+            assert_eq!(frontmatter.get_str("endRecur").unwrap(), None);
+            assert_eq!(frontmatter.get_date_opt("endRecur").unwrap(), None);
+            assert_eq!(frontmatter.get_time_opt("endRecur").unwrap(), None);
+            assert_eq!(frontmatter.get_bool("endRecur").unwrap(), None);
+            assert_eq!(frontmatter.get_list("endRecur").unwrap(), None);
+        });
+    }
+    with("", |frontmatter| {
+        assert_eq!(
+            message(frontmatter.get_date("date")),
+            "note.md: has no 'date'"
+        );
+        assert_eq!(
+            message(frontmatter.get_time("startTime")),
+            "note.md: has no 'startTime'"
+        );
+    });
+}
+
+#[test]
+fn strings() {
+    with("title: Dentist  \ndaysOfWeek: [M, W]\n", |frontmatter| {
+        assert_eq!(frontmatter.get_str("title").unwrap(), Some("Dentist"));
+        assert_eq!(
+            message(frontmatter.get_str("daysOfWeek")),
+            "note.md: 'daysOfWeek': is a list, not a single value"
+        );
+    });
+}
+
+#[test]
+fn dates_and_times() {
+    with(
+        "date: \"2024-05-03\"\nendDate: 2024-05-32\nstartTime: 09:00 # early\nendTime: 9am\n",
+        |frontmatter| {
+            assert_eq!(
+                frontmatter.get_date("date").unwrap(),
+                NaiveDate::from_ymd_opt(2024, 5, 3).unwrap()
+            );
+            assert_eq!(
+                message(frontmatter.get_date_opt("endDate")),
+                "note.md: 'endDate' is '2024-05-32', not a date (input is out of range)"
+            );
+            assert_eq!(
+                frontmatter.get_time("startTime").unwrap(),
+                NaiveTime::from_hms_opt(9, 0, 0).unwrap()
+            );
+            assert_eq!(
+                message(frontmatter.get_time("endTime")),
+                "note.md: 'endTime' is '9am', not a time (input contains invalid characters)"
+            );
+        },
+    );
+}
+
+#[test]
+fn booleans_and_numbers() {
+    with(
+        "allDay: True\npinned: false\nskipHolidays: yes\ntravelMinutes: 20\nweekOfMonth: first\n",
+        |frontmatter| {
+            assert_eq!(frontmatter.get_bool("allDay").unwrap(), Some(true));
+            assert_eq!(frontmatter.get_bool("pinned").unwrap(), Some(false));
+            assert_eq!(
+                message(frontmatter.get_bool("skipHolidays")),
+                "note.md: 'skipHolidays': is 'yes', not true or false"
+            );
+            assert_eq!(
+                frontmatter
+                    .get_parsed::<u32>("travelMinutes", "a number of minutes")
+                    .unwrap(),
+                Some(20)
+            );
+            assert_eq!(
+                message(frontmatter.get_parsed::<i8>("weekOfMonth", "a number")),
+                "note.md: 'weekOfMonth': is 'first', not a number"
+            );
+        },
+    );
+}
+
+#[test]
+fn lists() {
+    with("daysOfWeek: [M, W]\ndays: M\n", |frontmatter| {
+        assert_eq!(
+            frontmatter.get_list("daysOfWeek").unwrap(),
+            Some(&["M", "W"][..])
+        );
+        // only `daysOfWeek` and `aliases` are read as lists
+        assert_eq!(
+            message(frontmatter.get_list("days")),
+            "note.md: 'days': is 'M', not a list"
+        );
+    });
+}
+
+#[test]
+fn duplicate_keys() {
+    // the first one wins, so a key added further down can't quietly change the event
+    with("date: 2024-05-08\ndate: 2024-05-09\n", |frontmatter| {
+        assert_eq!(
+            frontmatter.get_date("date").unwrap(),
+            NaiveDate::from_ymd_opt(2024, 5, 8).unwrap()
+        );
+    });
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let run = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_obsidian-calendar-summary"))
+            .args(["--now", "2024-05-08T10:00"])
+            .args(args)
+            .current_dir(&fixtures)
+            .env("XDG_CONFIG_HOME", &fixtures)
+            .env("XDG_STATE_HOME", &fixtures)
+            .output()
+            .unwrap()
+    };
+    let output = run(&["duplicate_keys"]);
+    assert!(output.status.success(), "exited with {}", output.status);
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "14:00 - 15:00 (4 hours)  | Dentist\n"
+    );
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "Warning: duplicate_keys/Dentist.md: 'date': duplicate key, using the first value\n"
+    );
+    // it's a warning like any other, so lint lists it and --strict stops at it
+    let output = run(&["lint", "--porcelain", "duplicate_keys"]);
+    assert!(output.status.success(), "exited with {}", output.status);
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "duplicate_keys/Dentist.md\twarning\tduplicate_key\t\
+         duplicate_keys/Dentist.md: 'date': duplicate key, using the first value\n"
+    );
+    let output = run(&["--errors", "json", "duplicate_keys"]);
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "{\"errors\":[{\"code\":\"duplicate_key\",\"field\":\"date\",\
+         \"path\":\"duplicate_keys/Dentist.md\",\"event\":null,\"message\":\
+         \"duplicate_keys/Dentist.md: 'date': duplicate key, using the first value\"}]}\n"
+    );
+    assert_eq!(run(&["--strict", "duplicate_keys"]).status.code(), Some(1));
+}
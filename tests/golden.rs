@@ -0,0 +1,885 @@
+//! Runs the binary on the notes in `tests/fixtures/vault` at a fixed time and compares what it
+//! prints with the files in `tests/golden`. After a deliberate change to the output, run
+//! `UPDATE_GOLDEN=1 cargo test` to rewrite them and review the diff.
+
+use std::{
+    path::Path,
+    process::{Command, Output},
+};
+
+const NOW: &str = "2024-05-08T10:00";
+
+fn output(now: &str, args: &[&str]) -> Output {
+    output_in("UTC", now, args)
+}
+
+/// Runs the binary with the local timezone set to `zone`
+fn output_in(zone: &str, now: &str, args: &[&str]) -> Output {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    Command::new(env!("CARGO_BIN_EXE_obsidian-calendar-summary"))
+        .args(args)
+        .args(["--now", now, "--errors", "json", "vault"])
+        .current_dir(&fixtures)
+        // keep the user's config file and snoozed events out of it
+        .env("XDG_CONFIG_HOME", &fixtures)
+        .env("XDG_STATE_HOME", &fixtures)
+        .env_remove("COLUMNS")
+        .env_remove("COLORFGBG")
+        .env("TZ", zone)
+        .output()
+        .expect("failed to run obsidian-calendar-summary")
+}
+
+fn run(now: &str, args: &[&str]) -> (String, String) {
+    let output = output(now, args);
+    assert!(output.status.success(), "exited with {}", output.status);
+    (
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+fn check(name: &str, actual: &str) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(name);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("cannot read {}: {}", path.display(), e));
+    assert_eq!(
+        expected,
+        actual,
+        "output differs from {} (UPDATE_GOLDEN=1 rewrites it)",
+        path.display()
+    );
+}
+
+#[test]
+fn today() {
+    let (stdout, stderr) = run(NOW, &[]);
+    check("today.txt", &stdout);
+    check("errors.json", &stderr);
+}
+
+#[test]
+fn today_json() {
+    let (stdout, _) = run(NOW, &["--format", "json"]);
+    check("today.json", &stdout);
+}
+
+#[test]
+fn week() {
+    let (stdout, _) = run(NOW, &["week"]);
+    check("week.txt", &stdout);
+}
+
+#[test]
+fn week_collapse_recurring() {
+    let mut out = String::new();
+    for args in [
+        &["week", "--collapse-recurring"][..],
+        // the days are in the order they're shown, so Sunday comes first
+        &["week", "--collapse-recurring", "--week-start", "sun"],
+        // and hidden days don't count
+        &["week", "--collapse-recurring", "--hide-weekends"],
+    ] {
+        out += &format!("{}\n", args.join(" "));
+        out += &run(NOW, args).0;
+    }
+    check("week_collapse_recurring.txt", &out);
+}
+
+#[test]
+fn past_midnight() {
+    // the Gig runs from 22:00 until 01:00 the next day
+    let (stdout, _) = run("2024-12-30T23:58", &["--format", "json", "--progress"]);
+    check("past_midnight.json", &stdout);
+}
+
+#[test]
+fn new_years_eve() {
+    // nothing is left on the 31st, so the Fireworks at 00:05 are shown
+    let (stdout, _) = run("2024-12-31T23:58", &["--show-next-day"]);
+    check("new_years_eve.txt", &stdout);
+}
+
+#[test]
+fn new_year() {
+    let (stdout, _) = run("2025-01-01T00:01", &[]);
+    check("new_year.txt", &stdout);
+}
+
+#[test]
+fn week_over_new_year() {
+    let (stdout, _) = run("2024-12-31T23:58", &["week"]);
+    check("week_over_new_year.txt", &stdout);
+}
+
+#[test]
+fn allday_end() {
+    // the Conference's endDate is 2024-05-11, and Bin day's is today
+    let (stdout, _) = run("2024-05-10T10:00", &["--allday-end", "exclusive"]);
+    check("allday_end_exclusive.txt", &stdout);
+    let (stdout, _) = run("2024-05-10T10:00", &["--allday-end", "inclusive"]);
+    check("allday_end_inclusive.txt", &stdout);
+}
+
+#[test]
+fn several_events_in_one_note() {
+    let (stdout, _) = run("2024-06-03T08:00", &[]);
+    check("several_events.txt", &stdout);
+}
+
+#[test]
+fn lint() {
+    let output = output(NOW, &["lint"]);
+    assert_eq!(output.status.code(), Some(1));
+    check("lint.txt", &String::from_utf8(output.stdout).unwrap());
+}
+
+#[test]
+fn lint_porcelain() {
+    // the broken notes make it fail, not the warnings
+    let output = output(NOW, &["lint", "--porcelain"]);
+    assert_eq!(output.status.code(), Some(1));
+    check(
+        "lint_porcelain.txt",
+        &String::from_utf8(output.stdout).unwrap(),
+    );
+}
+
+#[test]
+fn suggest_focus() {
+    let (stdout, _) = run(NOW, &["--suggest-focus", "50m"]);
+    check("suggest_focus.txt", &stdout);
+}
+
+#[test]
+fn tmux() {
+    // tmux's own colour codes, since it shows ANSI escapes as they are
+    assert_eq!(
+        run(NOW, &["--format", "tmux"]).0,
+        "#[fg=cyan]Conference#[default] | #[fg=green]*09:45 Standup#[default] | \
+         #[default]14:00 Dentist#[default] | #[default]18:00 Gym#[default]\n"
+    );
+    // the Dentist starts within `--soon` minutes
+    assert_eq!(
+        run("2024-05-08T13:50", &["--format", "tmux"]).0,
+        "#[fg=cyan]Conference#[default] | #[fg=yellow]14:00 Dentist#[default] | \
+         #[default]18:00 Gym#[default]\n"
+    );
+    // cut off after 30 visible characters, not counting the codes
+    assert_eq!(
+        run(NOW, &["--format", "tmux", "--max-width", "30"]).0,
+        "#[fg=cyan]Conference#[default] | #[fg=green]*09:45 Standup#[default] | \n"
+    );
+}
+
+#[test]
+fn travel_time() {
+    // the Interview is at 14:00 and takes 30 minutes to get to
+    let mut out = String::new();
+    for (now, args) in [
+        ("2024-05-15T13:00", &[][..]),
+        ("2024-05-15T13:20", &["--format", "tmux"][..]),
+        ("2024-05-15T13:40", &[][..]),
+        ("2024-05-15T14:15", &[][..]),
+    ] {
+        out += format!("--now {} {}", now, args.join(" ")).trim_end();
+        out.push('\n');
+        out += &run(now, args).0;
+    }
+    check("travel_time.txt", &out);
+}
+
+#[test]
+fn themes() {
+    // the Standup is on and the Dentist and Gym are later
+    let now = "2024-05-08T09:50";
+    let mut out = String::new();
+    for args in [
+        &["--format", "tmux"][..],
+        &["--format", "tmux", "--theme", "light"],
+        &["--format", "polybar", "--theme", "light"],
+        &["--format", "i3blocks", "--theme", "light"],
+        &["--format", "tmux", "--config", "theme.toml"],
+        &[
+            "--format",
+            "polybar",
+            "--config",
+            "theme.toml",
+            "--theme",
+            "dark",
+        ],
+    ] {
+        out += &format!("{}\n", args.join(" "));
+        out += &run(now, args).0;
+    }
+    check("themes.txt", &out);
+}
+
+#[test]
+fn vacations() {
+    // Summer holiday and Long weekend overlap, and are followed by a normal week
+    let (stdout, _) = run("2024-07-01T09:00", &["week", "--week-of", "2024-W28"]);
+    check("vacations_overlapping.txt", &stdout);
+    let (stdout, _) = run("2024-07-01T09:00", &["week", "--week-of", "2024-W29"]);
+    check("vacations_end.txt", &stdout);
+    // Autumn break is on its own
+    let (stdout, _) = run("2024-10-21T09:00", &[]);
+    check("vacation_today.txt", &stdout);
+}
+
+#[test]
+fn week_start() {
+    // on a Saturday, the week starting on Sunday is the one which ends today
+    let saturday = "2024-05-11T12:00";
+    let (stdout, _) = run(saturday, &["week", "--week-start", "sun"]);
+    check("week_start_sun.txt", &stdout);
+    let (stdout, _) = run(saturday, &["week", "--week-start", "mon"]);
+    check("week_start_mon.txt", &stdout);
+    let (stdout, _) = run(saturday, &["month", "--week-start", "sun"]);
+    check("month_start_sun.txt", &stdout);
+}
+
+#[test]
+fn weekends() {
+    let saturday = "2024-05-11T12:00";
+    let (stdout, _) = run(saturday, &["week", "--skip-weekends"]);
+    check("weekends_skip_empty.txt", &stdout);
+    // the Long weekend is on the Saturday and Sunday of this week
+    let (stdout, _) = run(
+        saturday,
+        &["week", "--week-of", "2024-W28", "--skip-weekends"],
+    );
+    check("weekends_skip_with_events.txt", &stdout);
+    let (stdout, _) = run(
+        saturday,
+        &["week", "--week-of", "2024-W28", "--hide-weekends"],
+    );
+    check("weekends_hide.txt", &stdout);
+}
+
+#[test]
+fn clocks_change() {
+    // the Night shift is at 02:30 every Sunday, which doesn't exist when the clocks go forward
+    // and happens twice when they go back
+    for (zone, name, fall_back_week) in [
+        ("Europe/Berlin", "berlin", "2024-W43"),
+        ("America/New_York", "new_york", "2024-W44"),
+    ] {
+        let lint = output_in(zone, "2024-03-01T12:00", &["lint"]);
+        check(
+            &format!("clocks_change_{}_lint.txt", name),
+            &String::from_utf8(lint.stdout).unwrap(),
+        );
+        let week = output_in(
+            zone,
+            "2024-10-01T12:00",
+            &["week", "--week-of", fall_back_week],
+        );
+        assert!(week.status.success());
+        check(
+            &format!("clocks_change_{}_week.txt", name),
+            &String::from_utf8(week.stdout).unwrap(),
+        );
+    }
+}
+
+#[test]
+fn infer_category_from_dir() {
+    // Planning is in the Work folder, and Offsite is too but has its own category and colour
+    let now = "2024-05-20T08:00";
+    let (stdout, _) = run(now, &["--format", "json", "--infer-category-from-dir"]);
+    check("infer_category.json", &stdout);
+    // without the flag the folder isn't read at all
+    let (stdout, _) = run(now, &[]);
+    check("infer_category_off.txt", &stdout);
+}
+
+#[test]
+fn index() {
+    // the index is in Home/ but links from the root of the vault, which has an .obsidian folder
+    let (stdout, stderr) = run("2024-05-12T08:00", &["--index", "indexed/Home/Calendar.md"]);
+    check("index.txt", &stdout);
+    check("index_warnings.txt", &stderr);
+}
+
+#[test]
+fn sort_by_end() {
+    // the Hackathon starts before the Standup but ends long after it
+    let now = "2024-05-22T08:30";
+    let (stdout, _) = run(now, &["--sort", "start"]);
+    check("sort_start.txt", &stdout);
+    let (stdout, _) = run(now, &["--sort", "end"]);
+    check("sort_end.txt", &stdout);
+}
+
+#[test]
+fn reverse() {
+    // all-day events move to the bottom along with everything else, unless they're put somewhere
+    let (stdout, _) = run(NOW, &["--reverse"]);
+    check("reverse.txt", &stdout);
+    let (stdout, _) = run(NOW, &["--reverse", "--allday-position", "top"]);
+    check("reverse_allday_top.txt", &stdout);
+}
+
+#[test]
+fn occurrence_index() {
+    // the Pottery course is on Tuesdays and Thursdays, ten times from 2024-09-03 to 2024-10-03
+    let mut out = String::new();
+    for now in ["2024-09-03T18:00", "2024-09-26T18:00", "2024-10-03T18:00"] {
+        out += &format!("--now {}\n", now);
+        out += &run(now, &["--show-occurrence-index", "--oneline"]).0;
+    }
+    check("occurrence_index.txt", &out);
+    let (stdout, _) = run("2024-10-03T18:00", &["--format", "json"]);
+    check("occurrence_index.json", &stdout);
+}
+
+#[test]
+fn monthly() {
+    // the Book club is on the first Monday, the Pub quiz on the last Friday, and the Fifth Monday
+    // social only in months with five Mondays
+    let (stdout, _) = run(
+        "2025-01-01T00:00",
+        &["--format", "remind", "--horizon", "365"],
+    );
+    let monthly: String = stdout
+        .lines()
+        .filter(|line| {
+            ["Book club", "Pub quiz", "Fifth Monday"]
+                .iter()
+                .any(|title| line.contains(title))
+        })
+        .map(|line| format!("{}\n", line))
+        .collect();
+    check("monthly.rem", &monthly);
+}
+
+#[test]
+fn show_duration() {
+    // the Gig runs past midnight, from 22:00 until 01:00
+    let mut out = String::new();
+    for now in [NOW, "2024-12-30T20:00"] {
+        out += &format!("--now {}\n", now);
+        out += &run(now, &["--show-duration"]).0;
+    }
+    check("show_duration.txt", &out);
+}
+
+#[test]
+fn time_window() {
+    // the Standup is ongoing at 10:00, and the Gig runs from 22:00 until 01:00
+    let mut out = String::new();
+    for (now, args) in [
+        (NOW, &["--until", "12:00"][..]),
+        (NOW, &["--from", "15:00"]),
+        (NOW, &["--from", "14:30", "--until", "17:59"]),
+        ("2024-05-08T10:30", &["--from", "15:00", "--next-in"]),
+        ("2024-12-30T20:00", &["--until", "21:00"]),
+        ("2024-12-30T20:00", &["--from", "23:00"]),
+    ] {
+        out += &format!("--now {} {}\n", now, args.join(" "));
+        out += &run(now, args).0;
+    }
+    check("time_window.txt", &out);
+}
+
+#[test]
+fn days_left() {
+    // the Summer holiday is from Monday the 8th to Friday the 12th of July, and the Long weekend
+    // starts on the 11th
+    let mut out = String::new();
+    for day in 7..=13 {
+        let now = format!("2024-07-{:02}T09:00", day);
+        out += &format!("--now {}\n", now);
+        out += &run(&now, &["--allday-only"]).0;
+    }
+    check("days_left.txt", &out);
+}
+
+#[test]
+fn broken_note() {
+    // without `--errors json` a note which can't be read stops everything, which a widget only
+    // reading the output of `--next-in` can tell from the exit status
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    for args in [&[][..], &["--next-in"]] {
+        let output = Command::new(env!("CARGO_BIN_EXE_obsidian-calendar-summary"))
+            .args(args)
+            .args(["--now", NOW, "vault"])
+            .current_dir(&fixtures)
+            .env("XDG_CONFIG_HOME", &fixtures)
+            .env("XDG_STATE_HOME", &fixtures)
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(1), "{:?}", args);
+        assert!(output.stdout.is_empty());
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(
+            stderr.ends_with(
+                "Error processing event files: vault/Broken.md: 'date' is '2024-05-32', not a date \
+                 (input is out of range)\n"
+            ),
+            "{}",
+            stderr
+        );
+    }
+}
+
+#[test]
+fn allday_filters() {
+    // the Conference is the only all-day event at 10:00; the flags drop events before anything
+    // counts them, so they're left out of `--header` and `--summary` too
+    let mut out = String::new();
+    for args in [
+        &["--no-allday"][..],
+        &["--allday-only"],
+        &["--no-allday", "--until", "12:00"],
+        &["--allday-only", "--from", "15:00"],
+        &["--no-allday", "--header"],
+        &["--allday-only", "--header"],
+        &[
+            "--allday-only",
+            "--format",
+            "json",
+            "--json-lines",
+            "--summary",
+        ],
+    ] {
+        out += &format!("{}\n", args.join(" "));
+        out += &run(NOW, args).0;
+    }
+    check("allday_filters.txt", &out);
+    // all-day events don't count as next either way, so without timed events there's none
+    let next_in = output(NOW, &["--allday-only", "--next-in"]);
+    assert_eq!(next_in.stdout, b"\n");
+    assert_eq!(next_in.status.code(), Some(1));
+    let both = output(NOW, &["--no-allday", "--allday-only"]);
+    assert_eq!(both.status.code(), Some(1));
+}
+
+#[test]
+fn pinned() {
+    // the timesheet is pinned, and due from 09:00 to 09:30
+    let mut out = String::new();
+    for (now, args) in [
+        ("2024-06-12T08:00", &[][..]),
+        ("2024-06-12T08:00", &["--until", "08:30"]),
+        ("2024-06-12T12:00", &[]),
+        ("2024-06-12T12:00", &["--next-in"]),
+        ("2024-06-12T12:00", &["--format", "tmux"]),
+        ("2024-06-12T12:00", &["--format", "polybar"]),
+    ] {
+        out += &format!("--now {} {}\n", now, args.join(" "));
+        out += &run(now, args).0;
+    }
+    check("pinned.txt", &out);
+    let (stdout, _) = run("2024-06-12T12:00", &["--format", "json"]);
+    check("pinned.json", &stdout);
+}
+
+#[test]
+fn explain_sort() {
+    let mut out = String::new();
+    for (now, args) in [
+        (NOW, &[][..]),
+        (NOW, &["--sort", "end", "--reverse"]),
+        // the timesheet is pinned and over, so it goes last whatever the sort says
+        ("2024-06-12T12:00", &[]),
+    ] {
+        out += &format!("--now {} {}\n", now, args.join(" "));
+        let (_, stderr) = run(now, &[args, &["--explain-sort"]].concat());
+        // leave out the problems with the fixtures, which `--errors json` prints too
+        for line in stderr.lines().filter(|line| !line.starts_with('{')) {
+            out += line;
+            out += "\n";
+        }
+    }
+    check("explain_sort.txt", &out);
+}
+
+#[test]
+fn diff_state() {
+    // since the snapshot, the Dentist has moved, the Gym has been added and Team lunch removed.
+    // The early call has just finished, which doesn't count as being removed.
+    let state = std::env::temp_dir().join(format!("diff-state-{}.json", std::process::id()));
+    let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/diff_state.json");
+    let state_arg = state.to_str().unwrap();
+    let mut out = String::new();
+    for args in [
+        &["--quiet", "--format", "json"][..],
+        &["--quiet"],
+        // nothing has changed since the first run
+        &[],
+    ] {
+        std::fs::copy(&fixture, &state).unwrap();
+        if args.is_empty() {
+            run(NOW, &["--diff-state", state_arg]);
+        }
+        out += &format!("--diff-state {}\n", args.join(" "));
+        out += &run(NOW, &[args, &["--diff-state", state_arg]].concat()).0;
+    }
+    let snapshot = std::fs::read_to_string(&state).unwrap();
+    std::fs::remove_file(&state).unwrap();
+    check("diff_state.txt", &out);
+    check("diff_state.json", &snapshot);
+}
+
+#[test]
+fn lang_de() {
+    let mut out = String::new();
+    for (now, args) in [
+        (NOW, &["--show-duration", "--show-occurrence-index"][..]),
+        (NOW, &["--sort", "end"]),
+        ("2024-05-08T09:50", &["--next-in", "--humanize"]),
+        ("2024-05-11T08:00", &["--show-next-day"]),
+        (NOW, &["month", "--spans"]),
+        (NOW, &["week"]),
+        // with the plurals overridden in the config file
+        (NOW, &["--config", "lang.toml"]),
+    ] {
+        out += &format!("--now {} {}\n", now, args.join(" "));
+        let lang: &[&str] = if args.contains(&"--config") {
+            &[]
+        } else {
+            &["--lang", "de"]
+        };
+        out += &run(now, &[lang, args].concat()).0;
+    }
+    check("lang_de.txt", &out);
+}
+
+#[test]
+fn seconds() {
+    // the Dentist is at 14:00
+    let mut out = String::new();
+    for (now, args) in [
+        ("2024-05-08T13:58:59", &[][..]),
+        ("2024-05-08T13:59:01", &[]),
+        ("2024-05-08T13:59:01", &["--next-in"]),
+        ("2024-05-08T13:55:30", &["--seconds"]),
+        ("2024-05-08T13:59:01", &["--seconds", "--format", "tmux"]),
+    ] {
+        out += &format!("--now {} {}\n", now, args.join(" "));
+        out += &run(now, args).0;
+    }
+    check("seconds.txt", &out);
+}
+
+#[test]
+fn grace() {
+    // the Standup ends at 10:15
+    let mut out = String::new();
+    for (now, args) in [
+        ("2024-05-08T10:16", &["--grace", "10m"][..]),
+        ("2024-05-08T10:26", &["--grace", "10m"]),
+        ("2024-05-08T10:16", &[]),
+        ("2024-05-08T10:16", &["--grace", "10m", "--next-in"]),
+        ("2024-05-08T10:16", &["--grace", "10m", "--format", "tmux"]),
+    ] {
+        out += &format!("--now {} {}\n", now, args.join(" "));
+        out += &run(now, args).0;
+    }
+    check("grace.txt", &out);
+}
+
+#[test]
+fn status() {
+    let mut out = String::new();
+    for (now, args) in [
+        (NOW, &["--format", "status"][..]),
+        (NOW, &["--format", "status", "--status-with-title"]),
+        (
+            "2024-05-08T13:50",
+            &["--format", "status", "--status-with-title"],
+        ),
+        (
+            "2024-05-08T11:00",
+            &["--format", "status", "--status-with-title"],
+        ),
+        // nothing else, even with no events left
+        (
+            "2024-05-08T23:00",
+            &["--format", "status", "--empty-text", "Nothing on"],
+        ),
+    ] {
+        out += &format!("--now {} {}\n", now, args.join(" "));
+        out += &run(now, args).0;
+    }
+    check("status.txt", &out);
+}
+
+#[test]
+fn empty() {
+    // nothing left by 23:00 but the conference, which is left out
+    let late = "2024-05-08T23:00";
+    let mut out = String::new();
+    for args in [
+        &[][..],
+        &["--empty-text", "Nothing until tomorrow ({date})"],
+        &["--format", "json", "--empty-text", "Nothing"],
+        &["--format", "tmux"],
+        &["--format", "polybar", "--empty-text", "Free"],
+        &["--format", "i3blocks"],
+        &["--format", "i3blocks", "--lang", "de"],
+        &["--quiet", "--empty-text", "Nothing"],
+    ] {
+        let args = [&["--no-allday"][..], args].concat();
+        out += &format!("{}\n", args.join(" "));
+        out += &run(late, &args).0;
+    }
+    check("empty.txt", &out);
+    // the text doesn't change whether there was anything to show
+    let output = output(
+        late,
+        &["--no-allday", "--next-in", "--empty-text", "free on {date}"],
+    );
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "free on Wed 08 May\n"
+    );
+}
+
+#[test]
+fn ignore() {
+    // only the broken notes which aren't ignored are reported
+    let (_, stderr) = run(
+        NOW,
+        &["--ignore", "Workshop.md", "--ignore", "*template.md"],
+    );
+    check("ignore_errors.json", &stderr);
+    // the config file's patterns are added to the flag's
+    let (_, stderr) = run(
+        NOW,
+        &[
+            "--config",
+            "ignore.toml",
+            "--ignore",
+            "Workshop.md",
+            "--ignore",
+            "*template.md",
+        ],
+    );
+    assert_eq!(stderr, "{\"errors\":[]}\n");
+}
+
+#[test]
+fn header() {
+    let mut out = String::new();
+    for (now, args) in [
+        (NOW, &["--header"][..]),
+        // counted after filtering
+        (NOW, &["--header", "--no-allday"]),
+        (NOW, &["--header", "--oneline"]),
+        // focus blocks aren't events
+        (NOW, &["--header", "--suggest-focus", "60m"]),
+        ("2024-05-08T23:00", &["--header"]),
+        (NOW, &["--header", "--lang", "de"]),
+        // only for the terminal
+        (NOW, &["--header", "--format", "tmux"]),
+        (NOW, &["--header", "--next-in"]),
+    ] {
+        out += &format!("--now {} {}\n", now, args.join(" "));
+        out += &run(now, args).0;
+    }
+    check("header.txt", &out);
+}
+
+#[test]
+fn offset() {
+    // it's a Wednesday, and the Gym is on Mondays, Wednesdays and Fridays
+    let mut out = String::new();
+    for offset in ["-1", "0", "1", "2", "-2"] {
+        let args = ["--offset", offset, "--oneline"];
+        out += &format!("--now {} {}\n", NOW, args.join(" "));
+        out += &run(NOW, &args).0;
+    }
+    check("offset.txt", &out);
+    for offset in ["3651", "-3651", "tomorrow"] {
+        assert!(!output(NOW, &["--offset", offset]).status.success());
+    }
+    assert!(output(NOW, &["--offset", "-3650"]).status.success());
+}
+
+#[test]
+fn min_duration() {
+    // the Standup is 30 minutes long
+    let mut out = String::new();
+    for args in [
+        &["--min-duration", "30m"][..],
+        &["--min-duration", "31m"],
+        &["--min-duration", "0"],
+        &["--min-duration", "31m", "--format", "status"],
+    ] {
+        out += &format!("{}\n", args.join(" "));
+        out += &run(NOW, args).0;
+    }
+    check("min_duration.txt", &out);
+    // everything is still exported
+    assert_eq!(
+        run(NOW, &["--min-duration", "31m", "--format", "remind"]).0,
+        run(NOW, &["--format", "remind"]).0
+    );
+}
+
+#[test]
+fn avail() {
+    // the days before today, and the part of today that's over, have no free time left
+    let mut out = String::new();
+    for args in [
+        &[
+            "avail",
+            "--from",
+            "2024-05-07",
+            "--to",
+            "2024-05-11",
+            "--day-start",
+            "09:00",
+            "--day-end",
+            "17:00",
+            "--slot",
+            "30m",
+        ][..],
+        &["avail", "--slot", "1h", "--day-end", "24:00"],
+        &["avail", "--from", "2024-05-09", "--allday-busy"],
+        &["avail", "--from", "2024-05-09", "--format", "json"],
+    ] {
+        out += &format!("{}\n", args.join(" "));
+        out += &run(NOW, args).0;
+    }
+    check("avail.txt", &out);
+    assert!(!output(
+        NOW,
+        &["avail", "--from", "2024-05-09", "--to", "2024-05-08"]
+    )
+    .status
+    .success());
+}
+
+#[test]
+fn relative_dates() {
+    // nothing is on over the weekend before Christmas, so Monday's Standup is next
+    let mut out = String::new();
+    for args in [
+        &["--show-next-day"][..],
+        &["--show-next-day", "--relative-dates"],
+        &["--show-next-day", "--relative-dates", "--format", "json"],
+    ] {
+        out += &format!("{}\n", args.join(" "));
+        out += &run("2024-12-20T23:59", args).0;
+    }
+    check("relative_dates.txt", &out);
+}
+
+#[test]
+fn round() {
+    // the countdowns and durations still go by the real times
+    let mut out = String::new();
+    for args in [
+        &["--round", "20m", "--show-duration"][..],
+        &["--round", "20m", "--format", "json"],
+    ] {
+        out += &format!("{}\n", args.join(" "));
+        out += &run("2024-05-08T08:00", args).0;
+    }
+    check("round.txt", &out);
+}
+
+#[test]
+fn output_file() {
+    let file = std::env::temp_dir().join(format!("summary-{}.txt", std::process::id()));
+    let file_arg = file.to_str().unwrap();
+    let _ = std::fs::remove_file(&file);
+    let (today, _) = run(NOW, &[]);
+    // each run replaces the file, and nothing goes to stdout
+    for _ in 0..2 {
+        assert_eq!(run(NOW, &["--output", file_arg]).0, "");
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), today);
+    }
+    run(NOW, &["--output", file_arg, "--append"]);
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), today.repeat(2));
+    std::fs::remove_file(&file).unwrap();
+
+    assert!(!output(NOW, &["--append"]).status.success());
+    let unwritable = output(NOW, &["--output", "no such folder/summary.txt"]);
+    assert_eq!(unwritable.status.code(), Some(1));
+    assert!(String::from_utf8(unwritable.stderr)
+        .unwrap()
+        .starts_with("Error: no such folder/summary.txt: "));
+}
+
+#[test]
+fn digest() {
+    // the pottery course ends on the Thursday of next week
+    let (stdout, _) = run("2024-09-27T10:00", &["digest", "--week-of", "next"]);
+    check("digest.txt", &stdout);
+    assert_eq!(
+        stdout,
+        run("2024-09-27T10:00", &["digest", "--week-of", "2024-W40"]).0
+    );
+    for line in stdout.lines() {
+        assert!(line.chars().count() <= 72, "{}", line);
+    }
+}
+
+#[test]
+fn hidden() {
+    // the partner's shift is only in the vault to be seen in Obsidian
+    let mut out = String::new();
+    for args in [
+        &[][..],
+        &["--show-hidden"],
+        &["--show-hidden", "--format", "json"],
+    ] {
+        out += &format!("{}\n", args.join(" "));
+        out += &run(NOW, args).0;
+    }
+    check("hidden.txt", &out);
+    for format in ["json", "remind", "org", "taskwarrior"] {
+        assert!(!run(NOW, &["--format", format]).0.contains("Partner"));
+        assert!(run(NOW, &["--show-hidden", "--format", format])
+            .0
+            .contains("Partner"));
+    }
+    // nor is its leftover `daysOfWeek` worth a warning
+    let lint = output(NOW, &["lint"]);
+    assert!(!String::from_utf8(lint.stdout).unwrap().contains("Partner"));
+}
+
+#[test]
+fn compact() {
+    let mut out = String::new();
+    for (now, args) in [
+        (NOW, &["--format", "compact"][..]),
+        (NOW, &["--format", "compact", "--max-title", "4"]),
+        (NOW, &["--format", "compact", "--suggest-focus", "1h"]),
+        ("2024-05-03T23:00", &["--format", "compact", "--no-allday"]),
+    ] {
+        out += &format!("{} {}\n", now, args.join(" "));
+        out += &run(now, args).0;
+    }
+    check("compact.txt", &out);
+    let usage = output(NOW, &["--max-title", "4"]);
+    assert_eq!(usage.status.code(), Some(1));
+    assert!(String::from_utf8(usage.stderr)
+        .unwrap()
+        .contains("'--max-title' only works with '--format compact'"));
+}
+
+#[test]
+fn heatmap() {
+    let mut out = String::new();
+    for args in [
+        &["month", "2024-05", "--heatmap"][..],
+        &["month", "2024-05", "--heatmap", "--format", "json"],
+    ] {
+        out += &format!("{}\n", args.join(" "));
+        out += &run(NOW, args).0;
+    }
+    check("heatmap.txt", &out);
+}
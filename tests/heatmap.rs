@@ -0,0 +1,140 @@
+//! `month --heatmap`: how busy each hour of each weekday is over a month
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use obsidian_calendar_summary::{
+    calendar::Calendar,
+    event::{Event, SourcedEvent},
+    lang::Lang,
+    month::{split_into_hours, Heatmap},
+};
+
+fn at(day: u32, time: &str) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2024, 5, day)
+        .unwrap()
+        .and_time(NaiveTime::parse_from_str(time, "%H:%M").unwrap())
+}
+
+/// The hours as e.g. "09:00 0.5"
+fn split(begin: NaiveDateTime, end: NaiveDateTime) -> Vec<String> {
+    split_into_hours(begin, end)
+        .into_iter()
+        .map(|(hour, amount)| format!("{} {}", hour.format("%d %H:%M"), amount))
+        .collect()
+}
+
+#[test]
+fn buckets() {
+    assert_eq!(
+        split(at(8, "09:30"), at(8, "11:15")),
+        ["08 09:00 0.5", "08 10:00 1", "08 11:00 0.25"]
+    );
+    assert_eq!(split(at(8, "09:00"), at(8, "10:00")), ["08 09:00 1"]);
+    assert_eq!(split(at(8, "09:10"), at(8, "09:40")), ["08 09:00 0.5"]);
+    // into the next day
+    assert_eq!(
+        split(at(8, "22:30"), at(9, "01:00")),
+        ["08 22:00 0.5", "08 23:00 1", "09 00:00 1"]
+    );
+    assert!(split(at(8, "09:00"), at(8, "09:00")).is_empty());
+}
+
+fn sourced(event: Event) -> SourcedEvent {
+    let path = format!("{}.md", event.title());
+    SourcedEvent::new(event, path)
+}
+
+fn calendar() -> Calendar {
+    let time = |time: &str| NaiveTime::parse_from_str(time, "%H:%M").unwrap();
+    let events = vec![
+        // every Monday and Wednesday in May, four Mondays and five Wednesdays
+        sourced(Event::Recurring {
+            title: "Lecture".into(),
+            begin: time("09:30"),
+            end: time("11:15"),
+            begin_recur: None,
+            end_recur: None,
+            recur_days: vec![Weekday::Mon, Weekday::Wed],
+            skip_holidays: false,
+            travel_minutes: None,
+            week_of_month: None,
+        }),
+        sourced(Event::Once {
+            title: "Launch".into(),
+            begin: time("23:00"),
+            end: time("01:00"),
+            day: at(3, "00:00").date(),
+            travel_minutes: None,
+            actual_end: None,
+        }),
+        // neither of these count
+        sourced(Event::AllDay {
+            title: "Conference".into(),
+            begin_date: at(7, "00:00").date(),
+            end_date: at(10, "00:00").date(),
+            suppress_recurring: false,
+        }),
+        SourcedEvent {
+            hidden: true,
+            ..sourced(Event::Once {
+                title: "Partner shift".into(),
+                begin: time("09:00"),
+                end: time("17:00"),
+                day: at(8, "00:00").date(),
+                travel_minutes: None,
+                actual_end: None,
+            })
+        },
+        // in June
+        sourced(Event::Once {
+            title: "Dentist".into(),
+            begin: time("14:00"),
+            end: time("15:00"),
+            day: NaiveDate::from_ymd_opt(2024, 6, 3).unwrap(),
+            travel_minutes: None,
+            actual_end: None,
+        }),
+    ];
+    Calendar {
+        events,
+        holidays: Vec::new(),
+        problems: Vec::new(),
+    }
+}
+
+#[test]
+fn over_the_month() {
+    let heatmap = Heatmap::new(&calendar(), at(15, "00:00").date(), false);
+    let (monday, wednesday, friday, saturday) = (0, 2, 4, 5);
+    assert_eq!(heatmap.hours[monday][9], 4.0 * 0.5);
+    assert_eq!(heatmap.hours[monday][10], 4.0);
+    assert_eq!(heatmap.hours[monday][11], 4.0 * 0.25);
+    assert_eq!(heatmap.hours[wednesday][10], 5.0);
+    // the 3rd is a Friday, and the launch goes on past midnight
+    assert_eq!(heatmap.hours[friday][23], 1.0);
+    assert_eq!(heatmap.hours[saturday][0], 1.0);
+    let total: f64 = heatmap.hours.iter().flatten().sum();
+    assert_eq!(total, 9.0 * 1.75 + 2.0);
+}
+
+#[test]
+fn rendered() {
+    let heatmap = Heatmap::new(&calendar(), at(15, "00:00").date(), false);
+    assert_eq!(
+        heatmap.text(&Lang::english()),
+        "May 2024
+    00    03    06    09    12    15    18    21
+Mo                    ▒▒██░░
+Tu
+We                    ▒▒██░░
+Th
+Fr                                                ░░
+Sa  ░░
+Su
+░ ≤1h 15m  ▒ ≤2h 30m  ▓ ≤3h 45m  █ ≤5h
+"
+    );
+    let json = heatmap.json();
+    assert!(json.starts_with(r#"{"month":"2024-05","hours":[[0,0,0,0,0,0,0,0,0,2,4,1,0,"#));
+    assert_eq!(json.matches('[').count(), 8);
+    assert_eq!(json.matches(',').count(), 1 + 6 + 7 * 23);
+}
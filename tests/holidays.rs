@@ -0,0 +1,111 @@
+//! Public holidays from an ICS file, and recurring events skipped on them
+
+use chrono::{NaiveDate, NaiveTime, Weekday};
+use obsidian_calendar_summary::{
+    calendar::Calendar,
+    event::{Event, SourcedEvent},
+    query::EventQuery,
+};
+use std::{path::Path, process::Command};
+
+fn date(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 5, day).unwrap()
+}
+
+fn sourced(event: Event) -> SourcedEvent {
+    SourcedEvent::new(event, "holidays.ics")
+}
+
+/// Every weekday morning, skipped on holidays only if `skip_holidays`
+fn weekdays(title: &str, skip_holidays: bool) -> SourcedEvent {
+    sourced(Event::Recurring {
+        title: title.into(),
+        begin: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+        end: NaiveTime::from_hms_opt(9, 30, 0).unwrap(),
+        begin_recur: Some(date(1)),
+        end_recur: None,
+        recur_days: vec![
+            Weekday::Mon,
+            Weekday::Tue,
+            Weekday::Wed,
+            Weekday::Thu,
+            Weekday::Fri,
+        ],
+        skip_holidays,
+        travel_minutes: None,
+        week_of_month: None,
+    })
+}
+
+/// The spring bank holiday on Monday the 27th
+fn calendar() -> Calendar {
+    Calendar {
+        events: vec![weekdays("Standup", true), weekdays("Emails", false)],
+        holidays: vec![sourced(Event::AllDay {
+            title: "Spring bank holiday".into(),
+            begin_date: date(27),
+            end_date: date(28),
+            suppress_recurring: false,
+        })],
+        problems: Vec::new(),
+    }
+}
+
+fn titles(calendar: &Calendar, day: u32, skip_all: bool) -> Vec<&str> {
+    EventQuery::new()
+        .on_date(date(day))
+        .skip_holidays(skip_all)
+        .apply(calendar)
+        .into_iter()
+        .map(|sourced| sourced.event.title())
+        .collect()
+}
+
+#[test]
+fn skipped_on_the_holiday() {
+    let calendar = calendar();
+    assert!(calendar.is_holiday(date(27)));
+    assert!(!calendar.is_holiday(date(28)));
+    // only the Standup says `skipHolidays: true`
+    assert_eq!(
+        titles(&calendar, 27, false),
+        ["Spring bank holiday", "Emails"]
+    );
+    assert_eq!(titles(&calendar, 27, true), ["Spring bank holiday"]);
+    // the days around it are as usual
+    assert_eq!(titles(&calendar, 24, true), ["Emails", "Standup"]);
+    assert_eq!(titles(&calendar, 28, true), ["Emails", "Standup"]);
+}
+
+fn run(config: &str, now: &str) -> String {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let output = Command::new(env!("CARGO_BIN_EXE_obsidian-calendar-summary"))
+        .args([
+            "--config", config, "--errors", "json", "--now", now, "vault",
+        ])
+        .current_dir(&fixtures)
+        .env("XDG_STATE_HOME", &fixtures)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "exited with {}", output.status);
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn from_config() {
+    // the Standup and Gym in the vault both recur on Mondays
+    assert_eq!(
+        run("holidays.toml", "2024-05-27T08:00"),
+        "Today                    | Spring bank holiday\n\
+         09:45 - 10:15 (1 hour)   | Standup\n\
+         18:00 - 19:00 (10 hours) | Gym\n"
+    );
+    assert_eq!(
+        run("holidays_skip.toml", "2024-05-27T08:00"),
+        "Today                    | Spring bank holiday\n"
+    );
+    assert_eq!(
+        run("holidays_skip.toml", "2024-05-28T08:00"),
+        "09:45 - 10:15 (1 hour)   | Standup\n"
+    );
+}
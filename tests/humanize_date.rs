@@ -0,0 +1,67 @@
+//! The names `--relative-dates` gives the days events are on, and how long is left of all-day
+//! events
+
+use chrono::NaiveDate;
+use obsidian_calendar_summary::{
+    lang::Lang,
+    render::{humanize_date, humanize_days},
+};
+
+fn date(date: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap()
+}
+
+#[test]
+fn within_the_week() {
+    let lang = Lang::english();
+    // a Wednesday
+    let today = date("2024-05-08");
+    for (day, name) in [
+        ("2024-05-08", "Today"),
+        ("2024-05-09", "Tomorrow"),
+        ("2024-05-10", "Fri"),
+        ("2024-05-14", "Tue"),
+        // a week on is Wednesday again, so it gets the date
+        ("2024-05-15", "Wed 15 May"),
+        ("2024-05-07", "Tue 07 May"),
+    ] {
+        assert_eq!(humanize_date(date(day), today, &lang), name, "{}", day);
+    }
+}
+
+#[test]
+fn over_the_end_of_the_month() {
+    let lang = Lang::english();
+    let today = date("2024-05-30");
+    for (day, name) in [
+        ("2024-05-31", "Tomorrow"),
+        ("2024-06-01", "Sat"),
+        ("2024-06-05", "Wed"),
+        ("2024-06-06", "Thu 06 Jun"),
+    ] {
+        assert_eq!(humanize_date(date(day), today, &lang), name, "{}", day);
+    }
+    // and the end of the year
+    assert_eq!(
+        humanize_date(date("2025-01-02"), date("2024-12-31"), &lang),
+        "Thu"
+    );
+}
+
+#[test]
+fn days_left() {
+    let lang = Lang::english();
+    let left: Vec<String> = (-1..=4).map(|days| humanize_days(days, &lang)).collect();
+    assert_eq!(
+        left,
+        [
+            // finished events are filtered out before this, but just in case
+            "ended",
+            "last day",
+            "ends tomorrow",
+            "2 days left",
+            "3 days left",
+            "4 days left"
+        ]
+    );
+}
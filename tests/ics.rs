@@ -0,0 +1,57 @@
+use chrono::Weekday;
+use obsidian_calendar_summary::{event::Event, ics::parse_ics};
+
+fn vevent(rrule: &str) -> String {
+    format!(
+        "BEGIN:VCALENDAR\nBEGIN:VEVENT\nSUMMARY:Club\nDTSTART:20250106T193000\n\
+         DTEND:20250106T213000\nRRULE:{}\nEND:VEVENT\nEND:VCALENDAR\n",
+        rrule
+    )
+}
+
+/// The days and week of the month of the recurring event in the ICS, if it could be read
+fn recurrence(rrule: &str) -> Option<(Vec<Weekday>, Option<i8>)> {
+    match parse_ics(&vevent(rrule)).pop()? {
+        Event::Recurring {
+            recur_days,
+            week_of_month,
+            ..
+        } => Some((recur_days, week_of_month)),
+        _ => None,
+    }
+}
+
+#[test]
+fn monthly_byday() {
+    assert_eq!(
+        recurrence("FREQ=MONTHLY;BYDAY=1MO"),
+        Some((vec![Weekday::Mon], Some(1)))
+    );
+    assert_eq!(
+        recurrence("FREQ=MONTHLY;BYDAY=-1FR"),
+        Some((vec![Weekday::Fri], Some(-1)))
+    );
+    assert_eq!(
+        recurrence("FREQ=MONTHLY;BYDAY=+2TU,+2TH"),
+        Some((vec![Weekday::Tue, Weekday::Thu], Some(2)))
+    );
+    assert_eq!(
+        recurrence("FREQ=WEEKLY;BYDAY=MO,WE"),
+        Some((vec![Weekday::Mon, Weekday::Wed], None))
+    );
+}
+
+#[test]
+fn unsupported_monthly() {
+    // different weeks for different days, every day of the month, a day of the month, and an
+    // ordinal in a weekly rule
+    for rrule in [
+        "FREQ=MONTHLY;BYDAY=1MO,3MO",
+        "FREQ=MONTHLY;BYDAY=MO",
+        "FREQ=MONTHLY;BYMONTHDAY=6",
+        "FREQ=MONTHLY;BYDAY=6MO",
+        "FREQ=WEEKLY;BYDAY=1MO",
+    ] {
+        assert_eq!(recurrence(rrule), None, "{}", rrule);
+    }
+}
@@ -0,0 +1,117 @@
+//! Leaving notes out with `--ignore` and the config file's `ignore` list, in
+//! `tests/fixtures/ignored`
+
+use obsidian_calendar_summary::{
+    glob::Glob,
+    parse::{parse_events, AllDayEnd, WeekdayLocale},
+};
+use std::path::Path;
+
+fn matches(pattern: &str, path: &str) -> bool {
+    Glob::new(pattern).unwrap().matches(Path::new(path))
+}
+
+#[test]
+fn patterns() {
+    for (pattern, path, expected) in [
+        ("*.archive.md", "Old.archive.md", true),
+        // names are matched in any folder
+        ("*.archive.md", "projects/notes/Old.archive.md", true),
+        ("*.archive.md", "Old.md", false),
+        ("_template*.md", "_template meeting.md", true),
+        ("_template*.md", "meeting_template.md", false),
+        ("?.md", "a.md", true),
+        ("?.md", "ab.md", false),
+        // and so are folders, which leaves out everything in them
+        ("archive", "archive/2023/Retro.md", true),
+        ("archive/", "old/archive/Retro.md", true),
+        ("archive", "archived/Retro.md", false),
+        // with a slash, the whole path has to match
+        ("projects/*.md", "projects/Kickoff.md", true),
+        ("projects/*.md", "projects/notes/Kickoff.md", false),
+        ("projects/*.md", "other/projects/Kickoff.md", false),
+        ("/Standup.md", "Standup.md", true),
+        ("/Standup.md", "projects/Standup.md", false),
+        ("projects/notes", "projects/notes/Design.md", true),
+        // `**` is any number of folders, including none
+        (
+            "projects/**/*.archive.md",
+            "projects/Kickoff.archive.md",
+            true,
+        ),
+        (
+            "projects/**/*.archive.md",
+            "projects/notes/old/Kickoff.archive.md",
+            true,
+        ),
+        ("projects/**/*.archive.md", "Kickoff.archive.md", false),
+        ("**/2023", "archive/2023/Retro.md", true),
+        ("archive/**", "archive/2023/Retro.md", true),
+    ] {
+        assert_eq!(matches(pattern, path), expected, "{} {}", pattern, path);
+    }
+    for pattern in ["", "/", "a//b"] {
+        assert!(Glob::new(pattern).is_err(), "{}", pattern);
+    }
+}
+
+fn titles(ignore: &[&str]) -> Vec<String> {
+    let ignore: Vec<Glob> = ignore
+        .iter()
+        .map(|pattern| Glob::new(pattern).unwrap())
+        .collect();
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/ignored");
+    let (events, problems) = parse_events(
+        dir,
+        WeekdayLocale::English,
+        AllDayEnd::Exclusive,
+        true,
+        false,
+        &ignore,
+        None,
+        false,
+        true,
+    )
+    .unwrap();
+    // the template's only a problem when it's read
+    let mut titles: Vec<String> = problems
+        .iter()
+        .map(|problem| format!("error: {}", problem.code()))
+        .collect();
+    titles.extend(
+        events
+            .iter()
+            .map(|sourced| sourced.event.title().to_string()),
+    );
+    titles
+}
+
+#[test]
+fn directories() {
+    assert_eq!(
+        titles(&[]),
+        [
+            "error: placeholder",
+            "Old standup",
+            "Standup",
+            "Retro",
+            "Kickoff",
+            "Design notes",
+            "Old kickoff"
+        ]
+    );
+    assert_eq!(
+        titles(&["*.archive.md", "_template*.md", "archive"]),
+        ["Standup", "Kickoff", "Design notes"]
+    );
+    assert_eq!(
+        titles(&["projects/notes/**"]),
+        [
+            "error: placeholder",
+            "Old standup",
+            "Standup",
+            "Retro",
+            "Kickoff"
+        ]
+    );
+}
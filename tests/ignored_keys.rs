@@ -0,0 +1,70 @@
+//! Keys which the kind of event doesn't use, like a `daysOfWeek` left on a single event by a
+//! template, are warned about rather than silently ignored
+
+use obsidian_calendar_summary::{
+    event::Event,
+    parse::{parse_cal_file_with_warnings, AllDayEnd, WeekdayLocale},
+};
+use std::path::Path;
+
+/// The event in the note and its warnings
+fn parse(frontmatter: &str) -> (Event, Vec<String>) {
+    let (mut events, warnings) = parse_cal_file_with_warnings(
+        &format!("---\n{}---\n", frontmatter),
+        Path::new("note.md"),
+        WeekdayLocale::English,
+        AllDayEnd::Exclusive,
+        None,
+        false,
+        true,
+    )
+    .unwrap_or_else(|e| panic!("{}", e));
+    for warning in &warnings {
+        assert_eq!(warning.code(), "ignored_key");
+        assert!(warning.is_warning());
+    }
+    (
+        events.remove(0),
+        warnings.iter().map(ToString::to_string).collect(),
+    )
+}
+
+#[test]
+fn single_with_recurrence_keys() {
+    // without a `type`, the leftover days don't make it recurring
+    let (event, warnings) = parse(
+        "title: Dentist\ndate: 2024-05-08\nstartTime: 14:00\nendTime: 15:00\ndaysOfWeek: [W]\nendRecur: 2024-06-01\n",
+    );
+    assert!(matches!(event, Event::Once { .. }));
+    assert_eq!(
+        warnings,
+        [
+            "note.md: 'daysOfWeek': only used by recurring events, so it's ignored",
+            "note.md: 'endRecur': only used by recurring events, so it's ignored",
+        ]
+    );
+}
+
+#[test]
+fn recurring_with_single_keys() {
+    let (event, warnings) = parse(
+        "title: Gym\ntype: recurring\ndate: 2024-05-08\ndaysOfWeek: [M, W]\nstartTime: 18:00\nendTime: 19:00\n",
+    );
+    assert!(matches!(event, Event::Recurring { .. }));
+    assert_eq!(
+        warnings,
+        ["note.md: 'date': only used by single events, so it's ignored"]
+    );
+}
+
+#[test]
+fn no_leftovers() {
+    for frontmatter in [
+        "title: Dentist\ndate: 2024-05-08\nstartTime: 14:00\nendTime: 15:00\n",
+        "title: Gym\ntype: recurring\ndaysOfWeek: [M]\nskipHolidays: true\nstartTime: 18:00\nendTime: 19:00\n",
+        // all-day events use `date` too, and nothing is checked for them
+        "title: Holiday\nallDay: true\ndate: 2024-05-08\ndaysOfWeek: [M]\n",
+    ] {
+        assert_eq!(parse(frontmatter).1, Vec::<String>::new(), "{}", frontmatter);
+    }
+}
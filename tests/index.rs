@@ -0,0 +1,100 @@
+use obsidian_calendar_summary::index::{resolve_link, vault_root, wiki_links};
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+fn notes() -> Vec<PathBuf> {
+    [
+        "Calendar.md",
+        "Standup.md",
+        "archive/2023/Retro.md",
+        "meetings/Retro.md",
+        "meetings/Standup.md",
+        "people/Sam.md",
+        "projects/b/Kickoff.md",
+        "projects/a/Kickoff.md",
+    ]
+    .iter()
+    .map(PathBuf::from)
+    .collect()
+}
+
+fn resolve(link: &str) -> Option<(String, usize)> {
+    let notes = notes();
+    resolve_link(&notes, link).map(|(note, matches)| (note.display().to_string(), matches))
+}
+
+#[test]
+fn links() {
+    let index = "- [[meetings/standup]]\n- [[Sam|1:1 with Sam]] and [[Sam#Agenda]]\n\
+                 - ![[Retro^block]] [[ ]] [[unclosed";
+    assert_eq!(wiki_links(index), ["meetings/standup", "Sam", "Retro"]);
+}
+
+#[test]
+fn with_and_without_md() {
+    assert_eq!(resolve("people/Sam"), Some(("people/Sam.md".into(), 1)));
+    assert_eq!(resolve("people/Sam.md"), Some(("people/Sam.md".into(), 1)));
+    assert_eq!(resolve("Sam.md"), Some(("people/Sam.md".into(), 1)));
+}
+
+#[test]
+fn paths_are_from_the_root() {
+    assert_eq!(
+        resolve("archive/2023/Retro"),
+        Some(("archive/2023/Retro.md".into(), 1))
+    );
+    // not a suffix match, and not relative to the index
+    assert_eq!(resolve("2023/Retro"), None);
+    assert_eq!(resolve("meetings/Sam"), None);
+}
+
+#[test]
+fn duplicate_names() {
+    // the one in the fewest folders wins
+    assert_eq!(resolve("Standup"), Some(("Standup.md".into(), 2)));
+    assert_eq!(resolve("Retro"), Some(("meetings/Retro.md".into(), 2)));
+    // then the first by path
+    assert_eq!(
+        resolve("Kickoff"),
+        Some(("projects/a/Kickoff.md".into(), 2))
+    );
+    assert_eq!(resolve("Cancelled"), None);
+}
+
+#[test]
+fn root() {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    assert_eq!(
+        vault_root(&fixtures.join("indexed/Home/Calendar.md")),
+        fixtures.join("indexed")
+    );
+    // without an .obsidian folder, the index's own folder
+    assert_eq!(
+        vault_root(&fixtures.join("vault/Standup.md")),
+        fixtures.join("vault")
+    );
+}
+
+#[test]
+fn link_warnings() {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let output = Command::new(env!("CARGO_BIN_EXE_obsidian-calendar-summary"))
+        .args(["--now", "2024-05-12T08:00", "lint", "--porcelain"])
+        .args(["--index", "indexed/Home/Calendar.md"])
+        .current_dir(&fixtures)
+        .env("XDG_CONFIG_HOME", &fixtures)
+        .env("XDG_STATE_HOME", &fixtures)
+        .output()
+        .unwrap();
+    // they're only warnings, so they don't fail a pre-commit hook
+    assert!(output.status.success(), "exited with {}", output.status);
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "indexed/Home/Calendar.md\twarning\tambiguous_link\t\
+         indexed/Home/Calendar.md: [[Retro]] could be any of 2 notes, using meetings/Retro.md\n\
+         indexed/Home/Calendar.md\twarning\tdangling_link\t\
+         indexed/Home/Calendar.md: [[Cancelled meeting]] doesn't lead to a note\n"
+    );
+}
@@ -0,0 +1,107 @@
+//! `--json-lines`: one JSON object per event per line, for readers of logs, with `--summary`
+//! marking where each run's events end
+
+use std::{
+    path::Path,
+    process::{Command, Output},
+};
+
+fn output(args: &[&str]) -> Output {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    Command::new(env!("CARGO_BIN_EXE_obsidian-calendar-summary"))
+        .args(args)
+        .args(["--errors", "json", "vault"])
+        .current_dir(&fixtures)
+        .env("XDG_CONFIG_HOME", &fixtures)
+        .env("XDG_STATE_HOME", &fixtures)
+        .output()
+        .unwrap()
+}
+
+fn run(now: &str, args: &[&str]) -> String {
+    let args = [&["--format", "json", "--json-lines", "--now", now], args].concat();
+    String::from_utf8(output(&args).stdout).unwrap()
+}
+
+fn titles(lines: &str) -> Vec<&str> {
+    lines
+        .lines()
+        .map(|line| {
+            assert!(line.starts_with('{') && line.ends_with('}'), "{}", line);
+            line.split_once(r#""title":""#)
+                .map_or("-", |(_, rest)| rest.split('"').next().unwrap())
+        })
+        .collect()
+}
+
+#[test]
+fn one_per_line() {
+    let lines = run("2024-05-08T10:00", &[]);
+    assert_eq!(titles(&lines), ["Conference", "Standup", "Dentist", "Gym"]);
+    // the same objects as in the array
+    let array = run("2024-05-08T10:00", &[]).replace('\n', ",");
+    let output = output(&["--format", "json", "--now", "2024-05-08T10:00"]);
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        format!("[{}]\n", array.trim_end_matches(','))
+    );
+    // nothing at all when there are no events
+    assert_eq!(run("2024-05-08T23:00", &["--no-allday"]), "");
+}
+
+/// The titles of the events in each batch, checking that each ends with a summary of them
+fn batches(log: &str) -> Vec<Vec<&str>> {
+    let mut batches = Vec::new();
+    let mut batch = Vec::new();
+    for line in log.lines() {
+        match line.strip_prefix(r#"{"summary":true,"count":"#) {
+            Some(rest) => {
+                let count: usize = rest.split(',').next().unwrap().parse().unwrap();
+                assert_eq!(count, batch.len());
+                batches.push(std::mem::take(&mut batch));
+            }
+            None => batch.extend(titles(line)),
+        }
+    }
+    assert!(batch.is_empty(), "no summary after {:?}", batch);
+    batches
+}
+
+#[test]
+fn refreshes() {
+    // two runs appended to the same log, as a loop refreshing it would
+    let log = run("2024-05-08T10:00", &["--summary"]) + &run("2024-05-08T16:00", &["--summary"]);
+    assert_eq!(
+        batches(&log),
+        [
+            vec!["Conference", "Standup", "Dentist", "Gym"],
+            vec!["Conference", "Gym"]
+        ]
+    );
+    assert!(
+        log.ends_with("{\"summary\":true,\"count\":2,\"generated_at\":\"2024-05-08T16:00:00\"}\n")
+    );
+    // the summary is there even with no events, so each refresh is still marked
+    assert_eq!(
+        run("2024-05-08T23:00", &["--no-allday", "--summary"]),
+        "{\"summary\":true,\"count\":0,\"generated_at\":\"2024-05-08T23:00:00\"}\n"
+    );
+}
+
+#[test]
+fn only_with_json() {
+    for (args, message) in [
+        (
+            &["--json-lines"][..],
+            "'--json-lines' only works with '--format json'",
+        ),
+        (
+            &["--format", "json", "--summary"],
+            "'--summary' only works with '--json-lines'",
+        ),
+    ] {
+        let output = output(args);
+        assert_eq!(output.status.code(), Some(1));
+        assert!(String::from_utf8(output.stderr).unwrap().contains(message));
+    }
+}
@@ -0,0 +1,55 @@
+//! The words the output is written in, and overriding them in the config file
+
+use obsidian_calendar_summary::{config::Config, lang::Lang};
+
+#[test]
+fn plurals() {
+    let lang = Lang::english();
+    assert_eq!(lang.minutes.format(1), "1 min");
+    assert_eq!(lang.minutes.format(0), "0 mins");
+    assert_eq!(lang.days_left.format(2), "2 days left");
+    let lang = Lang::german();
+    assert_eq!(lang.days.format(1), "1 Tag");
+    assert_eq!(lang.days.format(3), "3 Tage");
+}
+
+#[test]
+fn overrides() {
+    let config = Config::parse(
+        "[strings]\nnow = \"Läuft\"\nminutes = [\"{n} Minute\", \"{n} Minuten\"]\nweekdays = [\"Mo\", \"Di\", \"Mi\", \"Do\", \"Fr\", \"Sa\", \"So\"]\n",
+    )
+    .unwrap();
+    let lang = Lang::english().with_overrides(&config).unwrap();
+    assert_eq!(lang.now, "Läuft");
+    assert_eq!(lang.minutes.format(1), "1 Minute");
+    assert_eq!(lang.weekday(chrono::Weekday::Tue), "Di");
+    // the rest are left alone
+    assert_eq!(lang.today, "Today");
+}
+
+#[test]
+fn bad_overrides() {
+    for (config, message) in [
+        (
+            "[strings]\nnoww = \"Jetzt\"\n",
+            "Unknown config key 'strings.noww'",
+        ),
+        (
+            "[strings]\nminutes = \"{n} Minuten\"\n",
+            "Config key 'strings.minutes' should be a list of 2 strings",
+        ),
+        (
+            "[strings]\nmonths = [\"Jan\"]\n",
+            "Config key 'strings.months' should be a list of 12 strings",
+        ),
+        (
+            "[strings]\ntoday = true\n",
+            "Config key 'strings.today' should be a string",
+        ),
+    ] {
+        let error = Lang::english()
+            .with_overrides(&Config::parse(config).unwrap())
+            .unwrap_err();
+        assert!(error.to_string().contains(message), "{}", error);
+    }
+}
@@ -0,0 +1,60 @@
+//! Frontmatter which is broken in ways that once made the parser panic, which must be errors
+//! instead
+
+use obsidian_calendar_summary::parse::{parse_cal_file, AllDayEnd, WeekdayLocale};
+use std::path::Path;
+
+/// The error for a recurring event with `days` as its `daysOfWeek`
+fn error(days: &str) -> String {
+    let note = format!(
+        "---\ntitle: Gym\ntype: recurring\ndaysOfWeek: {}\nstartTime: 18:00\nendTime: 19:00\n---\n",
+        days
+    );
+    match parse_cal_file(
+        &note,
+        Path::new("note.md"),
+        WeekdayLocale::English,
+        AllDayEnd::Exclusive,
+    ) {
+        Ok(events) => panic!("{} was read as {:?}", days, events),
+        Err(e) => e.to_string(),
+    }
+}
+
+#[test]
+fn brackets_the_wrong_way_round() {
+    assert_eq!(
+        error("] oops ["),
+        "note.md: 'daysOfWeek': ] comes before the opening [ of the list"
+    );
+    assert_eq!(
+        error("]["),
+        "note.md: 'daysOfWeek': ] comes before the opening [ of the list"
+    );
+}
+
+#[test]
+fn unbalanced_brackets() {
+    assert_eq!(
+        error("[M, W"),
+        "note.md: 'daysOfWeek': cannot find closing ] on list"
+    );
+    assert_eq!(
+        error("M, W]"),
+        "note.md: 'daysOfWeek': cannot find opening [ on list"
+    );
+}
+
+#[test]
+fn multibyte_next_to_brackets() {
+    assert_eq!(
+        error("é]é[é"),
+        "note.md: 'daysOfWeek': ] comes before the opening [ of the list"
+    );
+    assert_eq!(
+        error("ü[ü"),
+        "note.md: 'daysOfWeek': cannot find closing ] on list"
+    );
+    assert!(error("[ü]ü").contains("unknown weekday 'ü'"));
+    assert!(error("[M,😀]").contains("unknown weekday '😀'"));
+}
@@ -0,0 +1,107 @@
+//! Hiding short timed events, like zero-length markers, with `--min-duration`
+
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use obsidian_calendar_summary::{
+    calendar::Calendar,
+    event::{Event, SourcedEvent},
+    focus::free_gaps,
+    query::EventQuery,
+};
+
+fn date() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 5, 8).unwrap()
+}
+
+fn at(time: &str) -> NaiveDateTime {
+    date().and_time(NaiveTime::parse_from_str(time, "%H:%M:%S").unwrap())
+}
+
+fn sourced(title: &str, event: Event) -> SourcedEvent {
+    SourcedEvent::new(event, format!("{}.md", title))
+}
+
+fn timed(title: &str, begin: &str, end: &str) -> SourcedEvent {
+    sourced(
+        title,
+        Event::Once {
+            title: title.into(),
+            begin: at(begin).time(),
+            end: at(end).time(),
+            day: date(),
+            travel_minutes: None,
+            actual_end: None,
+        },
+    )
+}
+
+fn calendar() -> Calendar {
+    Calendar {
+        events: vec![
+            timed("Marker", "12:00:00", "12:00:00"),
+            timed("Ping", "12:30:00", "12:30:30"),
+            timed("Minute", "13:00:00", "13:01:00"),
+            timed("Meeting", "14:00:00", "15:00:00"),
+            sourced(
+                "Conference",
+                Event::AllDay {
+                    title: "Conference".into(),
+                    begin_date: date(),
+                    end_date: date().succ_opt().unwrap(),
+                    suppress_recurring: false,
+                },
+            ),
+        ],
+        holidays: Vec::new(),
+        problems: Vec::new(),
+    }
+}
+
+fn titles(min_duration: Option<Duration>) -> Vec<String> {
+    let calendar = calendar();
+    let query = EventQuery::today(at("08:00:00"));
+    let query = match min_duration {
+        Some(min_duration) => query.min_duration(min_duration),
+        None => query,
+    };
+    query
+        .apply(&calendar)
+        .iter()
+        .map(|sourced| sourced.event.title().to_string())
+        .collect()
+}
+
+#[test]
+fn threshold() {
+    let everything = ["Conference", "Marker", "Ping", "Minute", "Meeting"];
+    assert_eq!(titles(None), everything);
+    assert_eq!(titles(Some(Duration::zero())), everything);
+    // an event exactly as long as the minimum stays
+    assert_eq!(
+        titles(Some(Duration::try_minutes(1).unwrap())),
+        ["Conference", "Minute", "Meeting"]
+    );
+    assert_eq!(
+        titles(Some(Duration::try_seconds(30).unwrap())),
+        ["Conference", "Ping", "Minute", "Meeting"]
+    );
+    // all-day events are never too short
+    assert_eq!(
+        titles(Some(Duration::try_hours(2).unwrap())),
+        ["Conference"]
+    );
+}
+
+#[test]
+fn not_busy() {
+    let calendar = calendar();
+    let events = EventQuery::today(at("08:00:00"))
+        .min_duration(Duration::try_minutes(5).unwrap())
+        .apply(&calendar);
+    assert_eq!(
+        free_gaps(&events, date(), at("11:00:00"), at("16:00:00")),
+        [
+            (at("11:00:00"), at("14:00:00")),
+            (at("15:00:00"), at("16:00:00"))
+        ]
+    );
+}
@@ -0,0 +1,127 @@
+//! Numbering the occurrences of a recurring event after its title, like "Algorithms (Lecture 7)",
+//! from the note's `numberOccurrences`
+
+use chrono::NaiveDate;
+use obsidian_calendar_summary::{
+    calendar::Calendar,
+    event::{Event, SourcedEvent},
+    parse::{parse_events, AllDayEnd, WeekdayLocale},
+};
+use std::{path::Path, process::Command};
+
+fn date(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 4, day).unwrap()
+}
+
+/// The lectures on Tuesdays and Thursdays from the 2nd, with reading week from the 15th to the
+/// 19th, and the holidays on `holidays`
+fn calendar(holidays: &[u32]) -> Calendar {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/lectures");
+    let (mut events, problems) = parse_events(
+        dir,
+        WeekdayLocale::English,
+        AllDayEnd::Exclusive,
+        false,
+        false,
+        &[],
+        None,
+        false,
+        true,
+    )
+    .unwrap();
+    assert!(problems.is_empty(), "{:?}", problems);
+    events.sort_by(|a, b| a.event.title().cmp(b.event.title()));
+    Calendar {
+        events,
+        holidays: holidays
+            .iter()
+            .map(|day| {
+                SourcedEvent::new(
+                    Event::AllDay {
+                        title: "Holiday".into(),
+                        begin_date: date(*day),
+                        end_date: date(*day + 1),
+                        suppress_recurring: false,
+                    },
+                    "holidays.ics",
+                )
+            })
+            .collect(),
+        problems: Vec::new(),
+    }
+}
+
+/// The numbers of the lectures on `days`
+fn numbers(calendar: &Calendar, days: &[u32]) -> Vec<Option<usize>> {
+    let lectures = &calendar.events[0];
+    assert_eq!(lectures.numbered.as_deref(), Some("Lecture"));
+    days.iter()
+        .map(|day| calendar.occurrence_number(&lectures.event, date(*day), false))
+        .collect()
+}
+
+#[test]
+fn counted_from_the_start() {
+    let calendar = calendar(&[]);
+    assert_eq!(
+        numbers(&calendar, &[2, 4, 9, 11]),
+        [Some(1), Some(2), Some(3), Some(4)]
+    );
+    // not on the Wednesday, and before it starts
+    assert_eq!(numbers(&calendar, &[3]), [None]);
+    assert_eq!(
+        calendar.occurrence_number(
+            &calendar.events[0].event,
+            NaiveDate::from_ymd_opt(2024, 3, 26).unwrap(),
+            false
+        ),
+        None
+    );
+}
+
+#[test]
+fn cancelled_ones_dont_count() {
+    // there are none in reading week, so the Tuesday after it is the fifth
+    let calendar = calendar(&[]);
+    assert_eq!(numbers(&calendar, &[16, 18]), [None, None]);
+    assert_eq!(numbers(&calendar, &[23, 25]), [Some(5), Some(6)]);
+    // a holiday on the 9th moves everything after it back one
+    let calendar = self::calendar(&[9]);
+    assert_eq!(numbers(&calendar, &[4, 9, 11]), [Some(2), None, Some(3)]);
+    assert_eq!(numbers(&calendar, &[23, 25]), [Some(4), Some(5)]);
+}
+
+#[test]
+fn only_numbered_notes() {
+    let calendar = calendar(&[]);
+    assert_eq!(calendar.events[1].event.title(), "Office hours");
+    assert_eq!(calendar.events[1].numbered, None);
+}
+
+fn run(now: &str, args: &[&str]) -> String {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let output = Command::new(env!("CARGO_BIN_EXE_obsidian-calendar-summary"))
+        .args(args)
+        .args(["--now", now, "lectures"])
+        .current_dir(&fixtures)
+        .env("XDG_CONFIG_HOME", &fixtures)
+        .env("XDG_STATE_HOME", &fixtures)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "exited with {}", output.status);
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn shown_after_the_title() {
+    assert_eq!(
+        run("2024-04-23T09:40", &[]),
+        "10:00 - 11:00 (20 mins)  | Algorithms (Lecture 5)\n"
+    );
+    assert!(run("2024-04-23T09:40", &["--format", "json"]).contains(r#""title":"Algorithms","#));
+    assert!(run("2024-04-23T09:40", &["--format", "json"]).contains(r#""numbered":"Lecture 5","#));
+    assert_eq!(
+        run("2024-04-24T14:40", &[]),
+        "15:00 - 16:00 (20 mins)  | Office hours\n"
+    );
+}
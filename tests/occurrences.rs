@@ -0,0 +1,183 @@
+//! Every occurrence over a range of days in order, merged from each event's own occurrences
+//! rather than collected and sorted
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use obsidian_calendar_summary::{
+    calendar::{Calendar, Occurrences},
+    event::{Event, SourcedEvent},
+};
+
+fn date(month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, month, day).unwrap()
+}
+
+fn time(hour: u32, minute: u32) -> NaiveTime {
+    NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+}
+
+fn sourced(event: Event) -> SourcedEvent {
+    let path = format!("{}.md", event.title());
+    SourcedEvent::new(event, path)
+}
+
+fn recurring(title: &str, begin: NaiveTime, days: &[Weekday]) -> Event {
+    Event::Recurring {
+        title: title.into(),
+        begin,
+        end: begin + chrono::Duration::try_minutes(30).unwrap(),
+        begin_recur: Some(date(5, 1)),
+        end_recur: None,
+        recur_days: days.to_vec(),
+        skip_holidays: false,
+        travel_minutes: None,
+        week_of_month: None,
+    }
+}
+
+/// Events of every kind, with some starting at the same time
+fn calendar() -> Calendar {
+    use Weekday::*;
+    let events = [
+        recurring("Standup", time(9, 0), &[Mon, Tue, Wed, Thu, Fri]),
+        recurring("Gym", time(18, 0), &[Mon, Thu]),
+        // at the same time as the Standup, and before it by title
+        recurring("Coffee", time(9, 0), &[Wed]),
+        // exactly the same as the Gym, so only the order they were read tells them apart
+        recurring("Gym", time(18, 0), &[Thu]),
+        Event::Once {
+            title: "Dentist".into(),
+            begin: time(14, 0),
+            end: time(15, 0),
+            day: date(5, 8),
+            travel_minutes: None,
+            actual_end: None,
+        },
+        Event::AllDay {
+            title: "Conference".into(),
+            begin_date: date(5, 7),
+            end_date: date(5, 10),
+            suppress_recurring: false,
+        },
+        Event::Deadline {
+            title: "Tax return".into(),
+            due: date(5, 9),
+            completed: false,
+        },
+    ];
+    Calendar {
+        events: events.into_iter().map(sourced).collect(),
+        holidays: Vec::new(),
+        problems: Vec::new(),
+    }
+}
+
+fn begins_at(sourced: &SourcedEvent, date: NaiveDate) -> NaiveDateTime {
+    sourced
+        .event
+        .times_on(date)
+        .map_or(date.and_time(NaiveTime::MIN), |(begin, _)| begin)
+}
+
+/// What the merge should give: everything collected, then sorted by when it begins and by title,
+/// keeping the order the events were read in for the rest
+fn collected(
+    calendar: &Calendar,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Vec<(String, NaiveDateTime, usize)> {
+    let mut all: Vec<(String, NaiveDateTime, usize)> = calendar
+        .events
+        .iter()
+        .enumerate()
+        .flat_map(|(i, sourced)| {
+            sourced.event.occurrences(from, to).map(move |date| {
+                (
+                    sourced.event.title().to_string(),
+                    begins_at(sourced, date),
+                    i,
+                )
+            })
+        })
+        .collect();
+    all.sort_by(|a, b| (a.1, &a.0).cmp(&(b.1, &b.0)));
+    all
+}
+
+fn merged(
+    calendar: &Calendar,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Vec<(String, NaiveDateTime, usize)> {
+    calendar
+        .occurrences(from, to)
+        .map(|(sourced, date)| {
+            let i = calendar
+                .events
+                .iter()
+                .position(|event| std::ptr::eq(event, sourced))
+                .unwrap();
+            (
+                sourced.event.title().to_string(),
+                begins_at(sourced, date),
+                i,
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn same_as_sorting() {
+    let calendar = calendar();
+    for (from, to) in [
+        (date(5, 6), date(5, 12)),
+        (date(5, 1), date(12, 31)),
+        (date(4, 1), date(5, 3)),
+        (date(5, 9), date(5, 9)),
+    ] {
+        assert_eq!(merged(&calendar, from, to), collected(&calendar, from, to));
+    }
+}
+
+#[test]
+fn in_order() {
+    let calendar = calendar();
+    let titles: Vec<String> = calendar
+        .occurrences(date(5, 8), date(5, 9))
+        .map(|(sourced, date)| format!("{} {}", date.format("%a"), sourced.event.title()))
+        .collect();
+    assert_eq!(
+        titles,
+        [
+            "Wed Conference",
+            "Wed Coffee",
+            "Wed Standup",
+            "Wed Dentist",
+            "Thu Conference",
+            "Thu Tax return",
+            "Thu Standup",
+            "Thu Gym",
+            "Thu Gym",
+        ]
+    );
+}
+
+#[test]
+fn nothing() {
+    let calendar = calendar();
+    // the range is before everything, or the wrong way round
+    assert_eq!(calendar.occurrences(date(4, 1), date(4, 30)).count(), 0);
+    assert_eq!(calendar.occurrences(date(5, 9), date(5, 8)).count(), 0);
+    assert_eq!(Occurrences::new(&[], date(5, 1), date(5, 31)).count(), 0);
+}
+
+#[test]
+fn forever() {
+    // recurring events without an end can be asked about any range, and only as much of it as is
+    // used is looked at. The 1st is a Wednesday.
+    let calendar = calendar();
+    let first = calendar
+        .occurrences(date(5, 1), NaiveDate::MAX)
+        .next()
+        .unwrap();
+    assert_eq!((first.0.event.title(), first.1), ("Coffee", date(5, 1)));
+}
@@ -0,0 +1,217 @@
+//! `open-next`: opening the link of the meeting which is on, or of the next one
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use obsidian_calendar_summary::{
+    cli::SortBy,
+    error::Error,
+    event::{Event, SourcedEvent},
+    lang::Lang,
+    open::{open_next, Launcher},
+    render::Fields,
+};
+use std::{path::Path, process::Command};
+
+/// Remembers what it was asked to open instead of opening it, or fails like a missing `xdg-open`
+#[derive(Default)]
+struct Recorder {
+    opened: Vec<String>,
+    broken: bool,
+}
+
+impl Launcher for Recorder {
+    fn launch(&mut self, url: &str) -> Result<(), Error> {
+        if self.broken {
+            return Err(Error::Open("Cannot run xdg-open".into()));
+        }
+        self.opened.push(url.into());
+        Ok(())
+    }
+}
+
+fn date() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 5, 8).unwrap()
+}
+
+fn time(time: &str) -> NaiveTime {
+    NaiveTime::parse_from_str(time, "%H:%M").unwrap()
+}
+
+fn meeting(title: &str, begin: &str, end: &str, url: Option<&str>) -> SourcedEvent {
+    SourcedEvent {
+        url: url.map(Into::into),
+        ..SourcedEvent::new(
+            Event::Once {
+                title: title.into(),
+                begin: time(begin),
+                end: time(end),
+                day: date(),
+                travel_minutes: None,
+                actual_end: None,
+            },
+            format!("{}.md", title),
+        )
+    }
+}
+
+/// What `open-next` says at `now`, opening the link with `launcher`
+fn open_with(
+    events: &[SourcedEvent],
+    now: &str,
+    dry_run: bool,
+    launcher: &mut Recorder,
+) -> Result<Option<String>, Error> {
+    let now = NaiveDateTime::new(date(), time(now));
+    let lang = Lang::english();
+    let fields: Vec<Fields> = events
+        .iter()
+        .map(|event| {
+            Fields::new(
+                event,
+                date(),
+                now,
+                SortBy::Start,
+                &lang,
+                false,
+                chrono::Duration::zero(),
+            )
+        })
+        .collect();
+    open_next(&fields, launcher, dry_run)
+}
+
+/// What `open-next` says at `now`, and what it opened
+fn open(
+    events: &[SourcedEvent],
+    now: &str,
+    dry_run: bool,
+) -> (Result<Option<String>, Error>, Vec<String>) {
+    let mut recorder = Recorder::default();
+    let done = open_with(events, now, dry_run, &mut recorder);
+    (done, recorder.opened)
+}
+
+fn events() -> Vec<SourcedEvent> {
+    vec![
+        meeting(
+            "Standup",
+            "09:00",
+            "09:15",
+            Some("https://meet.example.com/standup"),
+        ),
+        meeting("Team lunch", "10:30", "13:00", None),
+        meeting(
+            "Planning",
+            "11:00",
+            "12:00",
+            Some("https://zoom.example.com/j/123"),
+        ),
+        meeting(
+            "Review",
+            "11:30",
+            "12:30",
+            Some("https://meet.example.com/review"),
+        ),
+    ]
+}
+
+#[test]
+fn ongoing_then_nearest() {
+    let (done, opened) = open(&events(), "09:05", false);
+    assert_eq!(
+        done.unwrap().as_deref(),
+        Some("Opened https://meet.example.com/standup for Standup")
+    );
+    assert_eq!(opened, ["https://meet.example.com/standup"]);
+    // the lunch is on but has no link, so it's the next meeting
+    let (done, _) = open(&events(), "10:40", false);
+    assert_eq!(
+        done.unwrap().as_deref(),
+        Some("Opened https://zoom.example.com/j/123 for Planning")
+    );
+    // once Review is on too, Planning still started first
+    let (done, _) = open(&events(), "11:45", false);
+    assert!(done.unwrap().unwrap().ends_with("for Planning"));
+}
+
+#[test]
+fn ongoing_beats_upcoming_in_any_order() {
+    // the list can be sorted differently, e.g. by `--sort end` or `--reverse`
+    let mut events = events();
+    events.reverse();
+    let (done, _) = open(&events, "11:15", false);
+    assert!(done.unwrap().unwrap().ends_with("for Planning"));
+    let (done, _) = open(&events, "08:00", false);
+    assert!(done.unwrap().unwrap().ends_with("for Standup"));
+}
+
+#[test]
+fn dry_run() {
+    let (done, opened) = open(&events(), "10:40", true);
+    assert_eq!(
+        done.unwrap().as_deref(),
+        Some("https://zoom.example.com/j/123")
+    );
+    assert!(opened.is_empty());
+}
+
+#[test]
+fn nothing_to_open() {
+    let (done, opened) = open(&events(), "12:45", false);
+    assert_eq!(done.unwrap(), None);
+    assert!(opened.is_empty());
+    let (done, _) = open(
+        &[meeting("Team lunch", "10:30", "13:00", None)],
+        "09:00",
+        false,
+    );
+    assert_eq!(done.unwrap(), None);
+}
+
+#[test]
+fn launcher_fails() {
+    let mut recorder = Recorder {
+        broken: true,
+        ..Recorder::default()
+    };
+    let error = open_with(&events(), "09:05", false, &mut recorder).unwrap_err();
+    assert_eq!(error.code(), "open");
+}
+
+fn run(now: &str, args: &[&str]) -> (String, Option<i32>) {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let output = Command::new(env!("CARGO_BIN_EXE_obsidian-calendar-summary"))
+        .args(args)
+        .args(["--now", now, "meetings"])
+        .current_dir(&fixtures)
+        .env("XDG_CONFIG_HOME", &fixtures)
+        .env("XDG_STATE_HOME", &fixtures)
+        .output()
+        .unwrap();
+    (
+        String::from_utf8(output.stdout).unwrap(),
+        output.status.code(),
+    )
+}
+
+#[test]
+fn from_notes() {
+    // the `url` in the note, without its quotes or comment
+    assert_eq!(
+        run("2024-05-08T10:40", &["open-next", "--dry-run"]),
+        (
+            "https://zoom.example.com/j/123?pwd=abc#success\n".into(),
+            Some(0)
+        )
+    );
+    assert_eq!(
+        run("2024-05-08T12:30", &["open-next", "--dry-run"]),
+        (String::new(), Some(1))
+    );
+    assert_eq!(
+        run("2024-05-08T10:40", &[]).0,
+        "10:30 - 13:00 (Now)      | Team lunch\n11:00 - 12:00 (20 mins)  | Planning 🔗\n"
+    );
+    assert!(run("2024-05-08T10:40", &["--format", "json"])
+        .0
+        .contains(r#""url":"https://zoom.example.com/j/123?pwd=abc#success","#));
+}
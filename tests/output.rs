@@ -0,0 +1,81 @@
+//! Writing the output to a file with `--output`, which something else may be reading at the time
+
+use obsidian_calendar_summary::output::Destination;
+use std::path::PathBuf;
+
+fn file(name: &str) -> PathBuf {
+    let file = std::env::temp_dir().join(format!(
+        "obsidian-calendar-summary-output-{}-{}",
+        name,
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&file);
+    file
+}
+
+#[test]
+fn never_half_written() {
+    let file = file("atomic");
+    // big enough that writing it takes many writes
+    let renders = ["a".repeat(8 << 20), "b".repeat(8 << 20)];
+    let destination = Destination::File(file.clone());
+    destination.write(&renders[0]).unwrap();
+    std::thread::scope(|scope| {
+        let writer = scope.spawn(|| {
+            for render in renders.iter().cycle().take(20) {
+                destination.write(render).unwrap();
+            }
+        });
+        let mut reads = 0;
+        while !writer.is_finished() || reads == 0 {
+            let contents = std::fs::read_to_string(&file).unwrap();
+            assert!(
+                renders.contains(&contents),
+                "read {} bytes, half of a render",
+                contents.len()
+            );
+            reads += 1;
+        }
+    });
+    // nothing is left behind next to it
+    let leftovers = std::fs::read_dir(std::env::temp_dir())
+        .unwrap()
+        .filter(|entry| {
+            let name = entry.as_ref().unwrap().file_name();
+            let name = name.to_string_lossy();
+            name.contains(&*file.file_name().unwrap().to_string_lossy()) && name.ends_with(".tmp")
+        })
+        .count();
+    assert_eq!(leftovers, 0);
+    std::fs::remove_file(&file).unwrap();
+}
+
+#[test]
+fn append() {
+    let file = file("append");
+    let destination = Destination::Append(file.clone());
+    destination.write("09:00 - 10:00 | Standup\n").unwrap();
+    destination.write("14:00 - 15:00 | Dentist\n").unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&file).unwrap(),
+        "09:00 - 10:00 | Standup\n14:00 - 15:00 | Dentist\n"
+    );
+    std::fs::remove_file(&file).unwrap();
+}
+
+#[test]
+fn unwritable() {
+    let folder = file("missing");
+    let file = folder.join("summary.txt");
+    for destination in [
+        Destination::File(file.clone()),
+        Destination::Append(file.clone()),
+    ] {
+        let message = destination.write("Standup\n").unwrap_err().to_string();
+        assert!(
+            message.starts_with(&format!("{}: ", file.display())),
+            "{}",
+            message
+        );
+    }
+}
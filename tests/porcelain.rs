@@ -0,0 +1,89 @@
+//! `lint --porcelain` on single notes, as a pre-commit hook runs it on the notes which changed
+
+use std::{
+    path::Path,
+    process::{Command, Output},
+};
+
+fn lint(args: &[&str]) -> Output {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    Command::new(env!("CARGO_BIN_EXE_obsidian-calendar-summary"))
+        .args(["lint", "--now", "2024-05-08T10:00"])
+        .args(args)
+        .current_dir(&fixtures)
+        .env("XDG_CONFIG_HOME", &fixtures)
+        .env("TZ", "UTC")
+        .output()
+        .expect("failed to run obsidian-calendar-summary")
+}
+
+fn lines(output: &Output) -> Vec<Vec<String>> {
+    String::from_utf8(output.stdout.clone())
+        .unwrap()
+        .lines()
+        .map(|line| line.split('\t').map(String::from).collect())
+        .collect()
+}
+
+#[test]
+fn fine() {
+    let output = lint(&["--porcelain", "vault/Standup.md", "vault/Dentist.md"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn only_warnings() {
+    let output = lint(&["--porcelain", "vault/Template leftovers.md"]);
+    assert_eq!(output.status.code(), Some(0));
+    let lines = lines(&output);
+    assert_eq!(lines.len(), 3);
+    assert_eq!(
+        lines[0],
+        [
+            "vault/Template leftovers.md",
+            "warning",
+            "ignored_key",
+            "vault/Template leftovers.md (event 1): 'daysOfWeek': only used by recurring events, so it's ignored",
+        ]
+    );
+    // without `--porcelain`, anything at all fails
+    let output = lint(&["vault/Template leftovers.md"]);
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn errors() {
+    let output = lint(&[
+        "--porcelain",
+        "vault/Broken.md",
+        "vault/Template leftovers.md",
+    ]);
+    assert_eq!(output.status.code(), Some(1));
+    let lines = lines(&output);
+    assert_eq!(
+        lines[0],
+        [
+            "vault/Broken.md",
+            "error",
+            "bad_date",
+            "vault/Broken.md: 'date' is '2024-05-32', not a date (input is out of range)",
+        ]
+    );
+    assert!(lines[1..].iter().all(|line| line[1] == "warning"));
+}
+
+#[test]
+fn only_with_lint() {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let output = Command::new(env!("CARGO_BIN_EXE_obsidian-calendar-summary"))
+        .args(["--porcelain", "vault"])
+        .current_dir(&fixtures)
+        .env("XDG_CONFIG_HOME", &fixtures)
+        .output()
+        .expect("failed to run obsidian-calendar-summary");
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("'--porcelain' only works with 'lint'"));
+}
@@ -0,0 +1,51 @@
+//! `--progress`: how far through the ongoing event we are
+
+use chrono::NaiveDateTime;
+use obsidian_calendar_summary::render::{progress, progress_bar};
+
+fn at(time: &str) -> NaiveDateTime {
+    NaiveDateTime::parse_from_str(&format!("2024-05-08 {}", time), "%Y-%m-%d %H:%M:%S").unwrap()
+}
+
+/// The progress through a meeting from 09:00 to 09:30 at `now`
+fn meeting(now: &str) -> i64 {
+    progress(at("09:00:00"), at("09:30:00"), at(now))
+}
+
+#[test]
+fn edges() {
+    assert_eq!(meeting("09:00:00"), 0);
+    assert_eq!(meeting("09:30:00"), 100);
+    // only ongoing events have a progress, but it never goes past either end
+    assert_eq!(meeting("08:59:00"), 0);
+    assert_eq!(meeting("09:31:00"), 100);
+    // an event which takes no time is already over
+    assert_eq!(
+        progress(at("09:00:00"), at("09:00:00"), at("09:00:00")),
+        100
+    );
+}
+
+#[test]
+fn rounding() {
+    // to the nearest whole percent, with halves going up
+    assert_eq!(meeting("09:00:09"), 1); // 0.5%
+    assert_eq!(meeting("09:00:08"), 0); // 0.44%
+    assert_eq!(meeting("09:10:00"), 33); // 33.3%
+    assert_eq!(meeting("09:20:00"), 67); // 66.7%
+    assert_eq!(meeting("09:29:51"), 100); // 99.5%
+    assert_eq!(meeting("09:29:52"), 100);
+    assert_eq!(meeting("09:29:50"), 99); // 99.44%
+}
+
+#[test]
+fn bar() {
+    assert_eq!(progress_bar(0), "[--------] 0%");
+    assert_eq!(progress_bar(100), "[########] 100%");
+    assert_eq!(progress_bar(52), "[####----] 52%");
+    // a cell is filled once it's half covered
+    assert_eq!(progress_bar(6), "[--------] 6%");
+    assert_eq!(progress_bar(7), "[#-------] 7%");
+    assert_eq!(progress_bar(93), "[#######-] 93%");
+    assert_eq!(progress_bar(94), "[########] 94%");
+}
@@ -0,0 +1,230 @@
+//! Invariants of when events happen, checked on many generated events and days rather than a few
+//! picked by hand. The events come from a fixed seed, so a failure always happens again the same
+//! way and prints the event and day it happened with.
+
+use chrono::{Datelike, Days, Duration, NaiveDate, NaiveTime, Weekday};
+use obsidian_calendar_summary::{
+    calendar::Calendar,
+    event::{is_nth_weekday, Event, SourcedEvent},
+    query::EventQuery,
+};
+
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+/// A small xorshift generator, since this only needs to be spread out, not unpredictable
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    fn chance(&mut self, percent: u64) -> bool {
+        self.below(100) < percent
+    }
+
+    /// A day in the three years from 2023, which include a leap year
+    fn date(&mut self) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2023, 1, 1).unwrap() + Days::new(self.below(3 * 365))
+    }
+
+    fn time(&mut self) -> NaiveTime {
+        NaiveTime::from_hms_opt(self.below(24) as u32, self.below(4) as u32 * 15, 0).unwrap()
+    }
+
+    fn recurring(&mut self) -> Event {
+        let begin_recur = self.chance(80).then(|| self.date());
+        // sometimes before `begin_recur`, for an event which can never happen
+        let end_recur = self.chance(70).then(|| match begin_recur {
+            Some(begin_recur) if self.chance(90) => begin_recur + Days::new(self.below(400)),
+            _ => self.date(),
+        });
+        let mut recur_days: Vec<Weekday> =
+            WEEKDAYS.into_iter().filter(|_| self.chance(40)).collect();
+        if recur_days.is_empty() {
+            recur_days.push(WEEKDAYS[self.below(7) as usize]);
+        }
+        let week_of_month = match self.below(6) {
+            0 => Some(-1),
+            1 => Some(self.below(5) as i8 + 1),
+            _ => None,
+        };
+        Event::Recurring {
+            title: "Recurring".into(),
+            begin: self.time(),
+            end: self.time(),
+            begin_recur,
+            end_recur,
+            recur_days,
+            skip_holidays: false,
+            travel_minutes: None,
+            week_of_month,
+        }
+    }
+
+    fn event(&mut self) -> Event {
+        match self.below(4) {
+            0 => Event::Once {
+                title: "Once".into(),
+                begin: self.time(),
+                end: self.time(),
+                day: self.date(),
+                travel_minutes: None,
+                actual_end: None,
+            },
+            1 => {
+                let begin_date = self.date();
+                Event::AllDay {
+                    title: "All day".into(),
+                    begin_date,
+                    end_date: begin_date + Days::new(self.below(20) + 1),
+                    suppress_recurring: false,
+                }
+            }
+            _ => self.recurring(),
+        }
+    }
+}
+
+/// Runs `check` on generated events with a range of days to look at
+fn for_each_event(check: impl Fn(&Event, NaiveDate, NaiveDate)) {
+    let mut rng = Rng(0x5eed_ca1e_11da_7e55);
+    for _ in 0..2000 {
+        let event = rng.event();
+        let from = rng.date();
+        let to = from + Days::new(rng.below(120));
+        check(&event, from, to);
+    }
+}
+
+#[test]
+fn occurrences_are_in_order() {
+    for_each_event(|event, from, to| {
+        let dates: Vec<NaiveDate> = event.occurrences(from, to).collect();
+        assert!(
+            dates.windows(2).all(|pair| pair[0] < pair[1]),
+            "{:?} from {} to {}: {:?}",
+            event,
+            from,
+            to,
+            dates
+        );
+    });
+}
+
+#[test]
+fn occurrences_are_inside_the_span() {
+    for_each_event(|event, from, to| {
+        let (first, last) = event.span();
+        for date in event.occurrences(from, to) {
+            let context = format!("{:?} on {}", event, date);
+            assert!(from <= date && date <= to, "{}", context);
+            assert!(first.is_none_or(|first| first <= date), "{}", context);
+            assert!(last.is_none_or(|last| date <= last), "{}", context);
+            if let Event::Recurring {
+                recur_days,
+                week_of_month,
+                ..
+            } = event
+            {
+                assert!(recur_days.contains(&date.weekday()), "{}", context);
+                assert!(
+                    week_of_month.is_none_or(|n| is_nth_weekday(date, n)),
+                    "{}",
+                    context
+                );
+            }
+        }
+    });
+}
+
+#[test]
+fn occurrences_agree_with_occurs_on() {
+    let found = std::cell::Cell::new(0);
+    for_each_event(|event, from, to| {
+        let expected: Vec<NaiveDate> = from
+            .iter_days()
+            .take_while(|date| *date <= to)
+            .filter(|date| event.occurs_on(*date))
+            .collect();
+        found.set(found.get() + expected.len());
+        assert_eq!(
+            event.occurrences(from, to).collect::<Vec<_>>(),
+            expected,
+            "{:?} from {} to {}",
+            event,
+            from,
+            to
+        );
+    });
+    // enough of the events happen in their range for this to mean something
+    assert!(found.get() > 5_000, "only {} occurrences", found.get());
+}
+
+#[test]
+fn query_agrees_with_occurs_on() {
+    // the filter the command line uses, on a day and with the time left out of it
+    for_each_event(|event, from, _| {
+        let calendar = Calendar {
+            events: vec![SourcedEvent::new(event.clone(), "Event.md")],
+            holidays: Vec::new(),
+            problems: Vec::new(),
+        };
+        let shown = !EventQuery::new().on_date(from).apply(&calendar).is_empty();
+        assert_eq!(shown, event.occurs_on(from), "{:?} on {}", event, from);
+    });
+}
+
+#[test]
+fn ended_and_begun() {
+    let mut rng = Rng(0xdead_beef_cafe_f00d);
+    for _ in 0..2000 {
+        let event = rng.event();
+        let date = rng.date();
+        let now = date.and_time(rng.time());
+        let context = format!("{:?} on {} at {}", event, date, now);
+        match event.times_on(date) {
+            Some((begin, end)) => {
+                assert!(begin <= end, "{}", context);
+                assert_eq!(event.ended(date, now), end < now, "{}", context);
+                assert_eq!(
+                    event.begins_within(date, now, Duration::zero()),
+                    begin <= now,
+                    "{}",
+                    context
+                );
+                // something which is over has begun
+                if event.ended(date, now) {
+                    assert!(
+                        event.begins_within(date, now, Duration::zero()),
+                        "{}",
+                        context
+                    );
+                }
+            }
+            None => {
+                assert!(!event.ended(date, now), "{}", context);
+                assert!(
+                    event.begins_within(date, now, Duration::zero()),
+                    "{}",
+                    context
+                );
+            }
+        }
+    }
+}
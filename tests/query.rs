@@ -0,0 +1,347 @@
+//! The combinators of `EventQuery`. The time window, `grace`, `min_duration`, deadlines and
+//! holidays have tests of their own.
+
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use obsidian_calendar_summary::{
+    calendar::Calendar,
+    cli::{AllDayFilter, AllDayPosition, Options, SortBy},
+    event::{Event, SourcedEvent},
+    query::EventQuery,
+    snooze::{SnoozeTarget, Snoozed},
+    SortSpec,
+};
+use std::path::PathBuf;
+
+fn date() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 5, 8).unwrap()
+}
+
+fn time(time: &str) -> NaiveTime {
+    NaiveTime::parse_from_str(time, "%H:%M").unwrap()
+}
+
+fn at(now: &str) -> NaiveDateTime {
+    date().and_time(time(now))
+}
+
+fn sourced(event: Event, path: &str, category: Option<&str>) -> SourcedEvent {
+    SourcedEvent {
+        category: category.map(Into::into),
+        ..SourcedEvent::new(event, path)
+    }
+}
+
+fn once(title: &str, begin: &str, end: &str, category: Option<&str>) -> SourcedEvent {
+    let event = Event::Once {
+        title: title.into(),
+        begin: time(begin),
+        end: time(end),
+        day: date(),
+        travel_minutes: None,
+        actual_end: None,
+    };
+    sourced(event, &format!("{}.md", title), category)
+}
+
+/// Yoga every day, in the note at `path`
+fn yoga(path: &str) -> SourcedEvent {
+    let event = Event::Recurring {
+        title: "Yoga".into(),
+        begin: time("07:00"),
+        end: time("08:00"),
+        begin_recur: None,
+        end_recur: None,
+        recur_days: vec![
+            Weekday::Mon,
+            Weekday::Tue,
+            Weekday::Wed,
+            Weekday::Thu,
+            Weekday::Fri,
+            Weekday::Sat,
+            Weekday::Sun,
+        ],
+        skip_holidays: false,
+        travel_minutes: None,
+        week_of_month: None,
+    };
+    sourced(event, path, None)
+}
+
+fn calendar() -> Calendar {
+    let conference = Event::AllDay {
+        title: "Conference".into(),
+        begin_date: date(),
+        end_date: date().succ_opt().unwrap(),
+        suppress_recurring: false,
+    };
+    let mut secret = once("Secret", "15:00", "16:00", None);
+    secret.hidden = true;
+    let mut tomorrow = once("Tomorrow", "09:00", "10:00", None);
+    if let Event::Once { day, .. } = &mut tomorrow.event {
+        *day = date().succ_opt().unwrap();
+    }
+    Calendar {
+        events: vec![
+            sourced(conference, "Conference.md", Some("Work")),
+            yoga("Yoga.md"),
+            // a copy of it left behind by a sync tool
+            yoga("Sync conflict/Yoga.md"),
+            once("Standup", "09:00", "09:15", Some("Work")),
+            once("Lunch", "12:00", "13:00", Some("Personal")),
+            once("Planning", "12:00", "12:30", Some("work")),
+            secret,
+            once("Gym", "18:00", "19:00", None),
+            tomorrow,
+        ],
+        holidays: Vec::new(),
+        problems: Vec::new(),
+    }
+}
+
+fn titles(query: EventQuery) -> Vec<String> {
+    query
+        .apply(&calendar())
+        .iter()
+        .map(|sourced| sourced.event.title().to_string())
+        .collect()
+}
+
+/// Today's events at 08:30, including Yoga, which is over
+fn day() -> EventQuery<'static> {
+    EventQuery::new().on_date(date()).at(at("08:30"))
+}
+
+#[test]
+fn new() {
+    // everything but the hidden note, on any day, sorted by time
+    assert_eq!(
+        titles(EventQuery::new().at(at("08:30"))),
+        [
+            "Conference",
+            "Yoga",
+            "Yoga",
+            "Standup",
+            "Tomorrow",
+            "Lunch",
+            "Planning",
+            "Gym"
+        ]
+    );
+}
+
+#[test]
+fn on_date() {
+    assert_eq!(
+        titles(day()),
+        [
+            "Conference",
+            "Yoga",
+            "Yoga",
+            "Standup",
+            "Lunch",
+            "Planning",
+            "Gym"
+        ]
+    );
+    assert_eq!(
+        titles(EventQuery::new().on_date(date().succ_opt().unwrap())),
+        ["Yoga", "Yoga", "Tomorrow"]
+    );
+}
+
+#[test]
+fn unfinished() {
+    assert_eq!(
+        titles(day().unfinished()),
+        ["Conference", "Standup", "Lunch", "Planning", "Gym"]
+    );
+    // the same as `today`
+    assert_eq!(
+        titles(EventQuery::today(at("12:45"))),
+        ["Conference", "Lunch", "Gym"]
+    );
+}
+
+#[test]
+fn matching_title() {
+    assert_eq!(titles(day().matching_title("UP")), ["Standup"]);
+    assert_eq!(
+        titles(day().matching_title("o")),
+        ["Conference", "Yoga", "Yoga"]
+    );
+}
+
+#[test]
+fn categories() {
+    // ignoring case, so both "Work" and "work" are work
+    assert_eq!(
+        titles(day().categories(&["work"])),
+        ["Conference", "Standup", "Planning"]
+    );
+    assert_eq!(
+        titles(day().categories(&["Personal", "Work"])),
+        ["Conference", "Standup", "Lunch", "Planning"]
+    );
+    // events without a category never match
+    assert!(titles(day().categories(&[])).is_empty());
+}
+
+#[test]
+fn within() {
+    // all-day events count as started, and events which have started are kept
+    assert_eq!(
+        titles(day().within(Duration::try_hours(1).unwrap())),
+        ["Conference", "Yoga", "Yoga", "Standup"]
+    );
+    assert_eq!(
+        titles(day().unfinished().within(Duration::zero())),
+        ["Conference"]
+    );
+}
+
+#[test]
+fn allday_filter() {
+    assert_eq!(
+        titles(day().allday_filter(AllDayFilter::Only)),
+        ["Conference"]
+    );
+    assert!(!titles(day().allday_filter(AllDayFilter::Hide)).contains(&"Conference".into()));
+}
+
+#[test]
+fn allday_position() {
+    assert_eq!(
+        titles(day().allday_position(AllDayPosition::Bottom)).last(),
+        Some(&"Conference".into())
+    );
+}
+
+#[test]
+fn sort_by() {
+    // Planning ends before Lunch, and they start at the same time
+    let sorted = titles(day().unfinished().sort_by(SortBy::End));
+    assert_eq!(
+        sorted,
+        ["Conference", "Standup", "Planning", "Lunch", "Gym"]
+    );
+    // and events starting at the same time are in order of their titles
+    let sorted = titles(EventQuery::today(at("12:10")).sort_by(SortBy::Start));
+    assert_eq!(sorted, ["Conference", "Lunch", "Planning", "Gym"]);
+}
+
+#[test]
+fn reverse() {
+    // all-day events stay at the top unless `allday_position` says otherwise, and the order of
+    // events at the same time doesn't change
+    assert_eq!(
+        titles(day().unfinished().reverse(true)),
+        ["Conference", "Gym", "Lunch", "Planning", "Standup"]
+    );
+}
+
+#[test]
+fn sorted() {
+    let sort = SortSpec {
+        by: SortBy::End,
+        allday_position: AllDayPosition::Bottom,
+        reverse: true,
+        ..SortSpec::default()
+    };
+    assert_eq!(
+        titles(day().unfinished().sorted(sort)),
+        ["Gym", "Lunch", "Planning", "Standup", "Conference"]
+    );
+}
+
+#[test]
+fn without_snoozed() {
+    let mut snoozed = Snoozed::default();
+    snoozed.snooze(SnoozeTarget::Title("Lunch".into()), date());
+    assert_eq!(
+        titles(day().unfinished().without_snoozed(&snoozed)),
+        ["Conference", "Standup", "Planning", "Gym"]
+    );
+    // only on the day it was snoozed on, so Yoga is still there tomorrow
+    let tomorrow = date().succ_opt().unwrap();
+    snoozed.snooze(SnoozeTarget::Title("Yoga".into()), date());
+    assert_eq!(
+        titles(
+            EventQuery::new()
+                .on_date(tomorrow)
+                .without_snoozed(&snoozed)
+        ),
+        ["Yoga", "Yoga", "Tomorrow"]
+    );
+}
+
+#[test]
+fn dedup_recurring() {
+    let query = EventQuery::new()
+        .on_date(date())
+        .at(at("08:30"))
+        .matching_title("yoga")
+        .dedup_recurring(true);
+    let calendar = calendar();
+    let kept = query.apply(&calendar);
+    assert_eq!(kept.len(), 1);
+    assert_eq!(kept[0].path, PathBuf::from("Yoga.md"));
+}
+
+#[test]
+fn show_hidden() {
+    assert!(!titles(day()).contains(&"Secret".into()));
+    assert!(titles(day().show_hidden(true)).contains(&"Secret".into()));
+}
+
+#[test]
+fn filter() {
+    assert_eq!(
+        titles(day().filter(|event| event.title().len() == 4)),
+        ["Yoga", "Yoga"]
+    );
+    // every predicate has to match
+    assert_eq!(
+        titles(
+            day()
+                .filter(|event| event.title().starts_with('P'))
+                .filter(|event| event.title().ends_with('g'))
+        ),
+        ["Planning"]
+    );
+}
+
+#[test]
+fn with_options() {
+    // keep the user's config file out of it
+    std::env::set_var("XDG_CONFIG_HOME", "/nonexistent");
+    let options = Options::parse(
+        [
+            "--now",
+            "2024-05-08T08:30",
+            "--no-allday",
+            "--until",
+            "12:00",
+        ]
+        .into_iter()
+        .map(String::from),
+    )
+    .unwrap();
+    // the same as the flags' own combinators
+    assert_eq!(
+        titles(EventQuery::today(at("08:30")).with_options(&options)),
+        titles(
+            EventQuery::today(at("08:30"))
+                .allday_filter(AllDayFilter::Hide)
+                .until(time("12:00"))
+        )
+    );
+    assert_eq!(
+        titles(
+            EventQuery::new()
+                .on_date(date())
+                .unfinished()
+                .with_options(&options)
+        ),
+        ["Standup", "Lunch", "Planning"]
+    );
+}
@@ -0,0 +1,174 @@
+//! Dates like `tomorrow` in notes, read relative to today with `--allow-relative-dates`
+
+use chrono::NaiveDate;
+use obsidian_calendar_summary::{
+    error::Error,
+    event::Event,
+    parse::{parse_cal_file_with_warnings, relative_date, AllDayEnd, WeekdayLocale},
+};
+use std::{path::Path, process::Command};
+
+fn date(date: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap()
+}
+
+/// A Wednesday
+fn today() -> NaiveDate {
+    date("2024-05-08")
+}
+
+fn parse(frontmatter: &str, today: Option<NaiveDate>) -> Result<(Event, Vec<Error>), Error> {
+    parse_cal_file_with_warnings(
+        &format!("---\n{}---\n", frontmatter),
+        Path::new("note.md"),
+        WeekdayLocale::English,
+        AllDayEnd::Exclusive,
+        today,
+        false,
+        true,
+    )
+    .map(|(mut events, warnings)| (events.remove(0), warnings))
+}
+
+/// The day of a one-off event on `value`
+fn day(value: &str) -> NaiveDate {
+    let (event, warnings) = parse(
+        &format!(
+            "title: Dentist\ndate: {}\nstartTime: 14:00\nendTime: 15:00\n",
+            value
+        ),
+        Some(today()),
+    )
+    .unwrap_or_else(|e| panic!("{}", e));
+    assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    assert!(warnings[0].is_relative_date());
+    match event {
+        Event::Once { day, .. } => day,
+        _ => panic!("{:?}", event),
+    }
+}
+
+#[test]
+fn keywords() {
+    assert_eq!(day("today"), today());
+    assert_eq!(day("Tomorrow"), date("2024-05-09"));
+    assert_eq!(day("next-friday"), date("2024-05-10"));
+    assert_eq!(day("next-mon"), date("2024-05-13"));
+    // a week on, not today
+    assert_eq!(day("next-wednesday"), date("2024-05-15"));
+    assert_eq!(day("+3 days"), date("2024-05-11"));
+    assert_eq!(day("\"+1 day\""), date("2024-05-09"));
+    assert_eq!(day("+0 days # same day"), today());
+    for value in [
+        "yesterday",
+        "next-week",
+        "+3",
+        "3 days",
+        "+3 weeks",
+        "-1 days",
+    ] {
+        assert_eq!(relative_date(value, today()), None, "{}", value);
+    }
+}
+
+#[test]
+fn other_keys() {
+    let (event, warnings) = parse(
+        "title: Trip\nallDay: true\ndate: today\nendDate: +3 days\n",
+        Some(today()),
+    )
+    .unwrap();
+    assert!(matches!(
+        event,
+        Event::AllDay { begin_date, end_date, .. }
+            if begin_date == today() && end_date == date("2024-05-11")
+    ));
+    assert_eq!(
+        warnings
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>(),
+        [
+            "note.md: 'date': 'today' is relative, so it's 2024-05-08 today but changes from day to day",
+            "note.md: 'endDate': '+3 days' is relative, so it's 2024-05-11 today but changes from day to day",
+        ]
+    );
+    let (event, _) = parse(
+        "title: Gym\ntype: recurring\ndaysOfWeek: [M]\nstartRecur: today\nendRecur: next-sunday\nstartTime: 18:00\nendTime: 19:00\n",
+        Some(today()),
+    )
+    .unwrap();
+    assert!(matches!(
+        event,
+        Event::Recurring { begin_recur: Some(begin), end_recur: Some(end), .. }
+            if begin == today() && end == date("2024-05-12")
+    ));
+}
+
+#[test]
+fn rejected_without_the_flag() {
+    let e = parse(
+        "title: Dentist\ndate: tomorrow\nstartTime: 14:00\nendTime: 15:00\n",
+        None,
+    )
+    .unwrap_err();
+    assert_eq!(e.code(), "relative_date");
+    assert!(!e.is_warning());
+    assert_eq!(
+        e.to_string(),
+        "note.md: 'date': 'tomorrow' is a relative date, which needs --allow-relative-dates"
+    );
+    // anything else is still just not a date
+    let e = parse(
+        "title: Dentist\ndate: someday\nstartTime: 14:00\nendTime: 15:00\n",
+        Some(today()),
+    )
+    .unwrap_err();
+    assert_eq!(e.code(), "bad_date");
+}
+
+#[test]
+fn command() {
+    let vault = std::env::temp_dir().join(format!(
+        "obsidian-calendar-summary-relative-{}",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&vault).unwrap();
+    std::fs::write(
+        vault.join("Dentist.md"),
+        "---\ntitle: Dentist\ndate: today\nstartTime: 14:00\nendTime: 15:00\n---\n",
+    )
+    .unwrap();
+    let run = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_obsidian-calendar-summary"))
+            .args(args)
+            .args(["--now", "2024-05-09T10:00"])
+            .arg(&vault)
+            .env("XDG_CONFIG_HOME", &vault)
+            .output()
+            .expect("failed to run obsidian-calendar-summary")
+    };
+    let output = run(&["--allow-relative-dates"]);
+    assert!(output.status.success());
+    // without any warning, since they were asked for
+    assert!(output.stderr.is_empty());
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("Dentist"));
+    let stderr = String::from_utf8(run(&[]).stderr).unwrap();
+    assert!(
+        stderr.contains("needs --allow-relative-dates"),
+        "{}",
+        stderr
+    );
+    // but lint points them out
+    let output = run(&["lint", "--allow-relative-dates"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.contains("'today' is relative, so it's 2024-05-09 today"),
+        "{}",
+        stdout
+    );
+    assert!(!output.status.success());
+    let _ = std::fs::remove_dir_all(&vault);
+}
@@ -0,0 +1,67 @@
+//! Remote calendar sources: their names and the credentials handed to curl
+
+use obsidian_calendar_summary::{config::Config, remote::Source};
+
+fn source(name: &str, password_env: Option<&str>, token_env: Option<&str>) -> Source {
+    Source {
+        name: name.into(),
+        url: "https://dav.example.com/work.ics".into(),
+        username: Some("me".into()),
+        password_env: password_env.map(Into::into),
+        token_env: token_env.map(Into::into),
+    }
+}
+
+#[test]
+fn names() {
+    let sources = Source::from_config(
+        &Config::parse("[source.work-2]\nurl = \"https://dav.example.com/work.ics\"\n").unwrap(),
+    )
+    .unwrap();
+    assert_eq!(sources[0].name, "work-2");
+    // the name is used for the cache file, which mustn't end up anywhere else
+    for name in ["../../x", "a/b", "a\\\\b", "work calendar"] {
+        let config = format!(
+            "[source.{}]\nurl = \"https://dav.example.com/work.ics\"\n",
+            name
+        );
+        let error = Source::from_config(&Config::parse(&config).unwrap()).unwrap_err();
+        assert_eq!(error.code(), "config", "{}", name);
+    }
+    std::env::set_var("XDG_CACHE_HOME", "/cache");
+    assert_eq!(
+        source("../../x", None, None).cache_path().unwrap(),
+        std::path::Path::new("/cache/obsidian-calendar-summary/______x.ics")
+    );
+}
+
+#[test]
+fn credentials_are_quoted() {
+    std::env::set_var("REMOTE_TEST_PASSWORD", "pa\"ss\\word");
+    std::env::set_var("REMOTE_TEST_TOKEN", "abc\"\noutput = \"/tmp/x");
+    assert_eq!(
+        source("work", Some("REMOTE_TEST_PASSWORD"), None)
+            .curl_config()
+            .unwrap(),
+        "url = \"https://dav.example.com/work.ics\"\nuser = \"me:pa\\\"ss\\\\word\"\n"
+    );
+    // a newline would start another curl option
+    let error = source("work", None, Some("REMOTE_TEST_TOKEN"))
+        .curl_config()
+        .unwrap_err();
+    assert_eq!(error.code(), "fetch");
+    assert!(source("work", Some("REMOTE_TEST_UNSET"), None)
+        .curl_config()
+        .is_err());
+}
+
+#[test]
+fn url_is_not_an_option() {
+    // passed as an argument, this would make curl read its config from /path
+    let mut source = source("work", None, None);
+    source.url = "-K/path".into();
+    source.username = None;
+    assert_eq!(source.curl_config().unwrap(), "url = \"-K/path\"\n");
+    source.url = "https://dav.example.com/a\nb".into();
+    assert_eq!(source.curl_config().unwrap_err().code(), "fetch");
+}
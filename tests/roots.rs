@@ -0,0 +1,140 @@
+//! Calendar directories and notes mixed together on the command line, as a shell glob like
+//! `Calendar/2024-*` gives them
+
+use obsidian_calendar_summary::parse::calendar_roots;
+use std::{
+    path::PathBuf,
+    process::{Command, Output},
+};
+
+fn note(title: &str, start: &str, end: &str) -> String {
+    format!(
+        "---\ntitle: {}\nallDay: false\ndate: 2024-05-08\nstartTime: {}\nendTime: {}\n---\n",
+        title, start, end
+    )
+}
+
+/// A calendar with two folders, a note next to them and a file which isn't a note, removed
+/// again when the test is done
+struct Calendar(PathBuf);
+
+impl Calendar {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!(
+            "obsidian-calendar-summary-{}-{}",
+            name,
+            std::process::id()
+        ));
+        for folder in ["2024-04", "2024-05"] {
+            std::fs::create_dir_all(dir.join(folder)).unwrap();
+        }
+        std::fs::write(
+            dir.join("2024-04/Dentist.md"),
+            note("Dentist", "14:00", "15:00"),
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("2024-05/Planning.md"),
+            note("Planning", "11:00", "12:00"),
+        )
+        .unwrap();
+        std::fs::write(dir.join("2024-review.md"), note("Review", "16:00", "17:00")).unwrap();
+        std::fs::write(dir.join("2024-budget.csv"), "rent,1200\n").unwrap();
+        Calendar(dir)
+    }
+
+    fn run(&self, args: &[&str], paths: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_obsidian-calendar-summary"))
+            .args(args)
+            .args(["--now", "2024-05-08T10:00"])
+            .args(paths)
+            .current_dir(&self.0)
+            // keep the user's config file out of it
+            .env("XDG_CONFIG_HOME", &self.0)
+            .env("XDG_STATE_HOME", &self.0)
+            .output()
+            .expect("failed to run obsidian-calendar-summary")
+    }
+}
+
+impl Drop for Calendar {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// What `Calendar/2024-*` expands to, plus a folder given again another way and a glob which
+/// matched nothing, which the shell passes on as it is
+const MIXED: [&str; 7] = [
+    "2024-04",
+    "2024-05",
+    "2024-budget.csv",
+    "2024-review.md",
+    "./2024-04/",
+    "2024-04/../2024-05",
+    "2025-*",
+];
+
+#[test]
+fn mixed() {
+    let calendar = Calendar::new("roots-mixed");
+    let (roots, problems) = calendar_roots(
+        &MIXED
+            .iter()
+            .map(|path| calendar.0.join(path))
+            .collect::<Vec<_>>(),
+    );
+    assert_eq!(
+        roots,
+        ["2024-04", "2024-05", "2024-review.md"].map(|path| calendar.0.join(path))
+    );
+    let problems: Vec<String> = problems.iter().map(ToString::to_string).collect();
+    assert_eq!(problems.len(), 2);
+    assert_eq!(
+        problems[0],
+        format!(
+            "{}: not a directory or a .md note",
+            calendar.0.join("2024-budget.csv").display()
+        )
+    );
+    assert!(
+        problems[1].starts_with(&calendar.0.join("2025-*").display().to_string()),
+        "{}",
+        problems[1]
+    );
+}
+
+#[test]
+fn each_event_once() {
+    let calendar = Calendar::new("roots-once");
+    let output = calendar.run(&[], &MIXED);
+    assert!(output.status.success(), "exited with {}", output.status);
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "11:00 - 12:00 (1 hour)   | Planning\n14:00 - 15:00 (4 hours)  | Dentist\n16:00 - 17:00 (6 hours)  | Review\n"
+    );
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("Warning: skipping 2024-budget.csv: not a directory or a .md note"),
+        "{}",
+        stderr
+    );
+    assert!(stderr.contains("Warning: skipping 2025-*: "), "{}", stderr);
+}
+
+#[test]
+fn strict() {
+    // like any other problem, they stop everything with `--strict`
+    let calendar = Calendar::new("roots-strict");
+    let output = calendar.run(&["--strict"], &["2024-04", "2024-budget.csv"]);
+    assert!(output.stdout.is_empty());
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "Error processing event files: 2024-budget.csv: not a directory or a .md note\n"
+    );
+    let output = calendar.run(&["--strict"], &["2024-04", "2024-review.md"]);
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "14:00 - 15:00 (4 hours)  | Dentist\n16:00 - 17:00 (6 hours)  | Review\n"
+    );
+}
@@ -0,0 +1,64 @@
+//! How `--round` widens the times events are shown with
+
+use chrono::{Duration, NaiveTime};
+use obsidian_calendar_summary::render::round_out;
+
+fn time(time: &str) -> NaiveTime {
+    NaiveTime::parse_from_str(time, "%H:%M:%S").unwrap()
+}
+
+fn rounded(begin: &str, end: &str, minutes: i64) -> (NaiveTime, NaiveTime) {
+    round_out(
+        time(begin),
+        time(end),
+        Duration::try_minutes(minutes).unwrap(),
+    )
+}
+
+#[test]
+fn outwards() {
+    assert_eq!(
+        rounded("09:02:00", "09:58:00", 5),
+        (time("09:00:00"), time("10:00:00"))
+    );
+    // the start always goes down and the end up, however near the other multiple they are
+    assert_eq!(
+        rounded("09:07:00", "09:31:00", 5),
+        (time("09:05:00"), time("09:35:00"))
+    );
+    assert_eq!(
+        rounded("09:04:59", "09:05:01", 5),
+        (time("09:00:00"), time("09:10:00"))
+    );
+    // times which are already on a multiple stay where they are
+    assert_eq!(
+        rounded("09:00:00", "09:45:00", 15),
+        (time("09:00:00"), time("09:45:00"))
+    );
+}
+
+#[test]
+fn midnight() {
+    // an end rounded up to midnight is written like one at midnight in a note
+    assert_eq!(
+        rounded("23:30:00", "23:58:00", 5),
+        (time("23:30:00"), time("00:00:00"))
+    );
+    // for an event running past midnight, the end is rounded on the next day
+    assert_eq!(
+        rounded("22:03:00", "01:02:00", 5),
+        (time("22:00:00"), time("01:05:00"))
+    );
+    assert_eq!(
+        rounded("00:01:00", "00:02:00", 5),
+        (time("00:00:00"), time("00:05:00"))
+    );
+}
+
+#[test]
+fn no_step() {
+    assert_eq!(
+        rounded("09:02:00", "09:58:00", 0),
+        (time("09:02:00"), time("09:58:00"))
+    );
+}
@@ -0,0 +1,101 @@
+//! The versioned JSON format of events: each kind is written and read back unchanged. Deadlines
+//! are tested with the rest of their behaviour.
+
+use chrono::{NaiveDate, NaiveTime, Weekday};
+use obsidian_calendar_summary::{event::Event, schema};
+
+fn date(date: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap()
+}
+
+fn time(time: &str) -> NaiveTime {
+    NaiveTime::parse_from_str(time, "%H:%M").unwrap()
+}
+
+/// Writes the event, checks what was written and reads it back
+fn round_trip(event: Event, json: &str) {
+    assert_eq!(schema::to_json(&event), json);
+    assert_eq!(schema::from_json(json).unwrap(), event);
+}
+
+#[test]
+fn once() {
+    round_trip(
+        Event::Once {
+            title: "Dentist".into(),
+            begin: time("14:00"),
+            end: time("15:00"),
+            day: date("2024-05-08"),
+            travel_minutes: None,
+            actual_end: None,
+        },
+        r#"{"version":3,"type":"once","title":"Dentist","date":"2024-05-08","start":"14:00:00","end":"15:00:00","travel_minutes":null,"actual_end":null}"#,
+    );
+    // running past midnight and over, with a title which needs escaping
+    round_trip(
+        Event::Once {
+            title: "\"Late\" gig ♪".into(),
+            begin: time("22:00"),
+            end: time("01:00"),
+            day: date("2024-12-30"),
+            travel_minutes: Some(45),
+            actual_end: Some(time("01:30")),
+        },
+        r#"{"version":3,"type":"once","title":"\"Late\" gig ♪","date":"2024-12-30","start":"22:00:00","end":"01:00:00","travel_minutes":45,"actual_end":"01:30:00"}"#,
+    );
+}
+
+#[test]
+fn recurring() {
+    round_trip(
+        Event::Recurring {
+            title: "Gym".into(),
+            begin: time("18:00"),
+            end: time("19:00"),
+            begin_recur: Some(date("2024-03-01")),
+            end_recur: Some(date("2024-06-30")),
+            recur_days: vec![Weekday::Mon, Weekday::Wed, Weekday::Fri],
+            skip_holidays: true,
+            travel_minutes: Some(10),
+            week_of_month: None,
+        },
+        r#"{"version":3,"type":"recurring","title":"Gym","start":"18:00:00","end":"19:00:00","start_recur":"2024-03-01","end_recur":"2024-06-30","days_of_week":["mon","wed","fri"],"skip_holidays":true,"travel_minutes":10,"week_of_month":null}"#,
+    );
+    // the last Sunday of every month, forever
+    round_trip(
+        Event::Recurring {
+            title: "Book club".into(),
+            begin: time("19:30"),
+            end: time("21:00"),
+            begin_recur: None,
+            end_recur: None,
+            recur_days: vec![Weekday::Sun],
+            skip_holidays: false,
+            travel_minutes: None,
+            week_of_month: Some(-1),
+        },
+        r#"{"version":3,"type":"recurring","title":"Book club","start":"19:30:00","end":"21:00:00","start_recur":null,"end_recur":null,"days_of_week":["sun"],"skip_holidays":false,"travel_minutes":null,"week_of_month":-1}"#,
+    );
+}
+
+#[test]
+fn all_day() {
+    round_trip(
+        Event::AllDay {
+            title: "Conference".into(),
+            begin_date: date("2024-05-07"),
+            end_date: date("2024-05-11"),
+            suppress_recurring: false,
+        },
+        r#"{"version":3,"type":"all_day","title":"Conference","date":"2024-05-07","end_date":"2024-05-11","suppress_recurring":false}"#,
+    );
+    round_trip(
+        Event::AllDay {
+            title: "Summer holiday".into(),
+            begin_date: date("2024-07-08"),
+            end_date: date("2024-07-13"),
+            suppress_recurring: true,
+        },
+        r#"{"version":3,"type":"all_day","title":"Summer holiday","date":"2024-07-08","end_date":"2024-07-13","suppress_recurring":true}"#,
+    );
+}
@@ -0,0 +1,120 @@
+//! The keys which say how a note's events are shown rather than when they happen, like `pinned`
+//! and `category`, which are read like every other key and can be set on each block under
+//! `## Events`
+
+use obsidian_calendar_summary::{
+    event::SourcedEvent,
+    parse::{parse_cal_file, parse_events, AllDayEnd, WeekdayLocale},
+};
+use std::path::Path;
+
+fn events() -> Vec<SourcedEvent> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/shown");
+    let (mut events, problems) = parse_events(
+        dir,
+        WeekdayLocale::English,
+        AllDayEnd::Exclusive,
+        false,
+        false,
+        &[],
+        None,
+        false,
+        false,
+    )
+    .unwrap();
+    assert!(problems.is_empty(), "{:?}", problems);
+    events.sort_by_key(|sourced| sourced.event.begin());
+    events
+}
+
+/// The title, whether it's pinned, and its category and colour
+fn shown(sourced: &SourcedEvent) -> (&str, bool, Option<&str>, Option<&str>) {
+    (
+        sourced.event.title(),
+        sourced.pinned,
+        sourced.category.as_deref(),
+        sourced.color.as_deref(),
+    )
+}
+
+#[test]
+fn like_other_keys() {
+    // `True` is true like it is for `allDay`, and quotes and comments are left off
+    assert_eq!(shown(&events()[0]), ("Standup", true, Some("Work"), None));
+}
+
+#[test]
+fn per_block() {
+    let events = events();
+    let shown: Vec<_> = events[1..4].iter().map(shown).collect();
+    assert_eq!(
+        shown,
+        [
+            ("Keynote", false, Some("Conference"), Some("#2e8fb8")),
+            // the frontmatter's apply to the blocks which don't have their own
+            ("Workshop", false, Some("Conference"), Some("#2e8fb8")),
+            ("Dinner", true, Some("Social"), Some("#2e8fb8")),
+        ]
+    );
+}
+
+#[test]
+fn numbered() {
+    let events = events();
+    let numbered: Vec<_> = events[1..4]
+        .iter()
+        .map(|sourced| (sourced.event.title(), sourced.numbered.as_deref()))
+        .collect();
+    assert_eq!(
+        numbered,
+        [
+            ("Keynote", None),
+            ("Workshop", Some("Session")),
+            ("Dinner", None)
+        ]
+    );
+}
+
+#[test]
+fn url() {
+    // the quotes keep the ` #` from starting a comment
+    let events = events();
+    assert!(events[1..4]
+        .iter()
+        .all(|sourced| sourced.url.as_deref() == Some("https://meet.example.com/abc #keynote")));
+    assert_eq!(events[0].url, None);
+}
+
+#[test]
+fn hidden() {
+    // and since it's hidden, its leftover `daysOfWeek` isn't warned about either
+    let events = events();
+    let hidden: Vec<&str> = events
+        .iter()
+        .filter(|sourced| sourced.hidden)
+        .map(|sourced| sourced.event.title())
+        .collect();
+    assert_eq!(hidden, ["Partner shift"]);
+}
+
+#[test]
+fn bad_value() {
+    for key in ["pinned", "hidden"] {
+        let note = format!(
+            "---\ntitle: Standup\nallDay: false\ndate: 2024-05-08\nstartTime: 09:00\n\
+             endTime: 09:15\n{}: yes\n---\n",
+            key
+        );
+        let error = parse_cal_file(
+            &note,
+            Path::new("Standup.md"),
+            WeekdayLocale::English,
+            AllDayEnd::Exclusive,
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!("Standup.md: '{}': is 'yes', not true or false", key)
+        );
+    }
+}
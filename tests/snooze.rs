@@ -0,0 +1,153 @@
+//! Events hidden for the rest of the day with `snooze`
+
+use chrono::{NaiveDate, NaiveTime};
+use obsidian_calendar_summary::{
+    event::{Event, SourcedEvent},
+    snooze::{Snooze, SnoozeTarget, Snoozed},
+};
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+fn date(date: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap()
+}
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/vault")
+        .join(name)
+}
+
+fn standup(path: PathBuf) -> SourcedEvent {
+    SourcedEvent::new(
+        Event::Once {
+            title: "Standup".into(),
+            begin: NaiveTime::from_hms_opt(9, 45, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(10, 15, 0).unwrap(),
+            day: date("2024-05-08"),
+            travel_minutes: None,
+            actual_end: None,
+        },
+        path,
+    )
+}
+
+fn snoozed(target: SnoozeTarget) -> Snoozed {
+    let mut snoozed = Snoozed::default();
+    snoozed.snooze(target, date("2024-05-08"));
+    snoozed
+}
+
+#[test]
+fn matching_titles() {
+    let standup = standup(fixture("Standup.md"));
+    assert!(snoozed(SnoozeTarget::Title("Standup".into())).hides(&standup, date("2024-05-08")));
+    // the whole title, as it's written
+    for title in ["standup", "Stand", "Standup meeting"] {
+        assert!(!snoozed(SnoozeTarget::Title(title.into())).hides(&standup, date("2024-05-08")));
+    }
+}
+
+#[test]
+fn matching_paths() {
+    // however the note was named, it's the same file
+    let relative = Path::new("tests/fixtures/vault/../vault/Standup.md");
+    let target = SnoozeTarget::from_arg(fixture("Standup.md").to_str().unwrap());
+    assert!(matches!(target, SnoozeTarget::Path(_)));
+    assert!(snoozed(target.clone()).hides(&standup(relative.into()), date("2024-05-08")));
+    // another note with an event of the same title
+    assert!(!snoozed(target).hides(&standup(fixture("Dentist.md")), date("2024-05-08")));
+    // something which isn't a note is a title
+    assert_eq!(
+        SnoozeTarget::from_arg("tests/fixtures/vault"),
+        SnoozeTarget::Title("tests/fixtures/vault".into())
+    );
+}
+
+#[test]
+fn expiry() {
+    let standup = standup(fixture("Standup.md"));
+    let mut snoozed = snoozed(SnoozeTarget::Title("Standup".into()));
+    // just for the day it was snoozed on
+    assert!(!snoozed.hides(&standup, date("2024-05-09")));
+    assert!(!snoozed.hides(&standup, date("2024-05-07")));
+    // and forgotten the next time something is snoozed
+    snoozed.snooze(SnoozeTarget::Title("Gym".into()), date("2024-05-09"));
+    snoozed.snooze(SnoozeTarget::Title("Gym".into()), date("2024-05-09"));
+    assert_eq!(
+        snoozed.snoozes,
+        [Snooze {
+            target: SnoozeTarget::Title("Gym".into()),
+            date: date("2024-05-09"),
+        }]
+    );
+}
+
+#[test]
+fn json() {
+    let mut snoozed = snoozed(SnoozeTarget::Title("Say \"hi\"".into()));
+    snoozed.snooze(
+        SnoozeTarget::Path("/notes/Gym.md".into()),
+        date("2024-05-08"),
+    );
+    let json = snoozed.to_json();
+    assert_eq!(
+        json,
+        "[\n  {\"date\":\"2024-05-08\",\"title\":\"Say \\\"hi\\\"\"},\n  {\"date\":\"2024-05-08\",\"path\":\"/notes/Gym.md\"}\n]\n"
+    );
+    assert_eq!(Snoozed::parse(&json).unwrap(), snoozed);
+    assert_eq!(
+        Snoozed::parse(&Snoozed::default().to_json()).unwrap(),
+        Snoozed::default()
+    );
+    for broken in ["{}", "[{\"title\":\"Gym\"}]", "[{\"date\":\"2024-05-08\"}]"] {
+        assert!(Snoozed::parse(broken).is_err(), "{}", broken);
+    }
+}
+
+/// Runs the binary on the test vault with its own state directory
+fn run(state: &Path, args: &[&str]) -> String {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let output = Command::new(env!("CARGO_BIN_EXE_obsidian-calendar-summary"))
+        .args(args)
+        .args(["--now", "2024-05-08T10:00", "--errors", "json"])
+        .current_dir(&fixtures)
+        .env("XDG_CONFIG_HOME", &fixtures)
+        .env("XDG_STATE_HOME", state)
+        .env("TZ", "UTC")
+        .output()
+        .expect("failed to run obsidian-calendar-summary");
+    assert!(output.status.success(), "exited with {}", output.status);
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn command() {
+    let state = std::env::temp_dir().join(format!(
+        "obsidian-calendar-summary-snooze-{}",
+        std::process::id()
+    ));
+    run(&state, &["snooze", "Standup"]);
+    run(&state, &["snooze", "vault/Gym.md"]);
+    let today = run(&state, &["vault"]);
+    assert!(
+        !today.contains("Standup") && !today.contains("Gym"),
+        "{}",
+        today
+    );
+    assert!(today.contains("Dentist"), "{}", today);
+    let list = run(&state, &["snooze", "--list"]);
+    assert_eq!(
+        list,
+        format!(
+            "Standup\n{}\n",
+            std::fs::canonicalize(fixture("Gym.md")).unwrap().display()
+        )
+    );
+    run(&state, &["snooze", "--clear"]);
+    assert_eq!(run(&state, &["snooze", "--list"]), "");
+    assert!(run(&state, &["vault"]).contains("Standup"));
+    let _ = std::fs::remove_dir_all(&state);
+}
@@ -0,0 +1,224 @@
+use chrono::{NaiveDate, NaiveTime};
+use obsidian_calendar_summary::{
+    cli::{AllDayPosition, SortBy, Tiebreak},
+    event::{Event, SourcedEvent},
+    SortSpec,
+};
+use std::{
+    process::Command,
+    time::{Duration, UNIX_EPOCH},
+};
+
+fn day() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 5, 8).unwrap()
+}
+
+fn timed(title: &str, begin: &str, end: &str) -> Event {
+    let time = |time| NaiveTime::parse_from_str(time, "%H:%M").unwrap();
+    Event::Once {
+        title: title.into(),
+        begin: time(begin),
+        end: time(end),
+        day: day(),
+        travel_minutes: None,
+        actual_end: None,
+    }
+}
+
+/// The titles of the events in the order `sort` puts them in
+fn sorted(sort: SortSpec) -> String {
+    let mut events = [
+        timed("A", "09:00", "10:00"),
+        Event::AllDay {
+            title: "D".into(),
+            begin_date: day(),
+            end_date: day().succ_opt().unwrap(),
+            suppress_recurring: false,
+        },
+        // starts first but ends late
+        timed("B", "08:00", "17:00"),
+        // ends past midnight
+        timed("C", "22:00", "01:00"),
+    ];
+    events.sort_by(|a, b| sort.compare(a, b));
+    events.iter().map(Event::title).collect()
+}
+
+#[test]
+fn every_combination() {
+    use AllDayPosition::*;
+    use SortBy::*;
+    for (by, allday_position, reverse, expected) in [
+        (Start, Top, false, "DBAC"),
+        (Start, Top, true, "DCAB"),
+        (Start, Bottom, false, "BACD"),
+        (Start, Bottom, true, "CABD"),
+        (Start, Inline, false, "DBAC"),
+        (Start, Inline, true, "CABD"),
+        (End, Top, false, "DABC"),
+        (End, Top, true, "DCBA"),
+        (End, Bottom, false, "ABCD"),
+        (End, Bottom, true, "CBAD"),
+        (End, Inline, false, "DABC"),
+        (End, Inline, true, "CBAD"),
+    ] {
+        let sort = SortSpec {
+            by,
+            allday_position,
+            reverse,
+            ..SortSpec::default()
+        };
+        assert_eq!(sorted(sort), expected, "{:?}", sort);
+    }
+}
+
+fn sourced(title: &str, path: &str, modified: u64) -> SourcedEvent {
+    SourcedEvent {
+        modified: Some(UNIX_EPOCH + Duration::from_secs(modified)),
+        ..SourcedEvent::new(timed(title, "09:00", "10:00"), path)
+    }
+}
+
+#[test]
+fn tiebreaks() {
+    let mut events = [
+        sourced("B", "a.md", 100),
+        sourced("C", "b.md", 300),
+        sourced("A", "c.md", 200),
+        // not at the same time, so it comes first whatever the tiebreak
+        SourcedEvent {
+            event: timed("D", "08:00", "09:00"),
+            ..sourced("D", "d.md", 0)
+        },
+    ];
+    for (tiebreak, expected) in [
+        (Tiebreak::Title, "DABC"),
+        (Tiebreak::Mtime, "DCAB"),
+        (Tiebreak::Path, "DBCA"),
+    ] {
+        let sort = SortSpec {
+            tiebreak,
+            ..SortSpec::default()
+        };
+        events.sort_by(|a, b| sort.compare_sourced(a, b));
+        let titles: String = events.iter().map(|sourced| sourced.event.title()).collect();
+        assert_eq!(titles, expected, "{:?}", tiebreak);
+    }
+}
+
+#[test]
+fn explained() {
+    let all_day = SourcedEvent {
+        event: Event::AllDay {
+            title: "Holiday".into(),
+            begin_date: day(),
+            end_date: day().succ_opt().unwrap(),
+            suppress_recurring: false,
+        },
+        ..sourced("Holiday", "holiday.md", 0)
+    };
+    let standup = sourced("Standup", "standup.md", 100);
+    let late = SourcedEvent {
+        event: timed("Planning", "22:00", "01:00"),
+        ..sourced("Planning", "planning.md", 200)
+    };
+    let review = sourced("Review", "review.md", 300);
+    let sort = SortSpec::default();
+    assert_eq!(
+        sort.explain(&all_day, &standup),
+        "Holiday < Standup: all-day events first"
+    );
+    assert_eq!(
+        sort.explain(&standup, &late),
+        "Standup < Planning: begin 09:00 < 22:00"
+    );
+    assert_eq!(
+        sort.explain(&standup, &review),
+        "Standup < Review: same time, by title"
+    );
+    assert_eq!(
+        sort.explain(&standup, &standup),
+        "Standup = Standup: tied, so in the order they were read"
+    );
+    let sort = SortSpec {
+        by: SortBy::End,
+        allday_position: AllDayPosition::Inline,
+        reverse: true,
+        tiebreak: Tiebreak::Mtime,
+    };
+    assert_eq!(
+        sort.explain(&late, &standup),
+        "Planning < Standup: end 01:00 (next day) > 10:00 (reversed)"
+    );
+    assert_eq!(
+        sort.explain(&standup, &all_day),
+        "Standup < Holiday: end 10:00 > 00:00 (all day) (reversed)"
+    );
+    assert_eq!(
+        sort.explain(&review, &standup),
+        "Review < Standup: same time, review.md edited more recently"
+    );
+    // every adjacent pair is explained by the key which decided it
+    let mut events = [&review, &late, &all_day, &standup];
+    let sort = SortSpec::default();
+    events.sort_by(|a, b| sort.compare_sourced(a, b));
+    for pair in events.windows(2) {
+        let key = sort.deciding_key(pair[0], pair[1]).unwrap();
+        assert_eq!(
+            sort.compare_by(key, pair[0], pair[1]),
+            std::cmp::Ordering::Less
+        );
+    }
+}
+
+#[test]
+fn tiebreak_by_editing() {
+    let vault = std::env::temp_dir().join(format!(
+        "obsidian-calendar-summary-tiebreak-{}",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&vault).unwrap();
+    for title in ["Planning", "Review"] {
+        std::fs::write(
+            vault.join(format!("{}.md", title)),
+            format!(
+                "---\ntitle: {}\nallDay: false\ndate: 2024-05-08\nstartTime: 14:00\nendTime: 15:00\n---\n",
+                title
+            ),
+        )
+        .unwrap();
+    }
+    let touch = |title: &str, secs: u64| {
+        std::fs::File::options()
+            .write(true)
+            .open(vault.join(format!("{}.md", title)))
+            .unwrap()
+            .set_modified(UNIX_EPOCH + Duration::from_secs(secs))
+            .unwrap();
+    };
+    let first = |tiebreak: &str| {
+        let output = Command::new(env!("CARGO_BIN_EXE_obsidian-calendar-summary"))
+            .args([
+                "--now",
+                "2024-05-08T10:00",
+                "--oneline",
+                "--tiebreak",
+                tiebreak,
+            ])
+            .arg(&vault)
+            .env("XDG_CONFIG_HOME", &vault)
+            .output()
+            .expect("failed to run obsidian-calendar-summary");
+        assert!(output.status.success(), "exited with {}", output.status);
+        let output = String::from_utf8(output.stdout).unwrap();
+        output.split(" | ").next().unwrap().to_string()
+    };
+    touch("Planning", 1_000_000);
+    touch("Review", 2_000_000);
+    assert!(first("mtime").contains("Review"));
+    // the title decides by default, however the notes were edited
+    assert!(first("title").contains("Planning"));
+    touch("Planning", 3_000_000);
+    assert!(first("mtime").contains("Planning"));
+    let _ = std::fs::remove_dir_all(&vault);
+}
@@ -0,0 +1,148 @@
+//! `--format status`: whether there's an event on, as one word
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use obsidian_calendar_summary::{
+    cli::SortBy,
+    event::{Event, SourcedEvent},
+    lang::Lang,
+    render::{classify, status, Fields, Status},
+};
+
+const SOON: i64 = 15;
+
+fn time(time: &str) -> NaiveTime {
+    NaiveTime::parse_from_str(time, "%H:%M").unwrap()
+}
+
+fn date() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 5, 8).unwrap()
+}
+
+fn sourced(event: Event) -> SourcedEvent {
+    SourcedEvent::new(event, "note.md")
+}
+
+fn timed(title: &str, begin: &str, end: &str, travel_minutes: Option<u32>) -> SourcedEvent {
+    sourced(Event::Once {
+        title: title.into(),
+        begin: time(begin),
+        end: time(end),
+        day: date(),
+        travel_minutes,
+        actual_end: None,
+    })
+}
+
+fn conference() -> SourcedEvent {
+    sourced(Event::AllDay {
+        title: "Conference".into(),
+        begin_date: date(),
+        end_date: date().succ_opt().unwrap(),
+        suppress_recurring: false,
+    })
+}
+
+/// The status at `now` and the title of the event which decided it
+fn classified(events: &[SourcedEvent], now: &str) -> (Status, Option<String>) {
+    let now = NaiveDateTime::new(date(), time(now));
+    let lang = Lang::english();
+    let fields: Vec<Fields> = events
+        .iter()
+        .map(|sourced| {
+            Fields::new(
+                sourced,
+                date(),
+                now,
+                SortBy::Start,
+                &lang,
+                false,
+                chrono::Duration::zero(),
+            )
+        })
+        .collect();
+    let (status, event) = classify(&fields, SOON);
+    (status, event.map(|fields| fields.title.to_string()))
+}
+
+#[test]
+fn classification() {
+    let events = [
+        conference(),
+        timed("Standup", "09:45", "10:15", None),
+        timed("Dentist", "14:00", "15:00", Some(30)),
+    ];
+    for (now, expected, title) in [
+        ("09:00", Status::Free, None),
+        // the threshold is inclusive, like the highlighting
+        ("09:30", Status::Soon, Some("Standup")),
+        ("09:45", Status::Busy, Some("Standup")),
+        // up to and including the minute it ends
+        ("10:15", Status::Busy, Some("Standup")),
+        // all-day events don't count
+        ("10:16", Status::Free, None),
+        // soon once it's nearly time to leave
+        ("13:14", Status::Free, None),
+        ("13:15", Status::Soon, Some("Dentist")),
+        ("13:50", Status::Soon, Some("Dentist")),
+        ("14:30", Status::Busy, Some("Dentist")),
+        ("16:00", Status::Free, None),
+    ] {
+        assert_eq!(
+            classified(&events, now),
+            (expected, title.map(String::from)),
+            "{}",
+            now
+        );
+    }
+}
+
+#[test]
+fn busy_beats_soon() {
+    let events = [
+        timed("Workshop", "09:00", "12:00", None),
+        timed("Lunch", "12:00", "13:00", None),
+    ];
+    assert_eq!(
+        classified(&events, "11:50"),
+        (Status::Busy, Some("Workshop".into()))
+    );
+}
+
+#[test]
+fn focus_blocks_dont_count() {
+    let mut focus = timed("Focus block", "09:00", "10:00", None);
+    focus.synthetic = true;
+    assert_eq!(classified(&[focus], "09:30"), (Status::Free, None));
+}
+
+#[test]
+fn words() {
+    assert_eq!(status(&[], SOON, true), "free");
+    let standup = timed("Standup", "09:45", "10:15", None);
+    let lang = Lang::english();
+    let now = NaiveDateTime::new(date(), time("10:00"));
+    let fields = [Fields::new(
+        &standup,
+        date(),
+        now,
+        SortBy::Start,
+        &lang,
+        false,
+        chrono::Duration::zero(),
+    )];
+    assert_eq!(status(&fields, SOON, false), "busy");
+    assert_eq!(status(&fields, SOON, true), "busy Standup");
+}
+
+#[test]
+fn hidden_events_dont_count() {
+    // shown with `--show-hidden`, but dimmed rather than making anyone busy
+    let mut shift = timed("Partner's shift", "09:00", "17:00", None);
+    shift.hidden = true;
+    let events = [shift, timed("Dentist", "14:00", "15:00", None)];
+    assert_eq!(classified(&events, "10:00"), (Status::Free, None));
+    assert_eq!(
+        classified(&events, "13:50"),
+        (Status::Soon, Some("Dentist".into()))
+    );
+}
@@ -0,0 +1,99 @@
+//! The colours events are highlighted with, for dark and light backgrounds
+
+use obsidian_calendar_summary::{
+    config::Config,
+    render::Role,
+    theme::{light_background, Colour, Palette, Theme},
+};
+
+#[test]
+fn auto() {
+    assert_eq!(light_background("15;0"), Some(false));
+    assert_eq!(light_background("0;15"), Some(true));
+    assert_eq!(light_background("0;7"), Some(true));
+    assert_eq!(light_background("15;8"), Some(false));
+    // rxvt puts "default" in the middle
+    assert_eq!(light_background("0;default;15"), Some(true));
+    assert_eq!(light_background("default"), None);
+    assert_eq!(Theme::Auto.resolve(Some("0;15")), Theme::Light);
+    assert_eq!(Theme::Auto.resolve(Some("15;0")), Theme::Dark);
+    // dark when there's nothing to go on
+    assert_eq!(Theme::Auto.resolve(Some("")), Theme::Dark);
+    assert_eq!(Theme::Auto.resolve(None), Theme::Dark);
+    // only auto looks at the background
+    assert_eq!(Theme::Dark.resolve(Some("0;15")), Theme::Dark);
+}
+
+#[test]
+fn colours() {
+    let dark = Palette::dark();
+    let styles = |palette: &Palette, role| {
+        palette
+            .colour(role)
+            .map(|colour| (colour.tmux(), colour.hex().to_string()))
+    };
+    assert_eq!(
+        styles(&dark, Role::Ended),
+        Some(("dim".into(), "#808080".into()))
+    );
+    assert_eq!(
+        styles(&dark, Role::Imminent),
+        Some(("fg=yellow".into(), "#ffff00".into()))
+    );
+    assert_eq!(styles(&dark, Role::Later), None);
+    // yellow and grey can't be read on white
+    let light = Palette::light();
+    assert_eq!(
+        styles(&light, Role::Imminent),
+        Some(("fg=#af5f00".into(), "#af5f00".into()))
+    );
+    assert_eq!(
+        styles(&light, Role::Ended),
+        Some(("fg=#6c6c6c".into(), "#6c6c6c".into()))
+    );
+    assert_eq!(Palette::for_theme(Theme::Light), light);
+}
+
+#[test]
+fn overrides() {
+    let config = Config::parse(
+        "color.ongoing = \"magenta\"\n[color]\nlater = \"#2E8FB8\"\nended = \"none\"\n",
+    )
+    .unwrap();
+    let palette = Palette::dark().with_overrides(&config).unwrap();
+    assert_eq!(
+        palette.colour(Role::Ongoing),
+        Some(&Colour::Named("magenta"))
+    );
+    assert_eq!(
+        palette.colour(Role::Later),
+        Some(&Colour::Hex("#2e8fb8".into()))
+    );
+    assert_eq!(palette.colour(Role::Ended), None);
+    // the rest are left alone
+    assert_eq!(palette.colour(Role::AllDay), Some(&Colour::Named("cyan")));
+}
+
+#[test]
+fn bad_overrides() {
+    for (config, message) in [
+        ("color.soon = \"red\"\n", "Unknown config key 'color.soon'"),
+        (
+            "color.ongoing = \"lime\"\n",
+            "Config key 'color.ongoing' should be a colour",
+        ),
+        (
+            "color.ongoing = \"#0f0\"\n",
+            "Config key 'color.ongoing' should be a colour",
+        ),
+        (
+            "color.ongoing = true\n",
+            "Config key 'color.ongoing' should be a colour",
+        ),
+    ] {
+        let error = Palette::dark()
+            .with_overrides(&Config::parse(config).unwrap())
+            .unwrap_err();
+        assert!(error.to_string().contains(message), "{}", error);
+    }
+}
@@ -0,0 +1,159 @@
+//! How `unfinished`, `--grace`, `--from`/`--until` and `--within` pick events by the times they
+//! begin and end on the day
+
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use obsidian_calendar_summary::{
+    calendar::Calendar,
+    event::{Event, SourcedEvent},
+    query::EventQuery,
+};
+
+fn date() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 5, 8).unwrap()
+}
+
+fn time(time: &str) -> NaiveTime {
+    NaiveTime::parse_from_str(time, "%H:%M").unwrap()
+}
+
+fn at(at: &str) -> NaiveDateTime {
+    date().and_time(time(at))
+}
+
+fn sourced(title: &str, event: Event) -> SourcedEvent {
+    SourcedEvent::new(event, format!("{}.md", title))
+}
+
+fn timed(title: &str, begin: &str, end: &str) -> SourcedEvent {
+    sourced(
+        title,
+        Event::Once {
+            title: title.into(),
+            begin: time(begin),
+            end: time(end),
+            day: date(),
+            travel_minutes: None,
+            actual_end: None,
+        },
+    )
+}
+
+fn calendar() -> Calendar {
+    Calendar {
+        events: vec![
+            sourced(
+                "Conference",
+                Event::AllDay {
+                    title: "Conference".into(),
+                    begin_date: date(),
+                    end_date: date().succ_opt().unwrap(),
+                    suppress_recurring: false,
+                },
+            ),
+            timed("Swim", "06:00", "07:00"),
+            timed("Standup", "09:00", "09:15"),
+            timed("Lunch", "12:00", "13:00"),
+            timed("Dinner", "19:00", "20:00"),
+            // ends on the next day
+            timed("Gig", "22:00", "01:00"),
+        ],
+        holidays: Vec::new(),
+        problems: Vec::new(),
+    }
+}
+
+fn titles(query: EventQuery) -> Vec<String> {
+    query
+        .apply(&calendar())
+        .iter()
+        .map(|sourced| sourced.event.title().to_string())
+        .collect()
+}
+
+#[test]
+fn unfinished() {
+    assert_eq!(
+        titles(EventQuery::today(at("12:30"))),
+        ["Conference", "Lunch", "Dinner", "Gig"]
+    );
+    // an event is over once its end has passed, not when it began
+    assert_eq!(
+        titles(EventQuery::today(at("13:00"))),
+        ["Conference", "Lunch", "Dinner", "Gig"]
+    );
+    assert_eq!(
+        titles(EventQuery::today(at("13:10")).grace(Duration::try_minutes(15).unwrap())),
+        ["Conference", "Lunch", "Dinner", "Gig"]
+    );
+    // the gig ends after midnight, so it's still on at the end of the day
+    assert_eq!(
+        titles(EventQuery::today(at("23:59"))),
+        ["Conference", "Gig"]
+    );
+}
+
+#[test]
+fn window() {
+    let query = || EventQuery::new().on_date(date()).at(at("08:00"));
+    assert_eq!(
+        titles(query().from(time("12:00"))),
+        ["Conference", "Lunch", "Dinner", "Gig"]
+    );
+    assert_eq!(
+        titles(query().until(time("12:00"))),
+        ["Conference", "Swim", "Standup", "Lunch"]
+    );
+    assert_eq!(
+        titles(query().from(time("09:15")).until(time("19:00"))),
+        ["Conference", "Lunch", "Dinner"]
+    );
+    // ongoing events are kept whatever the window
+    assert_eq!(
+        titles(
+            EventQuery::new()
+                .on_date(date())
+                .at(at("06:30"))
+                .from(time("12:00"))
+                .until(time("13:00"))
+        ),
+        ["Conference", "Swim", "Lunch"]
+    );
+}
+
+#[test]
+fn overnight_window() {
+    // a window going round midnight keeps both ends of the day, rather than nothing
+    assert_eq!(
+        titles(
+            EventQuery::new()
+                .on_date(date())
+                .at(at("08:00"))
+                .from(time("21:00"))
+                .until(time("06:30"))
+        ),
+        ["Conference", "Swim", "Gig"]
+    );
+    assert_eq!(
+        titles(
+            EventQuery::new()
+                .on_date(date())
+                .at(at("08:00"))
+                .from(time("19:30"))
+                .until(time("09:00"))
+        ),
+        ["Conference", "Swim", "Standup", "Dinner", "Gig"]
+    );
+}
+
+#[test]
+fn within() {
+    assert_eq!(
+        titles(EventQuery::today(at("08:00")).within(Duration::try_hours(1).unwrap())),
+        ["Conference", "Standup"]
+    );
+    // the gig began before midnight, so it has begun whenever the day is over
+    assert_eq!(
+        titles(EventQuery::today(at("22:30")).within(Duration::zero())),
+        ["Conference", "Gig"]
+    );
+}
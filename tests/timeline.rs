@@ -0,0 +1,116 @@
+//! `--timeline`: where events fall on the bar drawn across the day
+
+use chrono::{NaiveDate, NaiveTime};
+use obsidian_calendar_summary::{
+    event::{Event, SourcedEvent},
+    timeline::{parse_clock, timeline, Scale},
+};
+
+/// 08:00 to 18:00 in 40 columns, so 15 minutes each
+const SCALE: Scale = Scale {
+    day_start: 8 * 3600,
+    day_end: 18 * 3600,
+    width: 40,
+};
+
+fn clock(time: &str) -> i64 {
+    parse_clock(time).unwrap()
+}
+
+fn columns(begin: &str, end: &str) -> Option<std::ops::Range<usize>> {
+    SCALE.columns(clock(begin), clock(end))
+}
+
+#[test]
+fn column() {
+    assert_eq!(SCALE.column(clock("08:00")), 0);
+    assert_eq!(SCALE.column(clock("09:00")), 4);
+    assert_eq!(SCALE.column(clock("09:14")), 4);
+    assert_eq!(SCALE.column(clock("09:15")), 5);
+    assert_eq!(SCALE.column(clock("18:00")), 40);
+    assert_eq!(columns("09:00", "10:30"), Some(4..10));
+}
+
+#[test]
+fn clamped() {
+    // clipped to the edges of the timeline
+    assert_eq!(SCALE.column(clock("07:00")), 0);
+    assert_eq!(SCALE.column(clock("24:00")), 40);
+    assert_eq!(columns("07:00", "09:00"), Some(0..4));
+    assert_eq!(columns("17:00", "20:00"), Some(36..40));
+    assert_eq!(columns("06:00", "20:00"), Some(0..40));
+    // entirely outside it, including ending exactly as it starts
+    assert_eq!(columns("06:00", "08:00"), None);
+    assert_eq!(columns("18:00", "19:00"), None);
+    // too short for a column of its own, but still shown
+    assert_eq!(columns("09:00", "09:05"), Some(4..5));
+    assert_eq!(columns("17:55", "18:00"), Some(39..40));
+}
+
+fn meeting(title: &str, begin: &str, end: &str) -> SourcedEvent {
+    let time = |time: &str| NaiveTime::parse_from_str(time, "%H:%M").unwrap();
+    SourcedEvent::new(
+        Event::Once {
+            title: title.into(),
+            begin: time(begin),
+            end: time(end),
+            day: NaiveDate::from_ymd_opt(2024, 5, 8).unwrap(),
+            travel_minutes: None,
+            actual_end: None,
+        },
+        format!("{}.md", title),
+    )
+}
+
+#[test]
+fn overlapping() {
+    let events = [
+        meeting("Standup", "09:00", "09:30"),
+        meeting("Workshop", "09:15", "11:00"),
+        meeting("Lunch", "12:00", "13:00"),
+        meeting("Planning", "12:30", "13:30"),
+        meeting("Review", "13:00", "14:00"),
+    ];
+    let events: Vec<&SourcedEvent> = events.iter().collect();
+    // each goes on the first row with room for it
+    assert_eq!(
+        timeline(&events, SCALE, NaiveTime::from_hms_opt(9, 20, 0).unwrap()),
+        "08:00                              18:00\n\
+         ....aa..........cccceeee................\n\
+         .....bbbbbbb......dddd..................\n\
+         \x20    ^\n\
+         a  09:00 - 09:30  Standup\n\
+         b  09:15 - 11:00  Workshop\n\
+         c  12:00 - 13:00  Lunch\n\
+         d  12:30 - 13:30  Planning\n\
+         e  13:00 - 14:00  Review\n"
+    );
+}
+
+#[test]
+fn past_midnight() {
+    let night = [meeting("Night shift", "22:00", "02:00")];
+    let events: Vec<&SourcedEvent> = night.iter().collect();
+    // the whole day in 48 columns, so half an hour each
+    let day = Scale {
+        day_start: 0,
+        day_end: clock("24:00"),
+        width: 48,
+    };
+    assert_eq!(
+        timeline(&events, day, NaiveTime::from_hms_opt(12, 0, 0).unwrap()),
+        "00:00                                      24:00\n\
+         ............................................aaaa\n\
+         \x20                       ^\n\
+         a  22:00 - 02:00  Night shift\n"
+    );
+    // and clipped to the end of a shorter timeline, like any other event
+    let late = [meeting("Late deploy", "17:00", "01:00")];
+    let events: Vec<&SourcedEvent> = late.iter().collect();
+    assert_eq!(
+        timeline(&events, SCALE, NaiveTime::from_hms_opt(7, 0, 0).unwrap()),
+        "08:00                              18:00\n\
+         ....................................aaaa\n\
+         a  17:00 - 01:00  Late deploy\n"
+    );
+}
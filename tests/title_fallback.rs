@@ -0,0 +1,84 @@
+//! What an event is called when its note has no `title`
+
+use std::path::Path;
+
+use obsidian_calendar_summary::parse::{parse_cal_file_with_warnings, AllDayEnd, WeekdayLocale};
+
+const EVENT: &str = "allDay: false\ndate: 2024-05-03\nstartTime: 09:00\nendTime: 10:00\n";
+
+fn title(note: &str, frontmatter: &str, strip_date_prefix: bool) -> String {
+    let (events, _) = parse_cal_file_with_warnings(
+        &format!("---\n{}{}---\n", frontmatter, EVENT),
+        Path::new(note),
+        WeekdayLocale::English,
+        AllDayEnd::Exclusive,
+        None,
+        strip_date_prefix,
+        true,
+    )
+    .unwrap_or_else(|e| panic!("{}", e));
+    events[0].title().to_string()
+}
+
+#[test]
+fn explicit_title() {
+    assert_eq!(
+        title(
+            "Calendar/Note.md",
+            "title: Dentist\naliases: [Teeth]\n",
+            true
+        ),
+        "Dentist"
+    );
+}
+
+#[test]
+fn first_alias() {
+    assert_eq!(
+        title("Calendar/Note.md", "aliases: [Teeth, Checkup]\n", false),
+        "Teeth"
+    );
+    assert_eq!(
+        title(
+            "Calendar/Note.md",
+            "aliases:\n  - \"Teeth\"\n  - Checkup\n",
+            false
+        ),
+        "Teeth"
+    );
+    assert_eq!(
+        title("Calendar/Note.md", "aliases: Teeth\n", false),
+        "Teeth"
+    );
+}
+
+#[test]
+fn file_name() {
+    assert_eq!(title("Calendar/Dentist.md", "", false), "Dentist");
+    // an empty list of aliases is no alias at all
+    assert_eq!(
+        title("Calendar/Dentist.md", "aliases: []\n", false),
+        "Dentist"
+    );
+    assert_eq!(
+        title("Calendar/2024-05-03 Dentist.md", "", false),
+        "2024-05-03 Dentist"
+    );
+}
+
+#[test]
+fn date_prefix() {
+    assert_eq!(title("Calendar/2024-05-03 Dentist.md", "", true), "Dentist");
+    assert_eq!(title("Calendar/2024-05-03-Dentist.md", "", true), "Dentist");
+    // only dates are stripped, and a note named just after the day keeps its name
+    assert_eq!(
+        title("Calendar/2024-13-03 Dentist.md", "", true),
+        "2024-13-03 Dentist"
+    );
+    assert_eq!(title("Calendar/2024-05-03.md", "", true), "2024-05-03");
+    // aliases are used as they're written
+    assert_eq!(
+        title("Calendar/Note.md", "aliases: [2024-05-03 Dentist]\n", true),
+        "2024-05-03 Dentist"
+    );
+}
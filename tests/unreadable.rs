@@ -0,0 +1,92 @@
+//! Notes which can't be read are skipped with a warning rather than stopping everything
+
+use std::{
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+const NOTE: &str =
+    "---\ntitle: Dentist\nallDay: false\ndate: 2024-05-08\nstartTime: 14:00\nendTime: 15:00\n---\n";
+
+/// A calendar directory of its own, removed again when the test is done
+struct Vault(PathBuf);
+
+impl Vault {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!(
+            "obsidian-calendar-summary-{}-{}",
+            name,
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("Dentist.md"), NOTE).unwrap();
+        Vault(dir)
+    }
+
+    fn run(&self, args: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_obsidian-calendar-summary"))
+            .args(args)
+            .args(["--now", "2024-05-08T10:00"])
+            .arg(&self.0)
+            // keep the user's config file out of it
+            .env("XDG_CONFIG_HOME", &self.0)
+            .env("TZ", "UTC")
+            .output()
+            .expect("failed to run obsidian-calendar-summary")
+    }
+}
+
+impl Drop for Vault {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+fn check_skipped(vault: &Vault, note: &Path) {
+    let output = vault.run(&[]);
+    assert!(output.status.success(), "exited with {}", output.status);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stdout.contains("Dentist"), "{}", stdout);
+    assert!(
+        stderr.starts_with(&format!("Warning: skipping {}: ", note.display())),
+        "{}",
+        stderr
+    );
+    // --strict stops at it, like any other broken note
+    let output = vault.run(&["--strict"]);
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .starts_with("Error processing event files: "));
+    // and --retry-unreadable still gives up in the end
+    let output = vault.run(&["--retry-unreadable"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Warning: skipping"));
+}
+
+#[test]
+fn not_utf8() {
+    let vault = Vault::new("not-utf8");
+    let note = vault.0.join("Scan.md");
+    std::fs::write(&note, b"---\ntitle: \xff\xfe\n---\n").unwrap();
+    check_skipped(&vault, &note);
+}
+
+#[cfg(unix)]
+#[test]
+fn no_permission() {
+    use std::os::unix::fs::PermissionsExt;
+    let vault = Vault::new("no-permission");
+    let note = vault.0.join("Locked.md");
+    std::fs::write(&note, NOTE).unwrap();
+    std::fs::set_permissions(&note, std::fs::Permissions::from_mode(0o000)).unwrap();
+    // root can read it anyway
+    if std::fs::read(&note).is_ok() {
+        eprintln!("skipping: permissions aren't enforced for this user");
+        return;
+    }
+    check_skipped(&vault, &note);
+}
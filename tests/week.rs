@@ -0,0 +1,81 @@
+use chrono::{
+    NaiveDate,
+    Weekday::{self, *},
+};
+use obsidian_calendar_summary::{
+    lang::Lang,
+    week::{compress_days, day_heading, first_day_of_iso_week, parse_iso_week},
+};
+
+fn compress(days: &[Weekday]) -> String {
+    compress_days(days, &Lang::english())
+}
+
+#[test]
+fn day_ranges() {
+    assert_eq!(compress(&[Mon, Tue, Thu]), "Mon–Tue, Thu");
+    assert_eq!(compress(&[Mon, Tue, Wed, Thu, Fri]), "Mon–Fri");
+    assert_eq!(compress(&[Mon, Wed, Fri]), "Mon, Wed, Fri");
+    assert_eq!(compress(&[Tue]), "Tue");
+    assert_eq!(compress(&[]), "");
+    // a week starting on Sunday runs on into Monday
+    assert_eq!(compress(&[Sun, Mon, Tue, Sat]), "Sun–Tue, Sat");
+    // the whole week
+    assert_eq!(compress(&[Mon, Tue, Wed, Thu, Fri, Sat, Sun]), "Mon–Sun");
+    assert_eq!(
+        compress_days(&[Mon, Tue, Thu], &Lang::german()),
+        "Mo–Di, Do"
+    );
+}
+
+fn date(date: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap()
+}
+
+#[test]
+fn iso_weeks() {
+    assert_eq!(parse_iso_week("2024-W23").unwrap(), date("2024-06-03"));
+    // week 1 is the week with the year's first Thursday, so it can start in December
+    assert_eq!(parse_iso_week("2025-W01").unwrap(), date("2024-12-30"));
+    assert_eq!(parse_iso_week("2024-W01").unwrap(), date("2024-01-01"));
+    assert_eq!(parse_iso_week("2021-W01").unwrap(), date("2021-01-04"));
+    // and the first days of January can be in the last week of the year before
+    assert_eq!(parse_iso_week("2020-W53").unwrap(), date("2020-12-28"));
+    assert_eq!(parse_iso_week("2026-W53").unwrap(), date("2026-12-28"));
+    for week in ["2021-W53", "2024-W53", "2024-W00", "2024-W54"] {
+        assert_eq!(
+            parse_iso_week(week).unwrap_err().code(),
+            "usage",
+            "{}",
+            week
+        );
+    }
+    for week in ["2024-23", "2024W23", "W23", "2024-Wxx", ""] {
+        assert!(parse_iso_week(week).is_err(), "{}", week);
+    }
+}
+
+#[test]
+fn first_day() {
+    let monday = parse_iso_week("2025-W01").unwrap();
+    assert_eq!(first_day_of_iso_week(monday, Mon), date("2024-12-30"));
+    // weeks starting on Sunday start the day before the ISO week
+    assert_eq!(first_day_of_iso_week(monday, Sun), date("2024-12-29"));
+}
+
+#[test]
+fn headings_around_new_year() {
+    let lang = Lang::english();
+    assert_eq!(
+        day_heading(date("2024-12-31"), &lang),
+        "Tue 31 Dec 2024 · 2025-W01"
+    );
+    assert_eq!(
+        day_heading(date("2021-01-01"), &lang),
+        "Fri 01 Jan 2021 · 2020-W53"
+    );
+    assert_eq!(
+        day_heading(date("2021-01-04"), &lang),
+        "Mon 04 Jan 2021 · W01"
+    );
+}
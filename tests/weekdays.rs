@@ -0,0 +1,140 @@
+//! The days in `daysOfWeek`: Full Calendar's letters, where Thursday is `R` and Sunday is `U`,
+//! and the two-letter abbreviations other tools write
+
+use chrono::Weekday;
+use obsidian_calendar_summary::{
+    error::Error,
+    event::Event,
+    parse::{parse_cal_file_with_warnings, AllDayEnd, WeekdayLocale},
+};
+use std::path::Path;
+
+/// The days of a recurring event with `days` as its `daysOfWeek`, and its warnings
+fn parse(days: &str, locale: WeekdayLocale) -> Result<(Vec<Weekday>, Vec<String>), Error> {
+    let (mut events, warnings) = parse_cal_file_with_warnings(
+        &format!(
+            "---\ntitle: Gym\ntype: recurring\ndaysOfWeek: {}\nstartTime: 18:00\nendTime: 19:00\n---\n",
+            days
+        ),
+        Path::new("note.md"),
+        locale,
+        AllDayEnd::Exclusive,
+        None,
+        false,
+        true,
+    )?;
+    let Event::Recurring { recur_days, .. } = events.remove(0) else {
+        panic!("{} isn't recurring", days);
+    };
+    for warning in &warnings {
+        assert_eq!(warning.code(), "ambiguous_weekday");
+        assert!(warning.is_warning());
+    }
+    Ok((
+        recur_days,
+        warnings.iter().map(ToString::to_string).collect(),
+    ))
+}
+
+fn days(days: &str) -> Vec<Weekday> {
+    parse(days, WeekdayLocale::English).unwrap().0
+}
+
+#[test]
+fn letters_and_abbreviations() {
+    use Weekday::*;
+    assert_eq!(
+        days("[M, T, W, R, F, S, U]"),
+        [Mon, Tue, Wed, Thu, Fri, Sat, Sun]
+    );
+    assert_eq!(
+        days("[Mo, Tu, We, Th, Fr, Sa, Su]"),
+        [Mon, Tue, Wed, Thu, Fri, Sat, Sun]
+    );
+    assert_eq!(days("[M, Th, Su]"), [Mon, Thu, Sun]);
+    // whatever the locale
+    assert_eq!(
+        parse("[M, Th, Di]", WeekdayLocale::German).unwrap().0,
+        [Mon, Thu, Tue]
+    );
+}
+
+#[test]
+fn lone_s() {
+    assert_eq!(
+        parse("[M, S]", WeekdayLocale::English).unwrap(),
+        (
+            vec![Weekday::Mon, Weekday::Sat],
+            vec![
+                "note.md: 'daysOfWeek': 'S' is read as Saturday, and Sunday is 'U' (write 'Sa' or 'Su' to be sure)"
+                    .to_string()
+            ]
+        )
+    );
+    // it can't be Sunday when Sunday is there too
+    for days in ["[S, U]", "[S, Su]", "[Sa]"] {
+        assert_eq!(parse(days, WeekdayLocale::English).unwrap().1, [""; 0]);
+    }
+}
+
+#[test]
+fn unknown() {
+    let error = parse("[M, Sun]", WeekdayLocale::English).unwrap_err();
+    assert_eq!(error.code(), "bad_weekday");
+    assert_eq!(
+        error.to_string(),
+        "note.md: 'daysOfWeek': unknown weekday 'Sun' (expected M/Mo for Monday, T/Tu for Tuesday, W/We for Wednesday, R/Th for Thursday, F/Fr for Friday, S/Sa for Saturday, U/Su for Sunday)"
+    );
+    // the locale's are listed too, once each
+    assert!(parse("[M, Sun]", WeekdayLocale::German)
+        .unwrap_err()
+        .to_string()
+        .contains("M/Mo for Monday, T/Tu/Di for Tuesday,"));
+}
+
+/// The days and times of the recurring event in `tests/fixtures/block_lists/<name>.md`
+fn block_list(name: &str) -> Result<(Vec<Weekday>, String), Error> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/block_lists")
+        .join(format!("{}.md", name));
+    let (mut events, _) = parse_cal_file_with_warnings(
+        &std::fs::read_to_string(&path).unwrap(),
+        &path,
+        WeekdayLocale::English,
+        AllDayEnd::Exclusive,
+        None,
+        false,
+        true,
+    )?;
+    let Event::Recurring {
+        recur_days,
+        begin,
+        end,
+        ..
+    } = events.remove(0)
+    else {
+        panic!("{} isn't recurring", name);
+    };
+    Ok((recur_days, format!("{} - {}", begin, end)))
+}
+
+#[test]
+fn block_lists() {
+    use Weekday::*;
+    let times = "18:00:00 - 19:00:00".to_string();
+    assert_eq!(block_list("Tabs").unwrap(), (vec![Mon, Wed], times.clone()));
+    // quotes and comments around the items
+    assert_eq!(
+        block_list("Quoted").unwrap(),
+        (vec![Mon, Thu, Sun], times.clone())
+    );
+    // the list ends at the next key, which is still read
+    assert_eq!(block_list("Followed").unwrap(), (vec![Tue, Fri], times));
+    // a dash at the start of the line isn't part of the list, so it's a broken line
+    let error = block_list("Unindented").unwrap_err().to_string();
+    assert!(
+        error.ends_with("expected 'key: value', found '- not an item'"),
+        "{}",
+        error
+    );
+}